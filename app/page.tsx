@@ -1,13 +1,14 @@
 'use client';
 
 import { useState } from 'react';
-import { Plus, FileText, Download } from 'lucide-react';
+import { Plus, FileText, Download, ClipboardCheck } from 'lucide-react';
 import { Button } from '@/components/ui/button';
 import { ReportList } from '@/components/report-list';
 import { ReportForm } from '@/components/report-form';
 import { AppSettings } from '@/components/app-settings';
 import { ConfigManagement } from '@/components/config-management';
 import { VulnDBForm } from '@/components/vuln-db-form';
+import { UnlockScreen } from '@/components/unlock-screen';
 import { useReport } from '@/contexts/ReportContext';
 import { useNavigation } from '@/contexts/NavigationContext';
 import { RiskReportData } from '@/lib/types';
@@ -17,6 +18,7 @@ export default function HomePage() {
   const { 
     currentProject, 
     isLoading, 
+    vaultStatus,
     generateReport,
     addReport,
     updateReport 
@@ -71,6 +73,10 @@ export default function HomePage() {
     );
   }
 
+  if (vaultStatus?.locked) {
+    return <UnlockScreen />;
+  }
+
   // 根据当前的视图模式显示不同的内容
   switch (viewMode) {
     case 'report-form':
@@ -118,13 +124,21 @@ export default function HomePage() {
                 新增报告
               </Button>
               <Button 
-                onClick={generateReport}
+                onClick={() => generateReport()}
                 variant="outline"
                 disabled={!currentProject.reportList.length}
               >
                 <Download className="h-4 w-4 mr-2" />
                 生成报告
               </Button>
+              <Button
+                onClick={() => generateReport('retest')}
+                variant="outline"
+                disabled={!currentProject.reportList.length}
+              >
+                <ClipboardCheck className="h-4 w-4 mr-2" />
+                生成复测报告
+              </Button>
             </div>
           </div>
           <ReportList 
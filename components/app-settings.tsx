@@ -1,6 +1,8 @@
 'use client';
 
+import { useEffect, useState } from 'react';
 import { Button } from '@/components/ui/button';
+import { Input } from '@/components/ui/input';
 import { Card, CardContent, CardHeader, CardTitle } from '@/components/ui/card';
 import { Separator } from '@/components/ui/separator';
 import { Label } from '@/components/ui/label';
@@ -9,9 +11,13 @@ import {
   Database, 
   RefreshCw, 
   Monitor,
-  HardDrive
+  HardDrive,
+  User
 } from 'lucide-react';
 import { useReport } from '@/contexts/ReportContext';
+import { EncryptionSettings } from '@/components/encryption-settings';
+import { BackupSettings } from '@/components/backup-settings';
+import { ReportSettings } from '@/components/report-settings';
 import { useTheme } from 'next-themes';
 import { toast } from 'sonner';
 import { useSettings } from '@/hooks/useSettings';
@@ -24,6 +30,11 @@ export function AppSettings() {
   const { theme, setTheme } = useTheme();
   const settings = useSettings();
   const vulnDB = useVulnDB();
+  const [authorName, setAuthorName] = useState('');
+
+  useEffect(() => {
+    setAuthorName(settings.settings.authorName ?? '');
+  }, [settings.settings.authorName]);
   const getTotalReports = () => {
     return projects.reduce((total, project) => total + project.reportList.length, 0);
   };
@@ -85,6 +96,32 @@ export function AppSettings() {
         </CardContent>
       </Card>
 
+      {/* 用户信息 */}
+      <Card>
+        <CardHeader>
+          <CardTitle className="flex items-center gap-2">
+            <User className="h-5 w-5" />
+            用户信息
+          </CardTitle>
+        </CardHeader>
+        <CardContent>
+          <div className="flex items-end gap-2">
+            <div className="flex-1 space-y-1">
+              <Label htmlFor="author-name" className="text-sm font-medium">用户名</Label>
+              <Input
+                id="author-name"
+                value={authorName}
+                onChange={(e) => setAuthorName(e.target.value)}
+                placeholder="记录在报告修改历史中的修改人"
+              />
+            </div>
+            <Button variant="outline" onClick={() => settings.setAuthorName(authorName)}>
+              保存
+            </Button>
+          </div>
+        </CardContent>
+      </Card>
+
       {/* 数据统计 */}
       <Card>
         <CardHeader>
@@ -113,6 +150,9 @@ export function AppSettings() {
         </CardContent>
       </Card>
 
+      {/* 报告生成 */}
+      <ReportSettings />
+
       {/* 数据管理 */}
       <Card>
         <CardHeader>
@@ -154,6 +194,12 @@ export function AppSettings() {
         </CardContent>
       </Card>
 
+      {/* 数据备份 */}
+      <BackupSettings />
+
+      {/* 数据加密 */}
+      <EncryptionSettings />
+
       {/* 版本信息 */}
       <Card>
         <CardHeader>
@@ -1,7 +1,7 @@
 'use client';
 
 import { useState } from 'react';
-import { Plus, Trash2, Folder, Download, Settings, Database, Edit2, Check, X, FileCog } from 'lucide-react';
+import { Plus, Trash2, Folder, Download, Settings, Database, Edit2, Check, X, FileCog, FileDown, FileUp, Lock, GitCompare, ListPlus } from 'lucide-react';
 import {
   Sidebar,
   SidebarContent,
@@ -22,8 +22,12 @@ import {
 } from '@/components/ui/context-menu';
 import { Button } from '@/components/ui/button';
 import { Input } from '@/components/ui/input';
+import { PasswordDialog } from '@/components/password-dialog';
+import { ProjectDiffDialog } from '@/components/project-diff';
+import { CustomFieldsDialog } from '@/components/custom-fields-dialog';
 import { useReport } from '@/contexts/ReportContext';
 import { useNavigation } from '@/contexts/NavigationContext';
+import { ArchiveFile } from '@/lib/types';
 
 export function AppSidebar() {
   const { viewMode, currentView, navigateToProject, navigateToAppSettings, navigateToConfigManagement, navigateToVulnDB } = useNavigation();
@@ -33,13 +37,49 @@ export function AppSidebar() {
     createProject, 
     deleteProject,
     renameProject,
-    loadDemoData 
+    loadDemoData,
+    exportProject,
+    selectArchive,
+    importProject,
+    vaultStatus,
+    lockVault
   } = useReport();
   
   const [showCreateForm, setShowCreateForm] = useState(false);
   const [newProjectName, setNewProjectName] = useState('');
   const [editingProject, setEditingProject] = useState<string | null>(null);
   const [editingName, setEditingName] = useState('');
+  // 导出时可选设置密码，导入加密归档时需要输入密码
+  const [exportingProject, setExportingProject] = useState<string | null>(null);
+  const [importingArchive, setImportingArchive] = useState<ArchiveFile | null>(null);
+  const [comparingProject, setComparingProject] = useState<string | null>(null);
+  const [fieldsProject, setFieldsProject] = useState<string | null>(null);
+
+  const handleExport = async (password: string) => {
+    const projectName = exportingProject;
+    setExportingProject(null);
+    if (projectName) {
+      await exportProject(projectName, password || undefined);
+    }
+  };
+
+  const handleSelectArchive = async () => {
+    const archive = await selectArchive();
+    if (!archive) return;
+    if (archive.encrypted) {
+      setImportingArchive(archive);
+    } else {
+      await importProject(archive);
+    }
+  };
+
+  const handleImport = async (password: string) => {
+    const archive = importingArchive;
+    setImportingArchive(null);
+    if (archive) {
+      await importProject(archive, password);
+    }
+  };
 
   const handleCreateProject = async (e: React.FormEvent) => {
     e.preventDefault();
@@ -82,6 +122,26 @@ export function AppSidebar() {
       <SidebarHeader className="flex flex-row items-center justify-between px-4 py-0 mt-4" data-tauri-drag-region>
         <h2 className="text-lg font-semibold select-none" data-tauri-drag-region>风险隐患报告生成</h2>
         <div className="flex gap-2">
+          {vaultStatus?.encrypted && !vaultStatus.locked && (
+            <Button
+              variant="ghost"
+              size="icon"
+              onClick={lockVault}
+              className="h-8 w-8"
+              title="锁定"
+            >
+              <Lock className="h-4 w-4" />
+            </Button>
+          )}
+          <Button
+            variant="ghost"
+            size="icon"
+            onClick={handleSelectArchive}
+            className="h-8 w-8"
+            title="导入项目"
+          >
+            <FileUp className="h-4 w-4" />
+          </Button>
           <Button
             variant="ghost"
             size="icon"
@@ -183,6 +243,18 @@ export function AppSidebar() {
                           <Edit2 className="h-4 w-4 mr-2" />
                           重命名项目
                         </ContextMenuItem>
+                        <ContextMenuItem onClick={() => setExportingProject(project.projectName)}>
+                          <FileDown className="h-4 w-4 mr-2" />
+                          导出项目
+                        </ContextMenuItem>
+                        <ContextMenuItem onClick={() => setComparingProject(project.projectName)}>
+                          <GitCompare className="h-4 w-4 mr-2" />
+                          对比项目
+                        </ContextMenuItem>
+                        <ContextMenuItem onClick={() => setFieldsProject(project.projectName)}>
+                          <ListPlus className="h-4 w-4 mr-2" />
+                          自定义字段
+                        </ContextMenuItem>
                         <ContextMenuItem 
                           onClick={() => deleteProject(project.projectName)}
                           className="text-destructive focus:text-destructive"
@@ -237,7 +309,7 @@ export function AppSidebar() {
           </SidebarGroupContent>
         </SidebarGroup>
       </SidebarContent>
-      {projects.length===0 && <SidebarFooter className="p-4">
+      {projects.length===0 && !vaultStatus?.locked && <SidebarFooter className="p-4">
         <Button 
           onClick={loadDemoData}
           variant="outline" 
@@ -247,7 +319,31 @@ export function AppSidebar() {
           加载演示数据
         </Button>
       </SidebarFooter>}
-      
+
+      <PasswordDialog
+        open={exportingProject !== null}
+        title="导出项目"
+        description="设置密码后归档文件将被加密，导入时需要输入相同的密码"
+        optional
+        confirm
+        onSubmit={handleExport}
+        onCancel={() => setExportingProject(null)}
+      />
+      <PasswordDialog
+        open={importingArchive !== null}
+        title="导入加密归档"
+        description="该归档文件已加密，请输入导出时设置的密码"
+        onSubmit={handleImport}
+        onCancel={() => setImportingArchive(null)}
+      />
+      <ProjectDiffDialog
+        projectName={comparingProject}
+        onClose={() => setComparingProject(null)}
+      />
+      <CustomFieldsDialog
+        projectName={fieldsProject}
+        onClose={() => setFieldsProject(null)}
+      />
     </Sidebar>
   );
 } 
\ No newline at end of file
@@ -0,0 +1,203 @@
+'use client';
+
+import { useCallback, useEffect, useState } from 'react';
+import { invoke } from '@tauri-apps/api/core';
+import { toast } from 'sonner';
+import { Archive, RotateCcw, Save } from 'lucide-react';
+import { Button } from '@/components/ui/button';
+import { Card, CardContent, CardHeader, CardTitle } from '@/components/ui/card';
+import { Input } from '@/components/ui/input';
+import { Label } from '@/components/ui/label';
+import { Separator } from '@/components/ui/separator';
+import { Switch } from '@/components/ui/switch';
+import {
+  Dialog,
+  DialogContent,
+  DialogDescription,
+  DialogFooter,
+  DialogHeader,
+  DialogTitle,
+} from '@/components/ui/dialog';
+import { useReport } from '@/contexts/ReportContext';
+import { BackupConfig, BackupInfo, BackupReason } from '@/lib/types';
+
+const REASON_LABELS: Record<BackupReason, string> = {
+  scheduled: '定时备份',
+  manual: '手动备份',
+  before_delete: '删除项目前',
+  before_import: '导入项目前',
+  before_restore: '恢复备份前',
+};
+
+const formatSize = (size: number) =>
+  size < 1024 * 1024 ? `${(size / 1024).toFixed(1)} KB` : `${(size / 1024 / 1024).toFixed(1)} MB`;
+
+// 项目数据备份设置及备份列表
+export function BackupSettings() {
+  const { restoreBackup, vaultStatus } = useReport();
+  const [config, setConfig] = useState<BackupConfig | null>(null);
+  const [backups, setBackups] = useState<BackupInfo[]>([]);
+  const [restoring, setRestoring] = useState<BackupInfo | null>(null);
+  const [isWorking, setIsWorking] = useState(false);
+
+  const loadBackups = useCallback(async () => {
+    try {
+      setBackups(await invoke<BackupInfo[]>('list_backups'));
+    } catch (error) {
+      toast.error(`读取备份列表失败: ${error}`);
+    }
+  }, []);
+
+  useEffect(() => {
+    invoke<BackupConfig>('backup_config').then(setConfig);
+    loadBackups();
+  }, [loadBackups]);
+
+  const handleSaveConfig = async () => {
+    if (!config) return;
+    try {
+      await invoke('set_backup_config', { config });
+      await loadBackups();
+      toast.success('备份设置已保存');
+    } catch (error) {
+      toast.error(`保存备份设置失败: ${error}`);
+    }
+  };
+
+  const handleCreate = async () => {
+    setIsWorking(true);
+    try {
+      await invoke('create_backup');
+      await loadBackups();
+      toast.success('备份创建成功');
+    } catch (error) {
+      toast.error(`创建备份失败: ${error}`);
+    } finally {
+      setIsWorking(false);
+    }
+  };
+
+  // 备份与当前数据的加密设置不同，恢复后加密状态会随备份改变
+  const encryptionChanges = restoring !== null && restoring.encrypted !== Boolean(vaultStatus?.encrypted);
+
+  const handleRestore = async () => {
+    const backup = restoring;
+    setRestoring(null);
+    if (!backup) return;
+
+    setIsWorking(true);
+    await restoreBackup(backup.id, encryptionChanges);
+    await loadBackups();
+    setIsWorking(false);
+  };
+
+  return (
+    <Card>
+      <CardHeader>
+        <CardTitle className="flex items-center gap-2">
+          <Archive className="h-5 w-5" />
+          数据备份
+        </CardTitle>
+      </CardHeader>
+      <CardContent className="space-y-4">
+        {config && (
+          <div className="flex flex-wrap items-end gap-4">
+            <div className="flex items-center gap-2 pb-2">
+              <Switch
+                id="backup-enabled"
+                checked={config.enabled}
+                onCheckedChange={(enabled) => setConfig({ ...config, enabled })}
+              />
+              <Label htmlFor="backup-enabled" className="text-sm font-medium">定时备份</Label>
+            </div>
+            <div className="space-y-1">
+              <Label htmlFor="backup-interval" className="text-sm font-medium">间隔（分钟）</Label>
+              <Input
+                id="backup-interval"
+                type="number"
+                min={1}
+                value={config.interval_minutes}
+                onChange={(e) => setConfig({ ...config, interval_minutes: Math.max(1, parseInt(e.target.value) || 1) })}
+                className="w-28"
+              />
+            </div>
+            <div className="space-y-1">
+              <Label htmlFor="backup-versions" className="text-sm font-medium">保留数量</Label>
+              <Input
+                id="backup-versions"
+                type="number"
+                min={1}
+                value={config.max_versions}
+                onChange={(e) => setConfig({ ...config, max_versions: Math.max(1, parseInt(e.target.value) || 1) })}
+                className="w-28"
+              />
+            </div>
+            <Button variant="outline" onClick={handleSaveConfig}>
+              <Save className="h-4 w-4 mr-2" />
+              保存
+            </Button>
+            <Button onClick={handleCreate} disabled={isWorking}>
+              <Archive className="h-4 w-4 mr-2" />
+              立即备份
+            </Button>
+          </div>
+        )}
+        <p className="text-sm text-muted-foreground">
+          数据有变化时按间隔自动备份，删除项目和导入项目前也会自动备份，超出保留数量后删除最旧的备份
+        </p>
+        <Separator />
+        {backups.length === 0 ? (
+          <p className="text-sm text-muted-foreground">暂无备份</p>
+        ) : (
+          <div className="space-y-2 max-h-72 overflow-y-auto">
+            {backups.map((backup) => (
+              <div key={backup.id} className="flex items-center justify-between p-2 border rounded-lg">
+                <div className="text-sm">
+                  <span className="font-medium">{new Date(backup.created_at).toLocaleString('zh-CN')}</span>
+                  <span className="ml-2 text-muted-foreground">
+                    {REASON_LABELS[backup.reason]} · {formatSize(backup.size)}
+                    {backup.encrypted && ' · 已加密'}
+                  </span>
+                </div>
+                <Button
+                  variant="ghost"
+                  size="sm"
+                  onClick={() => setRestoring(backup)}
+                  disabled={isWorking}
+                >
+                  <RotateCcw className="h-4 w-4 mr-2" />
+                  恢复
+                </Button>
+              </div>
+            ))}
+          </div>
+        )}
+      </CardContent>
+      <Dialog open={restoring !== null} onOpenChange={(open: boolean) => !open && setRestoring(null)}>
+        <DialogContent>
+          <DialogHeader>
+            <DialogTitle>恢复备份</DialogTitle>
+            <DialogDescription>
+              {restoring && `将所有项目数据恢复到 ${new Date(restoring.created_at).toLocaleString('zh-CN')} 的状态，恢复前的数据会自动备份。`}
+            </DialogDescription>
+            {encryptionChanges && (
+              <p className="text-sm text-destructive">
+                {restoring?.encrypted
+                  ? '该备份已加密而当前数据未加密，恢复后将启用加密，需要使用备份时的密码解锁。'
+                  : '该备份未加密，恢复后将关闭项目数据加密，数据以明文保存。'}
+              </p>
+            )}
+          </DialogHeader>
+          <DialogFooter>
+            <Button variant="outline" onClick={() => setRestoring(null)}>
+              取消
+            </Button>
+            <Button variant={encryptionChanges ? 'destructive' : 'default'} onClick={handleRestore}>
+              {encryptionChanges ? '确认恢复' : '恢复'}
+            </Button>
+          </DialogFooter>
+        </DialogContent>
+      </Dialog>
+    </Card>
+  );
+}
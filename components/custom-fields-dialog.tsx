@@ -0,0 +1,137 @@
+'use client';
+
+import { useEffect, useState } from 'react';
+import { Plus, Trash2 } from 'lucide-react';
+import { Button } from '@/components/ui/button';
+import { Input } from '@/components/ui/input';
+import { Label } from '@/components/ui/label';
+import { Switch } from '@/components/ui/switch';
+import {
+  Select,
+  SelectContent,
+  SelectItem,
+  SelectTrigger,
+  SelectValue,
+} from '@/components/ui/select';
+import {
+  Dialog,
+  DialogContent,
+  DialogDescription,
+  DialogFooter,
+  DialogHeader,
+  DialogTitle,
+} from '@/components/ui/dialog';
+import { useReport } from '@/contexts/ReportContext';
+import { CUSTOM_FIELD_TYPE_LABELS, CustomFieldDef, CustomFieldType } from '@/lib/types';
+
+interface CustomFieldsDialogProps {
+  projectName: string | null;
+  onClose: () => void;
+}
+
+// 编辑项目声明的自定义字段，字段会显示在报告表单和生成的文档中
+export function CustomFieldsDialog({ projectName, onClose }: CustomFieldsDialogProps) {
+  const { projects, setCustomFields } = useReport();
+  const [fields, setFields] = useState<CustomFieldDef[]>([]);
+
+  useEffect(() => {
+    if (projectName) {
+      const project = projects.find((p) => p.projectName === projectName);
+      setFields(project?.customFields ?? []);
+    }
+    // 只在打开对话框时读取一次
+    // eslint-disable-next-line react-hooks/exhaustive-deps
+  }, [projectName]);
+
+  const names = fields.map((field) => field.name.trim());
+  const invalid = names.some((name, index) => !name || names.indexOf(name) !== index);
+
+  const updateField = (index: number, changes: Partial<CustomFieldDef>) => {
+    setFields((prev) => prev.map((field, i) => (i === index ? { ...field, ...changes } : field)));
+  };
+
+  const handleSave = async () => {
+    if (!projectName || invalid) return;
+    const trimmed = fields.map((field) => ({ ...field, name: field.name.trim() }));
+    if (await setCustomFields(projectName, trimmed)) {
+      onClose();
+    }
+  };
+
+  return (
+    <Dialog open={projectName !== null} onOpenChange={(open: boolean) => !open && onClose()}>
+      <DialogContent className="max-w-2xl">
+        <DialogHeader>
+          <DialogTitle>自定义字段</DialogTitle>
+          <DialogDescription>
+            为项目 {projectName} 的报告添加额外字段，如系统名称、端口、CVE编号等
+          </DialogDescription>
+        </DialogHeader>
+        <div className="space-y-2 max-h-[60vh] overflow-y-auto">
+          {fields.length === 0 && (
+            <p className="text-sm text-muted-foreground py-4 text-center">暂无自定义字段</p>
+          )}
+          {fields.map((field, index) => (
+            <div key={index} className="flex items-center gap-2">
+              <Input
+                value={field.name}
+                onChange={(e) => updateField(index, { name: e.target.value })}
+                placeholder="字段名称"
+                className="flex-1"
+              />
+              <Select
+                value={field.type}
+                onValueChange={(value) => updateField(index, { type: value as CustomFieldType })}
+              >
+                <SelectTrigger className="w-32">
+                  <SelectValue />
+                </SelectTrigger>
+                <SelectContent>
+                  {(Object.keys(CUSTOM_FIELD_TYPE_LABELS) as CustomFieldType[]).map((type) => (
+                    <SelectItem key={type} value={type}>{CUSTOM_FIELD_TYPE_LABELS[type]}</SelectItem>
+                  ))}
+                </SelectContent>
+              </Select>
+              <div className="flex items-center gap-1">
+                <Switch
+                  id={`custom-field-required-${index}`}
+                  checked={field.required}
+                  onCheckedChange={(required) => updateField(index, { required })}
+                />
+                <Label htmlFor={`custom-field-required-${index}`} className="text-sm">必填</Label>
+              </div>
+              <Button
+                variant="ghost"
+                size="icon"
+                onClick={() => setFields((prev) => prev.filter((_, i) => i !== index))}
+                className="text-destructive hover:text-destructive"
+              >
+                <Trash2 className="h-4 w-4" />
+              </Button>
+            </div>
+          ))}
+          {invalid && (
+            <p className="text-sm text-destructive">字段名称不能为空或重复</p>
+          )}
+        </div>
+        <DialogFooter className="sm:justify-between">
+          <Button
+            variant="outline"
+            onClick={() => setFields((prev) => [...prev, { name: '', type: 'text', required: false }])}
+          >
+            <Plus className="h-4 w-4 mr-2" />
+            添加字段
+          </Button>
+          <div className="flex gap-2">
+            <Button variant="outline" onClick={onClose}>
+              取消
+            </Button>
+            <Button onClick={handleSave} disabled={invalid}>
+              保存
+            </Button>
+          </div>
+        </DialogFooter>
+      </DialogContent>
+    </Dialog>
+  );
+}
@@ -0,0 +1,205 @@
+'use client';
+
+import { useEffect, useState } from 'react';
+import { invoke } from '@tauri-apps/api/core';
+import { toast } from 'sonner';
+import { Lock, ShieldCheck } from 'lucide-react';
+import { Button } from '@/components/ui/button';
+import { Card, CardContent, CardHeader, CardTitle } from '@/components/ui/card';
+import { Input } from '@/components/ui/input';
+import { Label } from '@/components/ui/label';
+import { Separator } from '@/components/ui/separator';
+import { PasswordDialog } from '@/components/password-dialog';
+import { useReport } from '@/contexts/ReportContext';
+
+// 默认无操作 15 分钟后自动锁定
+const DEFAULT_AUTO_LOCK_MINUTES = 15;
+
+// 项目数据加密设置
+export function EncryptionSettings() {
+  const { vaultStatus, refreshVaultStatus, lockVault } = useReport();
+  const [password, setPassword] = useState('');
+  const [confirmation, setConfirmation] = useState('');
+  const [oldPassword, setOldPassword] = useState('');
+  const [newPassword, setNewPassword] = useState('');
+  const [autoLockMinutes, setAutoLockMinutes] = useState(DEFAULT_AUTO_LOCK_MINUTES);
+  const [showDisableDialog, setShowDisableDialog] = useState(false);
+  const [isWorking, setIsWorking] = useState(false);
+
+  useEffect(() => {
+    if (vaultStatus?.encrypted) {
+      setAutoLockMinutes(vaultStatus.autoLockMinutes);
+    }
+  }, [vaultStatus]);
+
+  // 加解密需要重写全部项目数据，执行期间禁用操作。
+  // 加密完成后删除旧备份失败时也会返回错误，因此无论成功与否都刷新加密状态
+  const run = async (action: () => Promise<unknown>, message: string) => {
+    setIsWorking(true);
+    try {
+      await action();
+      toast.success(message);
+      return true;
+    } catch (error) {
+      toast.error(`${error}`);
+      return false;
+    } finally {
+      await refreshVaultStatus();
+      setIsWorking(false);
+    }
+  };
+
+  const handleEnable = async () => {
+    const success = await run(
+      () => invoke('enable_encryption', { password, autoLockMinutes }),
+      '项目数据加密已启用'
+    );
+    if (success) {
+      setPassword('');
+      setConfirmation('');
+    }
+  };
+
+  const handleDisable = async (password: string) => {
+    setShowDisableDialog(false);
+    await run(() => invoke('disable_encryption', { password }), '项目数据加密已关闭');
+  };
+
+  const handleChangePassword = async () => {
+    const success = await run(
+      () => invoke('change_password', { oldPassword, newPassword }),
+      '密码修改成功'
+    );
+    if (success) {
+      setOldPassword('');
+      setNewPassword('');
+    }
+  };
+
+  const handleSaveAutoLock = async () => {
+    await run(() => invoke('set_auto_lock', { minutes: autoLockMinutes }), '自动锁定时间已保存');
+  };
+
+  const autoLockInput = (
+    <div className="space-y-1">
+      <Label htmlFor="auto-lock-minutes" className="text-sm font-medium">自动锁定（分钟）</Label>
+      <Input
+        id="auto-lock-minutes"
+        type="number"
+        min={0}
+        value={autoLockMinutes}
+        onChange={(e) => setAutoLockMinutes(Math.max(0, parseInt(e.target.value) || 0))}
+        className="w-32"
+      />
+      <p className="text-sm text-muted-foreground">无操作超过设定时间后自动锁定，0 表示不自动锁定</p>
+    </div>
+  );
+
+  return (
+    <Card>
+      <CardHeader>
+        <CardTitle className="flex items-center gap-2">
+          <ShieldCheck className="h-5 w-5" />
+          数据加密
+        </CardTitle>
+      </CardHeader>
+      <CardContent className="space-y-4">
+        {!vaultStatus?.encrypted ? (
+          <>
+            <p className="text-sm text-muted-foreground">
+              启用后项目数据使用密码加密保存，每次启动应用需要输入密码解锁。密码丢失后数据无法恢复。启用加密或修改密码后会删除已有的备份
+            </p>
+            <div className="grid grid-cols-1 md:grid-cols-2 gap-4">
+              <div className="space-y-1">
+                <Label htmlFor="vault-password" className="text-sm font-medium">密码</Label>
+                <Input
+                  id="vault-password"
+                  type="password"
+                  value={password}
+                  onChange={(e) => setPassword(e.target.value)}
+                />
+              </div>
+              <div className="space-y-1">
+                <Label htmlFor="vault-password-confirm" className="text-sm font-medium">确认密码</Label>
+                <Input
+                  id="vault-password-confirm"
+                  type="password"
+                  value={confirmation}
+                  onChange={(e) => setConfirmation(e.target.value)}
+                />
+              </div>
+            </div>
+            {autoLockInput}
+            <Button
+              onClick={handleEnable}
+              disabled={isWorking || !password || password !== confirmation}
+            >
+              <Lock className="h-4 w-4 mr-2" />
+              启用加密
+            </Button>
+          </>
+        ) : (
+          <>
+            <div className="flex items-end justify-between gap-4">
+              {autoLockInput}
+              <div className="flex gap-2">
+                <Button variant="outline" onClick={handleSaveAutoLock} disabled={isWorking}>
+                  保存
+                </Button>
+                <Button variant="outline" onClick={lockVault} disabled={isWorking}>
+                  <Lock className="h-4 w-4 mr-2" />
+                  立即锁定
+                </Button>
+              </div>
+            </div>
+            <Separator />
+            <div className="grid grid-cols-1 md:grid-cols-2 gap-4">
+              <div className="space-y-1">
+                <Label htmlFor="vault-old-password" className="text-sm font-medium">当前密码</Label>
+                <Input
+                  id="vault-old-password"
+                  type="password"
+                  value={oldPassword}
+                  onChange={(e) => setOldPassword(e.target.value)}
+                />
+              </div>
+              <div className="space-y-1">
+                <Label htmlFor="vault-new-password" className="text-sm font-medium">新密码</Label>
+                <Input
+                  id="vault-new-password"
+                  type="password"
+                  value={newPassword}
+                  onChange={(e) => setNewPassword(e.target.value)}
+                />
+              </div>
+            </div>
+            <div className="flex gap-2">
+              <Button
+                variant="outline"
+                onClick={handleChangePassword}
+                disabled={isWorking || !oldPassword || !newPassword}
+              >
+                修改密码
+              </Button>
+              <Button
+                variant="outline"
+                onClick={() => setShowDisableDialog(true)}
+                disabled={isWorking}
+                className="text-destructive"
+              >
+                关闭加密
+              </Button>
+            </div>
+          </>
+        )}
+      </CardContent>
+      <PasswordDialog
+        open={showDisableDialog}
+        title="关闭加密"
+        description="关闭后项目数据将以明文保存，请输入当前密码确认"
+        onSubmit={handleDisable}
+        onCancel={() => setShowDisableDialog(false)}
+      />
+    </Card>
+  );
+}
@@ -0,0 +1,273 @@
+'use client';
+
+import { useCallback, useEffect, useState } from 'react';
+import type { MouseEvent } from 'react';
+import { invoke } from '@tauri-apps/api/core';
+import { toast } from 'sonner';
+import { Undo2 } from 'lucide-react';
+import { Button } from '@/components/ui/button';
+import { Input } from '@/components/ui/input';
+import {
+  Dialog,
+  DialogContent,
+  DialogDescription,
+  DialogFooter,
+  DialogHeader,
+  DialogTitle,
+} from '@/components/ui/dialog';
+import { AnnotationShape } from '@/lib/types';
+
+type Tool = AnnotationShape['type'];
+
+const TOOL_LABELS: Record<Tool, string> = {
+  rect: '矩形框',
+  arrow: '箭头',
+  text: '文字',
+  redact: '遮挡',
+  blur: '模糊',
+};
+
+// 预览颜色与后端默认值保持一致
+const ANNOTATION_COLOR = '#FF0000';
+const DEFAULT_FONT_SIZE = 24;
+
+// 拖拽的起点与终点，坐标以原图像素为单位
+interface Drag {
+  x0: number;
+  y0: number;
+  x1: number;
+  y1: number;
+}
+
+// 根据拖拽区域生成标注图形，区域过小时视为误触
+const toShape = (tool: Tool, drag: Drag): AnnotationShape | null => {
+  if (tool === 'arrow') {
+    if (Math.hypot(drag.x1 - drag.x0, drag.y1 - drag.y0) < 4) return null;
+    return { type: 'arrow', from_x: drag.x0, from_y: drag.y0, to_x: drag.x1, to_y: drag.y1 };
+  }
+
+  const x = Math.min(drag.x0, drag.x1);
+  const y = Math.min(drag.y0, drag.y1);
+  const width = Math.abs(drag.x1 - drag.x0);
+  const height = Math.abs(drag.y1 - drag.y0);
+  if (width < 2 || height < 2) return null;
+
+  switch (tool) {
+    case 'rect':
+      return { type: 'rect', x, y, width, height };
+    case 'redact':
+      return { type: 'redact', x, y, width, height };
+    case 'blur':
+      return { type: 'blur', x, y, width, height };
+    default:
+      return null;
+  }
+};
+
+// 在预览层中绘制标注图形
+const renderShape = (shape: AnnotationShape, key: number | string) => {
+  switch (shape.type) {
+    case 'rect':
+      return (
+        <rect key={key} x={shape.x} y={shape.y} width={shape.width} height={shape.height}
+          fill="none" stroke={ANNOTATION_COLOR} strokeWidth={shape.thickness ?? 3} />
+      );
+    case 'redact':
+      return <rect key={key} x={shape.x} y={shape.y} width={shape.width} height={shape.height} fill="#000000" />;
+    case 'blur':
+      return (
+        <rect key={key} x={shape.x} y={shape.y} width={shape.width} height={shape.height}
+          fill="#9CA3AF" fillOpacity={0.6} stroke="#6B7280" strokeDasharray="6 4" />
+      );
+    case 'arrow':
+      return (
+        <line key={key} x1={shape.from_x} y1={shape.from_y} x2={shape.to_x} y2={shape.to_y}
+          stroke={ANNOTATION_COLOR} strokeWidth={shape.thickness ?? 3} markerEnd="url(#annotation-arrow)" />
+      );
+    case 'text':
+      return (
+        <text key={key} x={shape.x} y={shape.y} fill={ANNOTATION_COLOR}
+          fontSize={shape.size ?? DEFAULT_FONT_SIZE} dominantBaseline="hanging">
+          {shape.text}
+        </text>
+      );
+  }
+};
+
+interface ImageAnnotatorProps {
+  image: Uint8Array | null; // 待标注的图片，为 null 时关闭对话框
+  onComplete: (data: Uint8Array) => void; // 返回标注后的图片，未添加标注时返回原图
+  onCancel: () => void;
+}
+
+// 图片标注对话框，图片存入报告前可添加矩形框、箭头、文字、遮挡和模糊
+export function ImageAnnotator({ image, onComplete, onCancel }: ImageAnnotatorProps) {
+  const [src, setSrc] = useState('');
+  const [size, setSize] = useState({ width: 0, height: 0 });
+  const [tool, setTool] = useState<Tool>('rect');
+  const [text, setText] = useState('');
+  const [shapes, setShapes] = useState<AnnotationShape[]>([]);
+  const [drag, setDrag] = useState<Drag | null>(null);
+  const [isApplying, setIsApplying] = useState(false);
+
+  useEffect(() => {
+    setShapes([]);
+    setDrag(null);
+    setSize({ width: 0, height: 0 });
+    if (!image) {
+      setSrc('');
+      return;
+    }
+    const url = URL.createObjectURL(new Blob([new Uint8Array(image)]));
+    setSrc(url);
+    return () => URL.revokeObjectURL(url);
+  }, [image]);
+
+  // 将鼠标位置换算为原图像素坐标
+  const toImagePoint = useCallback((event: MouseEvent<SVGSVGElement>) => {
+    const rect = event.currentTarget.getBoundingClientRect();
+    const scale = rect.width > 0 ? size.width / rect.width : 1;
+    return {
+      x: Math.round((event.clientX - rect.left) * scale),
+      y: Math.round((event.clientY - rect.top) * scale),
+    };
+  }, [size.width]);
+
+  const handleMouseDown = useCallback((event: MouseEvent<SVGSVGElement>) => {
+    const point = toImagePoint(event);
+    if (tool === 'text') {
+      if (!text.trim()) {
+        toast.error('请先输入标注文字');
+        return;
+      }
+      setShapes(prev => [...prev, { type: 'text', x: point.x, y: point.y, text: text.trim() }]);
+      return;
+    }
+    setDrag({ x0: point.x, y0: point.y, x1: point.x, y1: point.y });
+  }, [tool, text, toImagePoint]);
+
+  const handleMouseMove = useCallback((event: MouseEvent<SVGSVGElement>) => {
+    if (!drag) return;
+    const point = toImagePoint(event);
+    setDrag(prev => prev && { ...prev, x1: point.x, y1: point.y });
+  }, [drag, toImagePoint]);
+
+  const handleMouseUp = useCallback(() => {
+    if (!drag) return;
+    const shape = toShape(tool, drag);
+    if (shape) {
+      setShapes(prev => [...prev, shape]);
+    }
+    setDrag(null);
+  }, [drag, tool]);
+
+  const handleApply = useCallback(async () => {
+    if (!image) return;
+    if (shapes.length === 0) {
+      onComplete(image);
+      return;
+    }
+
+    setIsApplying(true);
+    try {
+      const result = await invoke<number[]>('annotate_image', { image: Array.from(image), shapes });
+      onComplete(new Uint8Array(result));
+    } catch (error) {
+      console.error('Failed to annotate image:', error);
+      toast.error(`图片标注失败：${error}`);
+    } finally {
+      setIsApplying(false);
+    }
+  }, [image, shapes, onComplete]);
+
+  const preview = drag ? toShape(tool, drag) : null;
+
+  return (
+    <Dialog open={image !== null} onOpenChange={(open: boolean) => !open && !isApplying && onCancel()}>
+      <DialogContent className="max-w-5xl max-h-[90vh] flex flex-col">
+        <DialogHeader>
+          <DialogTitle>标注图片</DialogTitle>
+          <DialogDescription>在图片上拖拽添加标注，不需要标注时直接添加原图</DialogDescription>
+        </DialogHeader>
+
+        <div className="flex flex-wrap items-center gap-2">
+          {(Object.keys(TOOL_LABELS) as Tool[]).map((key) => (
+            <Button
+              key={key}
+              type="button"
+              size="sm"
+              variant={tool === key ? 'default' : 'outline'}
+              onClick={() => setTool(key)}
+            >
+              {TOOL_LABELS[key]}
+            </Button>
+          ))}
+          {tool === 'text' && (
+            <Input
+              value={text}
+              onChange={(e) => setText(e.target.value)}
+              placeholder="输入文字后点击图片放置"
+              className="h-8 w-56"
+            />
+          )}
+          <Button
+            type="button"
+            size="sm"
+            variant="ghost"
+            onClick={() => setShapes(prev => prev.slice(0, -1))}
+            disabled={shapes.length === 0 || isApplying}
+          >
+            <Undo2 className="h-4 w-4 mr-1" />
+            撤销
+          </Button>
+        </div>
+
+        <div className="flex-1 overflow-auto border rounded-lg">
+          {src && (
+            <div className="relative inline-block">
+              {/* eslint-disable-next-line @next/next/no-img-element */}
+              <img
+                src={src}
+                alt="待标注图片"
+                className="block max-w-full select-none"
+                draggable={false}
+                onLoad={(e) => setSize({
+                  width: e.currentTarget.naturalWidth,
+                  height: e.currentTarget.naturalHeight,
+                })}
+              />
+              {size.width > 0 && (
+                <svg
+                  className="absolute inset-0 h-full w-full cursor-crosshair"
+                  viewBox={`0 0 ${size.width} ${size.height}`}
+                  onMouseDown={handleMouseDown}
+                  onMouseMove={handleMouseMove}
+                  onMouseUp={handleMouseUp}
+                  onMouseLeave={handleMouseUp}
+                >
+                  <defs>
+                    <marker id="annotation-arrow" viewBox="0 0 10 10" refX="8" refY="5"
+                      markerWidth="5" markerHeight="5" orient="auto-start-reverse">
+                      <path d="M 0 0 L 10 5 L 0 10 z" fill={ANNOTATION_COLOR} />
+                    </marker>
+                  </defs>
+                  {shapes.map((shape, index) => renderShape(shape, index))}
+                  {preview && renderShape(preview, 'preview')}
+                </svg>
+              )}
+            </div>
+          )}
+        </div>
+
+        <DialogFooter>
+          <Button type="button" variant="outline" onClick={onCancel} disabled={isApplying}>
+            取消
+          </Button>
+          <Button type="button" onClick={handleApply} disabled={isApplying}>
+            {isApplying ? '处理中...' : shapes.length > 0 ? `应用${shapes.length}处标注` : '添加原图'}
+          </Button>
+        </DialogFooter>
+      </DialogContent>
+    </Dialog>
+  );
+}
@@ -0,0 +1,104 @@
+'use client';
+
+import { useEffect, useState } from 'react';
+import { Button } from '@/components/ui/button';
+import { Input } from '@/components/ui/input';
+import { Label } from '@/components/ui/label';
+import {
+  Dialog,
+  DialogContent,
+  DialogDescription,
+  DialogFooter,
+  DialogHeader,
+  DialogTitle,
+} from '@/components/ui/dialog';
+
+interface PasswordDialogProps {
+  open: boolean;
+  title: string;
+  description?: string;
+  optional?: boolean; // 允许不填写密码
+  confirm?: boolean;  // 需要再次输入确认
+  onSubmit: (password: string) => void;
+  onCancel: () => void;
+}
+
+// 密码输入对话框，用于归档加密导出和导入
+export function PasswordDialog({
+  open,
+  title,
+  description,
+  optional = false,
+  confirm = false,
+  onSubmit,
+  onCancel
+}: PasswordDialogProps) {
+  const [password, setPassword] = useState('');
+  const [confirmation, setConfirmation] = useState('');
+
+  useEffect(() => {
+    if (open) {
+      setPassword('');
+      setConfirmation('');
+    }
+  }, [open]);
+
+  const mismatch = confirm && password !== confirmation;
+  const disabled = (!optional && !password) || mismatch;
+
+  const handleSubmit = (e: React.FormEvent) => {
+    e.preventDefault();
+    if (!disabled) {
+      onSubmit(password);
+    }
+  };
+
+  return (
+    <Dialog open={open} onOpenChange={(open: boolean) => !open && onCancel()}>
+      <DialogContent>
+        <form onSubmit={handleSubmit}>
+          <DialogHeader>
+            <DialogTitle>{title}</DialogTitle>
+            {description && <DialogDescription>{description}</DialogDescription>}
+          </DialogHeader>
+          <div className="space-y-4 py-4">
+            <div className="space-y-2">
+              <Label htmlFor="dialog-password">密码</Label>
+              <Input
+                id="dialog-password"
+                type="password"
+                value={password}
+                onChange={(e) => setPassword(e.target.value)}
+                placeholder={optional ? '留空则不加密' : '请输入密码'}
+                autoFocus
+              />
+            </div>
+            {confirm && password && (
+              <div className="space-y-2">
+                <Label htmlFor="dialog-password-confirm">确认密码</Label>
+                <Input
+                  id="dialog-password-confirm"
+                  type="password"
+                  value={confirmation}
+                  onChange={(e) => setConfirmation(e.target.value)}
+                  placeholder="再次输入密码"
+                />
+                {mismatch && confirmation && (
+                  <p className="text-sm text-destructive">两次输入的密码不一致</p>
+                )}
+              </div>
+            )}
+          </div>
+          <DialogFooter>
+            <Button type="button" variant="outline" onClick={onCancel}>
+              取消
+            </Button>
+            <Button type="submit" disabled={disabled}>
+              确认
+            </Button>
+          </DialogFooter>
+        </form>
+      </DialogContent>
+    </Dialog>
+  );
+}
@@ -0,0 +1,221 @@
+'use client';
+
+import { useEffect, useState } from 'react';
+import { invoke } from '@tauri-apps/api/core';
+import { toast } from 'sonner';
+import { FileDown, FileUp } from 'lucide-react';
+import { Button } from '@/components/ui/button';
+import { Label } from '@/components/ui/label';
+import {
+  Select,
+  SelectContent,
+  SelectItem,
+  SelectTrigger,
+  SelectValue,
+} from '@/components/ui/select';
+import {
+  Dialog,
+  DialogContent,
+  DialogDescription,
+  DialogHeader,
+  DialogTitle,
+} from '@/components/ui/dialog';
+import { PasswordDialog } from '@/components/password-dialog';
+import { useReport } from '@/contexts/ReportContext';
+import {
+  ArchiveFile,
+  ChangeKind,
+  DiffFormat,
+  ProjectDiff,
+  fieldLabel,
+  formatChangeValue,
+  SnapshotSource,
+} from '@/lib/types';
+
+const KIND_LABELS: Record<ChangeKind, string> = {
+  added: '新增',
+  removed: '删除',
+  modified: '修改',
+};
+
+const KIND_STYLES: Record<ChangeKind, string> = {
+  added: 'text-green-700 dark:text-green-400',
+  removed: 'text-red-600 dark:text-red-400',
+  modified: 'text-yellow-700 dark:text-yellow-400',
+};
+
+interface ProjectDiffDialogProps {
+  projectName: string | null; // 作为新版本的项目
+  onClose: () => void;
+}
+
+// 对比项目与其他项目或项目归档，查看新增、删除和修改的报告
+export function ProjectDiffDialog({ projectName, onClose }: ProjectDiffDialogProps) {
+  const { projects, selectArchive } = useReport();
+  const [baseProject, setBaseProject] = useState('');
+  const [archive, setArchive] = useState<ArchiveFile | null>(null);
+  const [askPassword, setAskPassword] = useState(false);
+  const [password, setPassword] = useState<string | undefined>();
+  const [diff, setDiff] = useState<ProjectDiff | null>(null);
+
+  useEffect(() => {
+    setBaseProject('');
+    setArchive(null);
+    setPassword(undefined);
+    setDiff(null);
+  }, [projectName]);
+
+  const baseSource = (): SnapshotSource | null => {
+    if (archive) return { type: 'archive', path: archive.path, password };
+    return baseProject ? { type: 'project', name: baseProject } : null;
+  };
+
+  const handleCompare = async (old: SnapshotSource | null = baseSource()) => {
+    if (!projectName || !old) return;
+    try {
+      setDiff(await invoke<ProjectDiff>('diff_projects', {
+        old,
+        new: { type: 'project', name: projectName },
+      }));
+    } catch (error) {
+      toast.error(`对比项目失败: ${error}`);
+    }
+  };
+
+  const handleSelectArchive = async () => {
+    const selected = await selectArchive();
+    if (!selected) return;
+    setArchive(selected);
+    setBaseProject('');
+    setPassword(undefined);
+    setDiff(null);
+    if (selected.encrypted) {
+      setAskPassword(true);
+    } else {
+      await handleCompare({ type: 'archive', path: selected.path });
+    }
+  };
+
+  const handlePassword = async (value: string) => {
+    setAskPassword(false);
+    setPassword(value);
+    if (archive) {
+      await handleCompare({ type: 'archive', path: archive.path, password: value });
+    }
+  };
+
+  const handleExport = async (format: DiffFormat) => {
+    const old = baseSource();
+    if (!projectName || !old) return;
+    try {
+      const message = await invoke<string>('export_project_diff', {
+        old,
+        new: { type: 'project', name: projectName },
+        format,
+      });
+      toast.success(message);
+    } catch (error) {
+      toast.error(`导出变更记录失败: ${error}`);
+    }
+  };
+
+  return (
+    <Dialog open={projectName !== null} onOpenChange={(open: boolean) => !open && onClose()}>
+      <DialogContent className="max-w-3xl max-h-[85vh] flex flex-col">
+        <DialogHeader>
+          <DialogTitle>对比项目</DialogTitle>
+          <DialogDescription>
+            选择原始版本，与当前项目 {projectName} 按报告对比
+          </DialogDescription>
+        </DialogHeader>
+        <div className="flex flex-wrap items-end gap-2">
+          <div className="flex-1 space-y-1">
+            <Label className="text-sm font-medium">原始版本</Label>
+            <Select
+              value={baseProject}
+              onValueChange={(value) => {
+                setBaseProject(value);
+                setArchive(null);
+                setDiff(null);
+              }}
+            >
+              <SelectTrigger>
+                <SelectValue placeholder={archive ? archive.path : '选择项目'} />
+              </SelectTrigger>
+              <SelectContent>
+                {projects
+                  .filter((project) => project.projectName !== projectName)
+                  .map((project) => (
+                    <SelectItem key={project.projectName} value={project.projectName}>
+                      {project.projectName}
+                    </SelectItem>
+                  ))}
+              </SelectContent>
+            </Select>
+          </div>
+          <Button variant="outline" onClick={handleSelectArchive}>
+            <FileUp className="h-4 w-4 mr-2" />
+            选择归档
+          </Button>
+          <Button onClick={() => handleCompare()} disabled={!baseProject && !archive}>
+            对比
+          </Button>
+        </div>
+        {diff && (
+          <>
+            <div className="flex items-center justify-between text-sm">
+              <span className="text-muted-foreground">
+                新增 {diff.reports.filter((report) => report.kind === 'added').length} 项，
+                删除 {diff.reports.filter((report) => report.kind === 'removed').length} 项，
+                修改 {diff.reports.filter((report) => report.kind === 'modified').length} 项，
+                未变化 {diff.unchanged} 项
+              </span>
+              <div className="flex gap-2">
+                <Button variant="outline" size="sm" onClick={() => handleExport('markdown')}>
+                  <FileDown className="h-4 w-4 mr-1" />
+                  Markdown
+                </Button>
+                <Button variant="outline" size="sm" onClick={() => handleExport('html')}>
+                  <FileDown className="h-4 w-4 mr-1" />
+                  HTML
+                </Button>
+              </div>
+            </div>
+            <div className="space-y-2 overflow-y-auto">
+              {diff.reports.length === 0 ? (
+                <p className="text-sm text-muted-foreground py-4 text-center">两个版本没有差异</p>
+              ) : (
+                diff.reports.map((report) => (
+                  <div key={`${report.kind}-${report.id}`} className="p-3 border rounded-lg text-sm space-y-1">
+                    <div>
+                      <span className={`font-medium ${KIND_STYLES[report.kind]}`}>【{KIND_LABELS[report.kind]}】</span>
+                      <span className="font-medium">{report.report_id} {report.report_name}</span>
+                    </div>
+                    {report.changes.map((change) => (
+                      <div key={change.field} className="break-all">
+                        <span className="text-muted-foreground">{fieldLabel(change.field)}：</span>
+                        <span className="text-red-600 dark:text-red-400 line-through">{formatChangeValue(change.old_value)}</span>
+                        <span className="mx-2 text-muted-foreground">→</span>
+                        <span className="text-green-700 dark:text-green-400">{formatChangeValue(change.new_value)}</span>
+                      </div>
+                    ))}
+                  </div>
+                ))
+              )}
+            </div>
+          </>
+        )}
+      </DialogContent>
+      <PasswordDialog
+        open={askPassword}
+        title="打开加密归档"
+        description="该归档文件已加密，请输入导出时设置的密码"
+        onSubmit={handlePassword}
+        onCancel={() => {
+          setAskPassword(false);
+          setArchive(null);
+        }}
+      />
+    </Dialog>
+  );
+}
@@ -3,7 +3,7 @@
 import { useState, useEffect, useCallback, useMemo } from 'react';
 import { open as openFileDialog } from '@tauri-apps/plugin-dialog';
 import { readFile } from '@tauri-apps/plugin-fs';
-import { X, Image as ImageIcon, Type, Eye, Clipboard, PictureInPicture2 } from 'lucide-react';
+import { X, Image as ImageIcon, Type, Eye, Clipboard, PictureInPicture2, Code, Globe } from 'lucide-react';
 import { toast } from 'sonner';
 import { Button } from '@/components/ui/button';
 import { invoke } from '@tauri-apps/api/core';
@@ -11,8 +11,10 @@ import { Input } from '@/components/ui/input';
 import { Label } from '@/components/ui/label';
 import { Select, SelectContent, SelectItem, SelectTrigger, SelectValue } from '@/components/ui/select';
 import { Separator } from '@/components/ui/separator';
+import { Switch } from '@/components/ui/switch';
 import { SidebarTrigger } from '@/components/ui/sidebar';
 import { CreatableCombobox } from '@/components/ui/creatable-combobox';
+import { ImageAnnotator } from '@/components/image-annotator';
 import {
   Dialog,
   DialogContent,
@@ -20,13 +22,27 @@ import {
   DialogTitle,
   DialogFooter,
 } from '@/components/ui/dialog';
-import { RiskReportData, ScreenshotContent } from '@/lib/types';
+import {
+  CodeBlock,
+  REMEDIATION_STATUS_LABELS,
+  RemediationStatus,
+  RiskReportData,
+  ScreenshotContent,
+  IMAGE_SIZING_LABELS,
+  ImageSizing,
+  ScreenshotImage,
+  getImageData,
+  getImageBlob
+} from '@/lib/types';
 import { hazardLevels } from '@/lib/config';
 import { useSettings } from '@/hooks/useSettings';
 import { useVulnDB } from '@/hooks/useVulnDB';
 import { useReport } from '@/contexts/ReportContext';
 import clipboard from "tauri-plugin-clipboard-api";
 
+// 可添加截图的字段
+type ScreenshotField = 'evidence_screenshots' | 'filing_screenshots' | 'retest_screenshots';
+
 interface ReportFormProps {
   report?: RiskReportData;
   onSave: (report: RiskReportData) => void;
@@ -39,6 +55,9 @@ const MAX_FILE_SIZE = 10 * 1024 * 1024;
 // 支持的图片格式
 const SUPPORTED_IMAGE_TYPES = ['png', 'jpg', 'jpeg', 'gif', 'webp', 'bmp'];
 
+// 新建代码块的默认内容
+const EMPTY_CODE_BLOCK: CodeBlock = { code: '', language: 'http', line_numbers: true, highlight: '' };
+
 // 生成隐患编号的函数 - 使用useCallback优化
 const generateReportId = (): string => {
   const now = new Date();
@@ -51,10 +70,31 @@ const generateReportId = (): string => {
   return `HN-${year}-${month}-${day}-${randomNum}`;
 };
 
+// 生成截图内容的列表摘要
+const describeScreenshot = (screenshot: ScreenshotContent, index: number): string => {
+  switch (screenshot.type) {
+    case 'image':
+      // 图片说明在列表中单独编辑
+      // 已保存的图片按需读取，未读取前不显示大小
+      return getImageData(screenshot.content).length > 0
+        ? `图片 ${index + 1} (${Math.round(getImageData(screenshot.content).length / 1024)}KB)`
+        : `图片 ${index + 1}`;
+    case 'code': {
+      const firstLine = screenshot.content.code.split('\n')[0];
+      return `代码块 ${index + 1}: ${firstLine.length > 50 ? `${firstLine.substring(0, 50)}...` : firstLine}`;
+    }
+    default:
+      return screenshot.content.length > 50
+        ? `${screenshot.content.substring(0, 50)}...`
+        : screenshot.content;
+  }
+};
+
 export function ReportForm({ report, onSave, onCancel }: ReportFormProps) {
   const { settings, addHazardType, addIndustry, addUnitType } = useSettings();
   const { addVulnData, getVulnData, getVulnNames } = useVulnDB();
-  const { isLoading: isReportLoading } = useReport();
+  const { isLoading: isReportLoading, currentProject } = useReport();
+  const customFields = currentProject?.customFields ?? [];
 
   // 使用useMemo优化默认数据
   const defaultData = useMemo(() => ({
@@ -79,17 +119,32 @@ export function ReportForm({ report, onSave, onCancel }: ReportFormProps) {
     vul_modify_repair: '',
     evidence_screenshots: [],
     filing_screenshots: [],
-    remark: ''
+    remark: '',
+    remediation_status: 'unfixed',
+    retest_date: '',
+    retest_screenshots: [],
+    custom_fields: {}
   }), []);
 
   const [formData, setFormData] = useState<RiskReportData>(() => report || defaultData);
   const [isSubmitting, setIsSubmitting] = useState(false);
+  // 网页截图仅支持 Windows 和 Linux，不支持的平台隐藏截图入口
+  const [captureSupported, setCaptureSupported] = useState(false);
+  const [isCapturing, setIsCapturing] = useState(false);
+  // 待标注的图片，标注完成后才添加到对应字段
+  const [pendingImage, setPendingImage] = useState<{ type: ScreenshotField; data: Uint8Array } | null>(null);
   const [previewImage, setPreviewImage] = useState<{ open: boolean; src: string; title: string }>({
     open: false,
     src: '',
     title: ''
   });
 
+  useEffect(() => {
+    invoke<boolean>('capture_supported')
+      .then(setCaptureSupported)
+      .catch(() => setCaptureSupported(false));
+  }, []);
+
   // 当settings加载完成后，如果是新建报告且相关字段为空，则设置默认值
   useEffect(() => {
     if (!report && settings.hazardTypes.length > 0) {
@@ -105,10 +160,17 @@ export function ReportForm({ report, onSave, onCancel }: ReportFormProps) {
   // 文本输入对话框状态
   const [textDialog, setTextDialog] = useState({
     open: false,
-    type: 'evidence_screenshots' as 'evidence_screenshots' | 'filing_screenshots',
+    type: 'evidence_screenshots' as ScreenshotField,
     value: ''
   });
 
+  // 代码块对话框状态
+  const [codeDialog, setCodeDialog] = useState({
+    open: false,
+    type: 'evidence_screenshots' as ScreenshotField,
+    block: EMPTY_CODE_BLOCK
+  });
+
   // 使用useCallback优化事件处理函数
   const handleInputChange = useCallback((field: keyof RiskReportData, value: string) => {
     setFormData(prev => {
@@ -130,6 +192,10 @@ export function ReportForm({ report, onSave, onCancel }: ReportFormProps) {
     });
   }, [getVulnData]);
 
+  const handleCustomFieldChange = useCallback((name: string, value: string) => {
+    setFormData(prev => ({ ...prev, custom_fields: { ...prev.custom_fields, [name]: value } }));
+  }, []);
+
   // 文件验证函数
   const validateFile = useCallback((filePath: string, fileSize?: number): boolean => {
     const extension = filePath.split('.').pop()?.toLowerCase();
@@ -158,6 +224,15 @@ export function ReportForm({ report, onSave, onCancel }: ReportFormProps) {
       return;
     }
 
+    // 项目声明的必填自定义字段
+    const missingField = customFields.find(
+      (field) => field.required && !(formData.custom_fields?.[field.name] ?? '').trim()
+    );
+    if (missingField) {
+      toast.error(`请填写${missingField.name}`);
+      return;
+    }
+
     setIsSubmitting(true);
 
     try {
@@ -198,9 +273,9 @@ export function ReportForm({ report, onSave, onCancel }: ReportFormProps) {
     } finally {
       setIsSubmitting(false);
     }
-  }, [formData, isSubmitting, isReportLoading, settings, addHazardType, addIndustry, addUnitType, getVulnData, addVulnData, onSave]);
+  }, [formData, customFields, isSubmitting, isReportLoading, settings, addHazardType, addIndustry, addUnitType, getVulnData, addVulnData, onSave]);
 
-  const handleAddImage = useCallback(async (type: 'evidence_screenshots' | 'filing_screenshots') => {
+  const handleAddImage = useCallback(async (type: ScreenshotField) => {
     try {
       const file = await openFileDialog({
         multiple: false,
@@ -227,17 +302,7 @@ export function ReportForm({ report, onSave, onCancel }: ReportFormProps) {
           return;
         }
 
-        const newScreenshot: ScreenshotContent = {
-          type: 'image',
-          content: fileData
-        };
-
-        setFormData(prev => ({
-          ...prev,
-          [type]: [...prev[type], newScreenshot]
-        }));
-
-        toast.success('图片添加成功');
+        setPendingImage({ type, data: fileData });
       }
     } catch (error) {
       console.error('Failed to add image:', error);
@@ -245,7 +310,7 @@ export function ReportForm({ report, onSave, onCancel }: ReportFormProps) {
     }
   }, [validateFile]);
 
-  const handlePasteImage = useCallback(async (type: 'evidence_screenshots' | 'filing_screenshots') => {
+  const handlePasteImage = useCallback(async (type: ScreenshotField) => {
     try {
       const fileData = await clipboard.readImageBinary("Uint8Array") as Uint8Array;
 
@@ -256,17 +321,7 @@ export function ReportForm({ report, onSave, onCancel }: ReportFormProps) {
           return;
         }
 
-        const newScreenshot: ScreenshotContent = {
-          type: 'image',
-          content: fileData
-        };
-
-        setFormData(prev => ({
-          ...prev,
-          [type]: [...prev[type], newScreenshot]
-        }));
-
-        toast.success('图片粘贴成功');
+        setPendingImage({ type, data: fileData });
       } else {
         toast.error('剪贴板中没有图片数据');
       }
@@ -276,7 +331,49 @@ export function ReportForm({ report, onSave, onCancel }: ReportFormProps) {
     }
   }, []);
 
-  const handleAddText = useCallback((type: 'evidence_screenshots' | 'filing_screenshots') => {
+  // 截取目标网页的整页图片
+  const handleCaptureWebpage = useCallback(async (type: ScreenshotField) => {
+    const target = formData.target.trim();
+    if (!target) {
+      toast.error('请先填写目标地址');
+      return;
+    }
+
+    setIsCapturing(true);
+    try {
+      const screenshot = await invoke<ScreenshotContent>('capture_webpage', { target });
+      if (screenshot.type === 'image') {
+        setPendingImage({ type, data: new Uint8Array(getImageData(screenshot.content)) });
+      }
+    } catch (error) {
+      console.error('Failed to capture webpage:', error);
+      toast.error(`网页截图失败：${error}`);
+    } finally {
+      setIsCapturing(false);
+    }
+  }, [formData.target]);
+
+  // 标注完成后将图片添加到对应字段
+  const handleAnnotated = useCallback((data: Uint8Array) => {
+    if (!pendingImage) return;
+    const newScreenshot: ScreenshotContent = {
+      type: 'image',
+      content: data
+    };
+
+    setFormData(prev => ({
+      ...prev,
+      [pendingImage.type]: [...(prev[pendingImage.type] ?? []), newScreenshot]
+    }));
+    setPendingImage(null);
+    toast.success('图片添加成功');
+  }, [pendingImage]);
+
+  const handleCancelAnnotate = useCallback(() => {
+    setPendingImage(null);
+  }, []);
+
+  const handleAddText = useCallback((type: ScreenshotField) => {
     setTextDialog({
       open: true,
       type,
@@ -293,7 +390,7 @@ export function ReportForm({ report, onSave, onCancel }: ReportFormProps) {
 
       setFormData(prev => ({
         ...prev,
-        [textDialog.type]: [...prev[textDialog.type], newScreenshot]
+        [textDialog.type]: [...(prev[textDialog.type] ?? []), newScreenshot]
       }));
 
       toast.success('文本添加成功');
@@ -314,16 +411,68 @@ export function ReportForm({ report, onSave, onCancel }: ReportFormProps) {
     });
   }, []);
 
-  const removeScreenshot = useCallback((type: 'evidence_screenshots' | 'filing_screenshots', index: number) => {
+  const handleAddCode = useCallback((type: ScreenshotField) => {
+    setCodeDialog({
+      open: true,
+      type,
+      block: EMPTY_CODE_BLOCK
+    });
+  }, []);
+
+  const handleConfirmCode = useCallback(() => {
+    const { block } = codeDialog;
+    if (block.code.trim()) {
+      const newScreenshot: ScreenshotContent = {
+        type: 'code',
+        content: {
+          ...block,
+          language: block.language || null,
+          highlight: block.highlight?.trim() || null
+        }
+      };
+
+      setFormData(prev => ({
+        ...prev,
+        [codeDialog.type]: [...(prev[codeDialog.type] ?? []), newScreenshot]
+      }));
+
+      toast.success('代码块添加成功');
+    }
+
+    setCodeDialog(prev => ({ ...prev, open: false }));
+  }, [codeDialog]);
+
+  const handleCancelCode = useCallback(() => {
+    setCodeDialog(prev => ({ ...prev, open: false }));
+  }, []);
+
+  // 修改图片的说明等属性，旧格式的字节数组转换为图片对象
+  const updateImage = useCallback((type: ScreenshotField, index: number, changes: Partial<Omit<ScreenshotImage, 'data'>>) => {
+    setFormData(prev => ({
+      ...prev,
+      [type]: (prev[type] ?? []).map((screenshot, i) => {
+        if (i !== index || screenshot.type !== 'image') return screenshot;
+        const image = 'data' in screenshot.content ? screenshot.content : { data: screenshot.content };
+        return { type: 'image', content: { ...image, ...changes } };
+      })
+    }));
+  }, []);
+
+  const removeScreenshot = useCallback((type: ScreenshotField, index: number) => {
     setFormData(prev => ({
       ...prev,
-      [type]: prev[type].filter((_, i) => i !== index)
+      [type]: (prev[type] ?? []).filter((_, i) => i !== index)
     }));
   }, []);
 
   // 图片预览功能
-  const handlePreviewImage = useCallback((content: Uint8Array, title: string) => {
+  const handlePreviewImage = useCallback(async (image: ScreenshotImage | Uint8Array, title: string) => {
     try {
+      // 已保存的图片只包含引用，预览时读取图片数据
+      const blobRef = getImageBlob(image);
+      const content = getImageData(image).length === 0 && blobRef
+        ? new Uint8Array(await invoke<ArrayBuffer>('load_image', { hash: blobRef }))
+        : getImageData(image);
       // 检测图片格式
       let mimeType = 'image/png'; // 默认
       if (content.length > 8) {
@@ -387,13 +536,31 @@ export function ReportForm({ report, onSave, onCancel }: ReportFormProps) {
     }
   }, []);
 
-  const renderScreenshots = useCallback((type: 'evidence_screenshots' | 'filing_screenshots', title: string) => {
-    const screenshots = formData[type];
+  const renderScreenshots = useCallback((type: ScreenshotField, title: string) => {
+    const screenshots = formData[type] ?? [];
 
     return (
       <div className="space-y-2">
         <div className="flex items-center justify-between">
-          <Label className="text-base font-medium">{title}</Label>
+          <div className="flex items-center gap-2">
+            <Label className="text-base font-medium">{title}</Label>
+            {type === 'evidence_screenshots' && (
+              <Select
+                value={String(formData.evidence_columns || 1)}
+                onValueChange={(value) => setFormData(prev => ({ ...prev, evidence_columns: parseInt(value) }))}
+                disabled={isSubmitting}
+              >
+                <SelectTrigger className="h-8 w-28">
+                  <SelectValue />
+                </SelectTrigger>
+                <SelectContent>
+                  {[1, 2, 3].map((columns) => (
+                    <SelectItem key={columns} value={String(columns)}>每行{columns}张</SelectItem>
+                  ))}
+                </SelectContent>
+              </Select>
+            )}
+          </div>
           <div className="flex gap-2">
             {type === "filing_screenshots" &&
               <Button
@@ -405,6 +572,17 @@ export function ReportForm({ report, onSave, onCancel }: ReportFormProps) {
                 <PictureInPicture2 className="h-4 w-4 mr-1" />
                 备案查询窗口
               </Button>}
+            {captureSupported && type !== "filing_screenshots" &&
+              <Button
+                type="button"
+                variant="outline"
+                size="sm"
+                onClick={() => handleCaptureWebpage(type)}
+                disabled={isCapturing}
+              >
+                <Globe className="h-4 w-4 mr-1" />
+                {isCapturing ? '截图中...' : '网页截图'}
+              </Button>}
             <Button
               type="button"
               variant="outline"
@@ -414,6 +592,15 @@ export function ReportForm({ report, onSave, onCancel }: ReportFormProps) {
               <Type className="h-4 w-4 mr-1" />
               添加文本
             </Button>
+            <Button
+              type="button"
+              variant="outline"
+              size="sm"
+              onClick={() => handleAddCode(type)}
+            >
+              <Code className="h-4 w-4 mr-1" />
+              添加代码
+            </Button>
             <Button
               type="button"
               variant="outline"
@@ -440,20 +627,54 @@ export function ReportForm({ report, onSave, onCancel }: ReportFormProps) {
           <div className="space-y-2">
             {screenshots.map((screenshot, index) => (
               <div key={`${type}-${index}`} className="flex items-center justify-between p-3 border rounded-lg">
-                <div className="flex items-center gap-2">
+                <div className="flex flex-wrap items-center gap-2">
                   {screenshot.type === 'image' ? (
                     <ImageIcon className="h-4 w-4 text-blue-500" />
+                  ) : screenshot.type === 'code' ? (
+                    <Code className="h-4 w-4 text-purple-500" />
                   ) : (
                     <Type className="h-4 w-4 text-green-500" />
                   )}
                   <span className="text-sm">
-                    {screenshot.type === 'image'
-                      ? `图片 ${index + 1} (${Math.round(screenshot.content.length / 1024)}KB)`
-                      : screenshot.content.length > 50
-                        ? `${screenshot.content.substring(0, 50)}...`
-                        : screenshot.content
-                    }
+                    {describeScreenshot(screenshot, index)}
                   </span>
+                  {screenshot.type === 'image' && (() => {
+                    const image = 'data' in screenshot.content ? screenshot.content : null;
+                    return (
+                      <>
+                        <Input
+                          value={image?.caption ?? ''}
+                          onChange={(e) => updateImage(type, index, { caption: e.target.value || null })}
+                          placeholder="图片说明（可选）"
+                          className="h-8 w-56"
+                          disabled={isSubmitting}
+                        />
+                        <Select
+                          value={image?.sizing ?? 'fit_width'}
+                          onValueChange={(value) => updateImage(type, index, { sizing: value as ImageSizing })}
+                          disabled={isSubmitting}
+                        >
+                          <SelectTrigger className="h-8 w-28">
+                            <SelectValue />
+                          </SelectTrigger>
+                          <SelectContent>
+                            {(Object.keys(IMAGE_SIZING_LABELS) as ImageSizing[]).map((sizing) => (
+                              <SelectItem key={sizing} value={sizing}>{IMAGE_SIZING_LABELS[sizing]}</SelectItem>
+                            ))}
+                          </SelectContent>
+                        </Select>
+                        <Input
+                          type="number"
+                          min={1}
+                          value={image?.max_height ?? ''}
+                          onChange={(e) => updateImage(type, index, { max_height: parseInt(e.target.value) || null })}
+                          placeholder="最大高度(px)"
+                          className="h-8 w-32"
+                          disabled={isSubmitting}
+                        />
+                      </>
+                    );
+                  })()}
                 </div>
                 <div className="flex items-center gap-2">
                   {screenshot.type === 'image' && (
@@ -481,7 +702,7 @@ export function ReportForm({ report, onSave, onCancel }: ReportFormProps) {
         )}
       </div>
     );
-  }, [formData, openICPWindow, handleAddText, handleAddImage, handlePasteImage, handlePreviewImage, removeScreenshot]);
+  }, [formData, openICPWindow, captureSupported, isCapturing, handleCaptureWebpage, handleAddText, handleAddCode, handleAddImage, handlePasteImage, handlePreviewImage, updateImage, removeScreenshot, isSubmitting]);
 
   return (
     <>
@@ -697,6 +918,48 @@ export function ReportForm({ report, onSave, onCancel }: ReportFormProps) {
             </div>
           </div>
 
+          {customFields.length > 0 && (
+            <>
+              <Separator />
+
+              {/* 自定义字段 */}
+              <div className="space-y-4">
+                <h3 className="text-lg font-semibold">自定义字段</h3>
+
+                <div className="grid grid-cols-1 md:grid-cols-2 gap-4">
+                  {customFields.map((field, index) => {
+                    const id = `custom_field_${index}`;
+                    const value = formData.custom_fields?.[field.name] ?? '';
+                    return (
+                      <div key={field.name} className={field.type === 'multiline' ? 'md:col-span-2' : undefined}>
+                        <Label htmlFor={id}>
+                          {field.name}{field.required && <span className="text-destructive"> *</span>}
+                        </Label>
+                        {field.type === 'multiline' ? (
+                          <textarea
+                            id={id}
+                            value={value}
+                            onChange={(e) => handleCustomFieldChange(field.name, e.target.value)}
+                            className="w-full min-h-[80px] px-3 py-2 border rounded-md resize-y"
+                            disabled={isSubmitting}
+                          />
+                        ) : (
+                          <Input
+                            id={id}
+                            type={field.type === 'text' ? 'text' : field.type}
+                            value={value}
+                            onChange={(e) => handleCustomFieldChange(field.name, e.target.value)}
+                            disabled={isSubmitting}
+                          />
+                        )}
+                      </div>
+                    );
+                  })}
+                </div>
+              </div>
+            </>
+          )}
+
           <Separator />
 
           {/* 详细描述 */}
@@ -747,6 +1010,43 @@ export function ReportForm({ report, onSave, onCancel }: ReportFormProps) {
 
           <Separator />
 
+          {/* 复测信息 */}
+          <div className="space-y-4">
+            <h3 className="text-lg font-semibold">复测信息</h3>
+            <div className="grid grid-cols-1 md:grid-cols-2 gap-4">
+              <div>
+                <Label htmlFor="remediation_status">修复状态</Label>
+                <Select
+                  value={formData.remediation_status ?? 'unfixed'}
+                  onValueChange={(value) => handleInputChange('remediation_status', value)}
+                  disabled={isSubmitting}
+                >
+                  <SelectTrigger id="remediation_status">
+                    <SelectValue />
+                  </SelectTrigger>
+                  <SelectContent>
+                    {(Object.keys(REMEDIATION_STATUS_LABELS) as RemediationStatus[]).map((status) => (
+                      <SelectItem key={status} value={status}>{REMEDIATION_STATUS_LABELS[status]}</SelectItem>
+                    ))}
+                  </SelectContent>
+                </Select>
+              </div>
+              <div>
+                <Label htmlFor="retest_date">复测日期</Label>
+                <Input
+                  id="retest_date"
+                  type="date"
+                  value={formData.retest_date ?? ''}
+                  onChange={(e) => handleInputChange('retest_date', e.target.value)}
+                  disabled={isSubmitting}
+                />
+              </div>
+            </div>
+            {renderScreenshots('retest_screenshots', '复测证据截图')}
+          </div>
+
+          <Separator />
+
           {/* 提交按钮 */}
           <div className="flex justify-end gap-4">
             <Button type="button" variant="outline" onClick={onCancel} disabled={isSubmitting}>
@@ -796,6 +1096,80 @@ export function ReportForm({ report, onSave, onCancel }: ReportFormProps) {
         </DialogContent>
       </Dialog>
 
+      {/* 代码块对话框 */}
+      <Dialog open={codeDialog.open} onOpenChange={(open: boolean) => !open && handleCancelCode()}>
+        <DialogContent className="max-w-2xl">
+          <DialogHeader>
+            <DialogTitle>添加代码块</DialogTitle>
+          </DialogHeader>
+          <div className="space-y-4">
+            <div>
+              <Label htmlFor="code-content">代码/报文内容</Label>
+              <textarea
+                id="code-content"
+                value={codeDialog.block.code}
+                onChange={(e) => setCodeDialog(prev => ({ ...prev, block: { ...prev.block, code: e.target.value } }))}
+                className="w-full min-h-[200px] px-3 py-2 border rounded-md resize-y font-mono text-sm"
+                placeholder="粘贴HTTP请求/响应报文或代码..."
+                autoFocus
+              />
+            </div>
+            <div className="grid grid-cols-1 md:grid-cols-2 gap-4">
+              <div>
+                <Label>内容类型</Label>
+                <Select
+                  value={codeDialog.block.language || 'plain'}
+                  onValueChange={(value) => setCodeDialog(prev => ({
+                    ...prev,
+                    block: { ...prev.block, language: value === 'plain' ? '' : value }
+                  }))}
+                >
+                  <SelectTrigger>
+                    <SelectValue />
+                  </SelectTrigger>
+                  <SelectContent>
+                    <SelectItem value="http">HTTP报文</SelectItem>
+                    <SelectItem value="plain">普通代码</SelectItem>
+                  </SelectContent>
+                </Select>
+              </div>
+              <div>
+                <Label htmlFor="code-highlight">突出显示的Payload</Label>
+                <Input
+                  id="code-highlight"
+                  value={codeDialog.block.highlight ?? ''}
+                  onChange={(e) => setCodeDialog(prev => ({ ...prev, block: { ...prev.block, highlight: e.target.value } }))}
+                  placeholder="如 ' or 1=1--"
+                />
+              </div>
+            </div>
+            <div className="flex items-center gap-2">
+              <Switch
+                id="code-line-numbers"
+                checked={codeDialog.block.line_numbers ?? false}
+                onCheckedChange={(line_numbers) => setCodeDialog(prev => ({ ...prev, block: { ...prev.block, line_numbers } }))}
+              />
+              <Label htmlFor="code-line-numbers">显示行号</Label>
+            </div>
+          </div>
+          <DialogFooter>
+            <Button variant="outline" onClick={handleCancelCode}>
+              取消
+            </Button>
+            <Button onClick={handleConfirmCode} disabled={!codeDialog.block.code.trim()}>
+              确认
+            </Button>
+          </DialogFooter>
+        </DialogContent>
+      </Dialog>
+
+      {/* 图片标注对话框 */}
+      <ImageAnnotator
+        image={pendingImage?.data ?? null}
+        onComplete={handleAnnotated}
+        onCancel={handleCancelAnnotate}
+      />
+
       {/* 图片预览对话框 */}
       <Dialog open={previewImage.open} onOpenChange={handleClosePreview}>
         <DialogContent className="max-w-4xl max-h-[90vh]">
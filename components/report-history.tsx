@@ -0,0 +1,94 @@
+'use client';
+
+import { useCallback, useEffect, useState } from 'react';
+import { invoke } from '@tauri-apps/api/core';
+import { toast } from 'sonner';
+import { RotateCcw } from 'lucide-react';
+import { Button } from '@/components/ui/button';
+import {
+  Dialog,
+  DialogContent,
+  DialogDescription,
+  DialogHeader,
+  DialogTitle,
+} from '@/components/ui/dialog';
+import { useReport } from '@/contexts/ReportContext';
+import { FieldChange, fieldLabel, formatChangeValue, RiskReportData } from '@/lib/types';
+
+// 截图字段只记录摘要，不支持回退
+const SCREENSHOT_FIELDS = ['evidence_screenshots', 'filing_screenshots', 'retest_screenshots'];
+
+interface ReportHistoryProps {
+  report: RiskReportData | null;
+  onClose: () => void;
+}
+
+// 报告的字段修改历史，可将字段回退到修改前的值
+export function ReportHistory({ report, onClose }: ReportHistoryProps) {
+  const { currentProject, revertReportField } = useReport();
+  const [history, setHistory] = useState<FieldChange[]>([]);
+
+  const loadHistory = useCallback(async () => {
+    if (!report || !currentProject) return;
+    try {
+      setHistory(await invoke<FieldChange[]>('report_history', {
+        projectName: currentProject.projectName,
+        id: report.id
+      }));
+    } catch (error) {
+      toast.error(`读取修改历史失败: ${error}`);
+    }
+  }, [report, currentProject]);
+
+  useEffect(() => {
+    setHistory([]);
+    loadHistory();
+  }, [loadHistory]);
+
+  const handleRevert = async (change: FieldChange) => {
+    if (report && await revertReportField(report.id, change.id)) {
+      await loadHistory();
+    }
+  };
+
+  return (
+    <Dialog open={report !== null} onOpenChange={(open: boolean) => !open && onClose()}>
+      <DialogContent className="max-w-3xl max-h-[85vh] flex flex-col">
+        <DialogHeader>
+          <DialogTitle>修改历史</DialogTitle>
+          <DialogDescription>{report?.report_name || report?.report_id}</DialogDescription>
+        </DialogHeader>
+        <div className="space-y-2 overflow-y-auto">
+          {history.length === 0 ? (
+            <p className="text-sm text-muted-foreground py-4 text-center">暂无修改记录</p>
+          ) : (
+            history.map((change) => (
+              <div key={change.id} className="flex items-start justify-between gap-4 p-3 border rounded-lg text-sm">
+                <div className="min-w-0 flex-1 space-y-1">
+                  <div>
+                    <span className="font-medium">{fieldLabel(change.field)}</span>
+                    <span className="ml-2 text-muted-foreground">
+                      {new Date(change.changed_at * 1000).toLocaleString('zh-CN')}
+                      {change.author && ` · ${change.author}`}
+                    </span>
+                  </div>
+                  <div className="break-all">
+                    <span className="text-red-600 dark:text-red-400 line-through">{formatChangeValue(change.old_value)}</span>
+                    <span className="mx-2 text-muted-foreground">→</span>
+                    <span className="text-green-700 dark:text-green-400">{formatChangeValue(change.new_value)}</span>
+                  </div>
+                </div>
+                {!SCREENSHOT_FIELDS.includes(change.field) && (
+                  <Button variant="ghost" size="sm" onClick={() => handleRevert(change)} title="回退到修改前的值">
+                    <RotateCcw className="h-4 w-4 mr-1" />
+                    回退
+                  </Button>
+                )}
+              </div>
+            ))
+          )}
+        </div>
+      </DialogContent>
+    </Dialog>
+  );
+}
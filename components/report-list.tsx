@@ -1,9 +1,11 @@
 'use client';
 
-import { FileText, Edit, Trash2 } from 'lucide-react';
+import { useState } from 'react';
+import { FileText, Edit, Trash2, History } from 'lucide-react';
 import { Button } from '@/components/ui/button';
+import { ReportHistory } from '@/components/report-history';
 import { useReport } from '@/contexts/ReportContext';
-import { RiskReportData } from '@/lib/types';
+import { REMEDIATION_STATUS_LABELS, RiskReportData } from '@/lib/types';
 
 interface ReportListProps {
   onEditReport: (report: RiskReportData) => void;
@@ -11,6 +13,7 @@ interface ReportListProps {
 
 export function ReportList({ onEditReport }: ReportListProps) {
   const { currentProject, deleteReport } = useReport();
+  const [historyReport, setHistoryReport] = useState<RiskReportData | null>(null);
 
   if (!currentProject) return null;
 
@@ -75,6 +78,13 @@ export function ReportList({ onEditReport }: ReportListProps) {
                   <span className="font-medium">发现时间: </span>
                   <span>{report.report_time}</span>
                 </div>
+                <div>
+                  <span className="font-medium">修复状态: </span>
+                  <span>
+                    {REMEDIATION_STATUS_LABELS[report.remediation_status ?? 'unfixed']}
+                    {report.retest_date && `（${report.retest_date} 复测）`}
+                  </span>
+                </div>
               </div>
             </div>
             <div className="flex gap-2 ml-4">
@@ -85,6 +95,14 @@ export function ReportList({ onEditReport }: ReportListProps) {
               >
                 <Edit className="h-4 w-4" />
               </Button>
+              <Button
+                variant="ghost"
+                size="icon"
+                onClick={() => setHistoryReport(report)}
+                title="修改历史"
+              >
+                <History className="h-4 w-4" />
+              </Button>
               <Button
                 variant="ghost"
                 size="icon"
@@ -97,6 +115,10 @@ export function ReportList({ onEditReport }: ReportListProps) {
           </div>
         </div>
       ))}
+      <ReportHistory
+        report={historyReport}
+        onClose={() => setHistoryReport(null)}
+      />
     </div>
   );
 } 
\ No newline at end of file
@@ -0,0 +1,208 @@
+'use client';
+
+import { useEffect, useState } from 'react';
+import { toast } from 'sonner';
+import { FileText, Save } from 'lucide-react';
+import { Button } from '@/components/ui/button';
+import { Card, CardContent, CardHeader, CardTitle } from '@/components/ui/card';
+import { Input } from '@/components/ui/input';
+import { Label } from '@/components/ui/label';
+import { Separator } from '@/components/ui/separator';
+import { Switch } from '@/components/ui/switch';
+import {
+  Select,
+  SelectContent,
+  SelectItem,
+  SelectTrigger,
+  SelectValue,
+} from '@/components/ui/select';
+import { useSettings } from '@/hooks/useSettings';
+import {
+  DEFAULT_LEVEL_COLORS,
+  LEVEL_SHADING_TARGET_LABELS,
+  LevelColorConfig,
+  LevelShadingTarget,
+  REDACTION_ACTION_LABELS,
+  RedactionAction,
+  RedactionSettings,
+  SENSITIVE_KIND_LABELS,
+  SensitiveKind,
+} from '@/lib/types';
+
+const DEFAULT_REDACTION: RedactionSettings = {
+  enabled: false,
+  policy: { default_action: 'mask', actions: {} },
+};
+
+const ACTIONS = Object.keys(REDACTION_ACTION_LABELS) as RedactionAction[];
+
+// 生成报告时的样式与脱敏设置
+export function ReportSettings() {
+  const { settings, updateSettings } = useSettings();
+  const [levelColors, setLevelColors] = useState<LevelColorConfig>(DEFAULT_LEVEL_COLORS);
+  const [redaction, setRedaction] = useState<RedactionSettings>(DEFAULT_REDACTION);
+
+  useEffect(() => {
+    setLevelColors(settings.levelColors ?? DEFAULT_LEVEL_COLORS);
+  }, [settings.levelColors]);
+
+  useEffect(() => {
+    setRedaction(settings.redaction ?? DEFAULT_REDACTION);
+  }, [settings.redaction]);
+
+  const setKindAction = (kind: SensitiveKind, action: RedactionAction) => {
+    setRedaction((prev) => ({
+      ...prev,
+      policy: { ...prev.policy, actions: { ...prev.policy.actions, [kind]: action } },
+    }));
+  };
+
+  const setColor = (level: string, color: string) => {
+    setLevelColors((prev) => ({
+      ...prev,
+      colors: { ...prev.colors, [level]: color.replace('#', '').toUpperCase() },
+    }));
+  };
+
+  const handleSave = async () => {
+    if (await updateSettings({ levelColors, redaction })) {
+      toast.success('报告生成设置已保存');
+    }
+  };
+
+  return (
+    <Card>
+      <CardHeader>
+        <CardTitle className="flex items-center gap-2">
+          <FileText className="h-5 w-5" />
+          报告生成
+        </CardTitle>
+      </CardHeader>
+      <CardContent className="space-y-4">
+        <div className="space-y-1">
+          <Label className="text-sm font-medium">隐患级别着色</Label>
+          <Select
+            value={levelColors.target}
+            onValueChange={(target) => setLevelColors({ ...levelColors, target: target as LevelShadingTarget })}
+          >
+            <SelectTrigger className="w-48">
+              <SelectValue />
+            </SelectTrigger>
+            <SelectContent>
+              {(Object.keys(LEVEL_SHADING_TARGET_LABELS) as LevelShadingTarget[]).map((target) => (
+                <SelectItem key={target} value={target}>{LEVEL_SHADING_TARGET_LABELS[target]}</SelectItem>
+              ))}
+            </SelectContent>
+          </Select>
+        </div>
+        {levelColors.target !== 'none' && (
+          <div className="grid grid-cols-2 md:grid-cols-4 gap-4">
+            {settings.hazardLevels.map((level) => {
+              const color = levelColors.colors[level];
+              return (
+                <div key={level} className="flex items-center gap-2">
+                  <Input
+                    id={`level-color-${level}`}
+                    type="color"
+                    value={color ? `#${color}` : '#FFFFFF'}
+                    onChange={(e) => setColor(level, e.target.value)}
+                    className="w-12 h-9 p-1"
+                  />
+                  <Label htmlFor={`level-color-${level}`} className="text-sm">{level}</Label>
+                  {color && (
+                    <Button
+                      variant="ghost"
+                      size="sm"
+                      onClick={() => {
+                        const colors = { ...levelColors.colors };
+                        delete colors[level];
+                        setLevelColors({ ...levelColors, colors });
+                      }}
+                    >
+                      清除
+                    </Button>
+                  )}
+                </div>
+              );
+            })}
+          </div>
+        )}
+        <Separator />
+        <div className="flex items-center justify-between">
+          <div className="space-y-1">
+            <Label htmlFor="redaction-enabled" className="text-sm font-medium">敏感信息脱敏</Label>
+            <p className="text-sm text-muted-foreground">
+              生成报告时处理文本、代码块中的 Cookie、令牌、AK/SK、身份证号和手机号码，不修改项目数据
+            </p>
+          </div>
+          <Switch
+            id="redaction-enabled"
+            checked={redaction.enabled}
+            onCheckedChange={(enabled) => setRedaction({ ...redaction, enabled })}
+          />
+        </div>
+        {redaction.enabled && (
+          <div className="grid grid-cols-2 md:grid-cols-3 gap-4">
+            {(Object.keys(SENSITIVE_KIND_LABELS) as SensitiveKind[]).map((kind) => (
+              <div key={kind} className="space-y-1">
+                <Label className="text-sm">{SENSITIVE_KIND_LABELS[kind]}</Label>
+                <Select
+                  value={redaction.policy.actions?.[kind] ?? redaction.policy.default_action ?? 'mask'}
+                  onValueChange={(action) => setKindAction(kind, action as RedactionAction)}
+                >
+                  <SelectTrigger>
+                    <SelectValue />
+                  </SelectTrigger>
+                  <SelectContent>
+                    {ACTIONS.map((action) => (
+                      <SelectItem key={action} value={action}>{REDACTION_ACTION_LABELS[action]}</SelectItem>
+                    ))}
+                  </SelectContent>
+                </Select>
+              </div>
+            ))}
+            <div className="col-span-2 md:col-span-3 flex flex-wrap items-end gap-4">
+              <div className="flex items-center gap-2 pb-2">
+                <Switch
+                  id="redaction-ocr"
+                  checked={redaction.ocrImages ?? false}
+                  onCheckedChange={(ocrImages) => setRedaction({ ...redaction, ocrImages })}
+                />
+                <Label htmlFor="redaction-ocr" className="text-sm">识别并模糊截图中的敏感信息（需安装 Tesseract）</Label>
+              </div>
+              {redaction.ocrImages && (
+                <div className="flex-1 space-y-1">
+                  <Label htmlFor="tesseract-path" className="text-sm">Tesseract 路径</Label>
+                  <Input
+                    id="tesseract-path"
+                    value={redaction.tesseractPath ?? ''}
+                    onChange={(e) => setRedaction({ ...redaction, tesseractPath: e.target.value })}
+                    placeholder="留空时从 PATH 查找"
+                  />
+                  <p className="text-xs text-muted-foreground">
+                    应用不内置 OCR 引擎，需在系统中安装 tesseract 及中文简体语言包（chi_sim），安装方法见 README
+                  </p>
+                </div>
+              )}
+            </div>
+          </div>
+        )}
+        <div className="flex justify-end gap-2">
+          <Button
+            variant="outline"
+            onClick={() => {
+              setLevelColors(DEFAULT_LEVEL_COLORS);
+              setRedaction(DEFAULT_REDACTION);
+            }}
+          >
+            恢复默认
+          </Button>
+          <Button onClick={handleSave}>
+            <Save className="h-4 w-4 mr-2" />
+            保存
+          </Button>
+        </div>
+      </CardContent>
+    </Card>
+  );
+}
@@ -0,0 +1,49 @@
+'use client';
+
+import { useState } from 'react';
+import { Lock } from 'lucide-react';
+import { Button } from '@/components/ui/button';
+import { Input } from '@/components/ui/input';
+import { useReport } from '@/contexts/ReportContext';
+
+// 项目数据加密后的解锁页面
+export function UnlockScreen() {
+  const { unlockVault } = useReport();
+  const [password, setPassword] = useState('');
+  const [isUnlocking, setIsUnlocking] = useState(false);
+
+  const handleUnlock = async (e: React.FormEvent) => {
+    e.preventDefault();
+    if (!password) return;
+
+    setIsUnlocking(true);
+    const success = await unlockVault(password);
+    setIsUnlocking(false);
+    if (success) {
+      setPassword('');
+    }
+  };
+
+  return (
+    <div className="flex flex-col items-center justify-center h-full text-center" data-tauri-drag-region>
+      <Lock className="h-16 w-16 text-muted-foreground mb-4" />
+      <h2 className="text-2xl font-bold mb-2">项目数据已加密</h2>
+      <p className="text-muted-foreground mb-6 max-w-md">
+        请输入密码解锁项目数据
+      </p>
+      <form onSubmit={handleUnlock} className="flex w-full max-w-sm gap-2">
+        <Input
+          type="password"
+          value={password}
+          onChange={(e) => setPassword(e.target.value)}
+          placeholder="请输入密码"
+          autoFocus
+          disabled={isUnlocking}
+        />
+        <Button type="submit" disabled={!password || isUnlocking}>
+          {isUnlocking ? '解锁中...' : '解锁'}
+        </Button>
+      </form>
+    </div>
+  );
+}
@@ -1,11 +1,12 @@
 'use client';
 
 import React, { createContext, useContext, useState, useEffect, ReactNode, useCallback } from 'react';
-import { load, Store } from '@tauri-apps/plugin-store';
 import { invoke } from '@tauri-apps/api/core';
+import { listen } from '@tauri-apps/api/event';
 import { toast } from 'sonner';
-import { RiskReportData, ReportProject } from '@/lib/types';
+import { RiskReportData, ReportProject, ImportConflict, ImportSummary, ArchiveFile, VaultStatus, ReportVariant, CustomFieldDef } from '@/lib/types';
 import { demoProjects } from '@/lib/demo';
+import { useSettings } from '@/hooks/useSettings';
 
 // 上下文类型定义
 interface ReportContextType {
@@ -21,9 +22,19 @@ interface ReportContextType {
   addReport: (report: RiskReportData) => Promise<boolean>;
   updateReport: (internalId: string, report: RiskReportData) => Promise<boolean>;
   deleteReport: (internalId: string) => Promise<void>;
-  generateReport: () => Promise<void>;
+  generateReport: (variant?: ReportVariant) => Promise<void>;
   loadDemoData: () => Promise<void>;
   saveProject: (project: ReportProject) => Promise<boolean>;
+  exportProject: (projectName: string, password?: string) => Promise<void>;
+  selectArchive: () => Promise<ArchiveFile | null>;
+  importProject: (archive: ArchiveFile, password?: string, conflict?: ImportConflict) => Promise<void>;
+  vaultStatus: VaultStatus | null;
+  refreshVaultStatus: () => Promise<VaultStatus>;
+  unlockVault: (password: string) => Promise<boolean>;
+  lockVault: () => Promise<void>;
+  restoreBackup: (id: string, allowEncryptionChange?: boolean) => Promise<boolean>;
+  revertReportField: (internalId: string, changeId: number) => Promise<boolean>;
+  setCustomFields: (projectName: string, fields: CustomFieldDef[]) => Promise<boolean>;
 }
 
 // 错误类型定义
@@ -44,19 +55,50 @@ export function ReportProvider({ children }: { children: ReactNode }) {
   const [isLoading, setIsLoading] = useState(true);
   const [isSaving, setIsSaving] = useState(false);
   const [lastSaved, setLastSaved] = useState<Date | null>(null);
-  const [docStore, setDocStore] = useState<Store | null>(null);
+  const [isReady, setIsReady] = useState(false);
+  const [vaultStatus, setVaultStatus] = useState<VaultStatus | null>(null);
+  const { settings } = useSettings();
+  const author = settings.authorName ?? '';
   
-  // 初始化存储
+  // 初始化存储（项目数据保存在后端数据库中）
   useEffect(() => {
     initDocStore();
   }, []);
 
+  // 自动锁定后清除内存中的项目数据
+  useEffect(() => {
+    const unlisten = listen('vault-locked', () => {
+      clearProjects();
+      toast.info('长时间无操作，项目数据已自动锁定');
+    });
+    return () => {
+      unlisten.then(fn => fn());
+    };
+  }, []);
+
+  const clearProjects = () => {
+    setProjects([]);
+    setCurrentProject(null);
+    setIsReady(false);
+    setVaultStatus(prev => prev ? { ...prev, locked: true } : prev);
+  };
+
+  const refreshVaultStatus = useCallback(async (): Promise<VaultStatus> => {
+    const status = await invoke<VaultStatus>('vault_status');
+    setVaultStatus(status);
+    return status;
+  }, []);
+
   const initDocStore = async () => {
     try {
       setIsLoading(true);
-      const docStoreInstance = await load('docStore.json', { autoSave: false });
-      setDocStore(docStoreInstance);
-      await loadProjects(docStoreInstance);
+      const status = await refreshVaultStatus();
+      // 加密的项目数据需要先解锁
+      if (status.locked) {
+        return;
+      }
+      await loadProjects();
+      setIsReady(true);
       console.log('Store initialized successfully');
     } catch (error) {
       console.error('Failed to initialize store:', error);
@@ -67,30 +109,9 @@ export function ReportProvider({ children }: { children: ReactNode }) {
     }
   };
 
-  const loadProjects = async (storeInstance: Store) => {
+  const loadProjects = async () => {
     try {
-      const keys = await storeInstance.keys();
-      const loadedProjects: ReportProject[] = [];
-      
-      // 并行加载所有项目
-      const projectPromises = keys.map(async (key) => {
-        try {
-          const project = await storeInstance.get(key);
-          if (project && typeof project === 'object' && 'projectName' in project) {
-            return project as ReportProject;
-          }
-        } catch (error) {
-          console.warn(`Failed to load project ${key}:`, error);
-        }
-        return null;
-      });
-
-      const projectResults = await Promise.all(projectPromises);
-      projectResults.forEach(project => {
-        if (project) {
-          loadedProjects.push(project);
-        }
-      });
+      const loadedProjects = await invoke<ReportProject[]>('load_projects');
       
       setProjects(loadedProjects);
       if (loadedProjects.length > 0 && !currentProject) {
@@ -104,7 +125,7 @@ export function ReportProvider({ children }: { children: ReactNode }) {
   };
 
   const saveProject = useCallback(async (project: ReportProject): Promise<boolean> => {
-    if (!docStore) {
+    if (!isReady) {
       console.error('Store not initialized');
       toast.error('数据存储未初始化，请稍后重试');
       return false;
@@ -112,8 +133,7 @@ export function ReportProvider({ children }: { children: ReactNode }) {
 
     setIsSaving(true);
     try {
-      await docStore.set(project.projectName, project);
-      await docStore.save();
+      await invoke('save_project', { project, author });
       setLastSaved(new Date());
       return true;
     } catch (error) {
@@ -123,10 +143,31 @@ export function ReportProvider({ children }: { children: ReactNode }) {
     } finally {
       setIsSaving(false);
     }
-  }, [docStore]);
+  }, [isReady, author]);
+
+  // 保存单个报告，只写入变化的报告而不是整个项目
+  const saveReport = useCallback(async (projectName: string, report: RiskReportData): Promise<boolean> => {
+    if (!isReady) {
+      toast.error('数据存储未初始化，请稍后重试');
+      return false;
+    }
+
+    setIsSaving(true);
+    try {
+      await invoke('save_report', { projectName, report, author });
+      setLastSaved(new Date());
+      return true;
+    } catch (error) {
+      console.error('Failed to save report:', error);
+      toast.error('保存报告失败');
+      return false;
+    } finally {
+      setIsSaving(false);
+    }
+  }, [isReady, author]);
 
   const createProject = useCallback(async (name: string) => {
-    if (!docStore) {
+    if (!isReady) {
       throw new ReportError('存储未初始化', 'STORE_NOT_READY');
     }
     
@@ -159,14 +200,13 @@ export function ReportProvider({ children }: { children: ReactNode }) {
       toast.error(error instanceof ReportError ? error.message : '创建项目失败');
       throw error;
     }
-  }, [docStore, projects, saveProject]);
+  }, [isReady, projects, saveProject]);
 
   const deleteProject = useCallback(async (projectName: string) => {
-    if (!docStore) return;
+    if (!isReady) return;
     
     try {
-      await docStore.delete(projectName);
-      await docStore.save();
+      await invoke('delete_project', { projectName });
       
       setProjects(prev => prev.filter(p => p.projectName !== projectName));
       
@@ -181,10 +221,10 @@ export function ReportProvider({ children }: { children: ReactNode }) {
       toast.error('删除项目失败');
       throw new ReportError('删除项目失败', 'DELETE_FAILED');
     }
-  }, [docStore, currentProject, projects]);
+  }, [isReady, currentProject, projects]);
 
   const renameProject = useCallback(async (oldName: string, newName: string): Promise<boolean> => {
-    if (!docStore) return false;
+    if (!isReady) return false;
     
     const trimmedNewName = newName.trim();
     if (!trimmedNewName) {
@@ -213,10 +253,7 @@ export function ReportProvider({ children }: { children: ReactNode }) {
         projectName: trimmedNewName
       };
       
-      // 保存新项目并删除旧项目
-      await docStore.set(trimmedNewName, renamedProject);
-      await docStore.delete(oldName);
-      await docStore.save();
+      await invoke('rename_project', { oldName, newName: trimmedNewName });
       
       // 更新本地状态
       setProjects(prev => prev.map(p => 
@@ -235,7 +272,7 @@ export function ReportProvider({ children }: { children: ReactNode }) {
       toast.error('重命名项目失败');
       return false;
     }
-  }, [docStore, projects, currentProject]);
+  }, [isReady, projects, currentProject]);
 
   const addReport = useCallback(async (report: RiskReportData): Promise<boolean> => {
     if (!currentProject) {
@@ -243,11 +280,6 @@ export function ReportProvider({ children }: { children: ReactNode }) {
       return false;
     }
     
-    if (!docStore) {
-      toast.error('数据存储未初始化，请稍后重试');
-      return false;
-    }
-    
     // 检查隐患编号是否已存在
     const existingReport = currentProject.reportList.find(r => r.report_id === report.report_id);
     if (existingReport) {
@@ -267,7 +299,7 @@ export function ReportProvider({ children }: { children: ReactNode }) {
     };
     
     try {
-      const success = await saveProject(updatedProject);
+      const success = await saveReport(currentProject.projectName, reportWithId);
       if (success) {
         setCurrentProject(updatedProject);
         setProjects(prev => prev.map(p => 
@@ -282,7 +314,7 @@ export function ReportProvider({ children }: { children: ReactNode }) {
       toast.error('添加报告失败');
       return false;
     }
-  }, [currentProject, docStore, saveProject]);
+  }, [currentProject, saveReport]);
 
   const updateReport = useCallback(async (internalId: string, updatedReport: RiskReportData): Promise<boolean> => {
     if (!currentProject) {
@@ -290,11 +322,6 @@ export function ReportProvider({ children }: { children: ReactNode }) {
       return false;
     }
     
-    if (!docStore) {
-      toast.error('数据存储未初始化，请稍后重试');
-      return false;
-    }
-    
     // 找到当前报告
     const currentReport = currentProject.reportList.find(r => r.id === internalId);
     if (!currentReport) {
@@ -313,15 +340,16 @@ export function ReportProvider({ children }: { children: ReactNode }) {
       }
     }
     
+    const reportWithId = { ...updatedReport, id: internalId };
     const updatedProject = {
       ...currentProject,
       reportList: currentProject.reportList.map(r => 
-        r.id === internalId ? { ...updatedReport, id: internalId } : r
+        r.id === internalId ? reportWithId : r
       )
     };
     
     try {
-      const success = await saveProject(updatedProject);
+      const success = await saveReport(currentProject.projectName, reportWithId);
       if (success) {
         setCurrentProject(updatedProject);
         setProjects(prev => prev.map(p => 
@@ -336,7 +364,7 @@ export function ReportProvider({ children }: { children: ReactNode }) {
       toast.error('更新报告失败');
       return false;
     }
-  }, [currentProject, docStore, saveProject]);
+  }, [currentProject, saveReport]);
 
   const deleteReport = useCallback(async (internalId: string) => {
     if (!currentProject) {
@@ -344,7 +372,7 @@ export function ReportProvider({ children }: { children: ReactNode }) {
       return;
     }
     
-    if (!docStore) {
+    if (!isReady) {
       toast.error('数据存储未初始化，请稍后重试');
       return;
     }
@@ -355,21 +383,20 @@ export function ReportProvider({ children }: { children: ReactNode }) {
     };
     
     try {
-      const success = await saveProject(updatedProject);
-      if (success) {
-        setCurrentProject(updatedProject);
-        setProjects(prev => prev.map(p => 
-          p.projectName === currentProject.projectName ? updatedProject : p
-        ));
-        toast.success('报告删除成功');
-      }
+      await invoke('delete_report', { projectName: currentProject.projectName, id: internalId });
+      setLastSaved(new Date());
+      setCurrentProject(updatedProject);
+      setProjects(prev => prev.map(p => 
+        p.projectName === currentProject.projectName ? updatedProject : p
+      ));
+      toast.success('报告删除成功');
     } catch (error) {
       console.error('Failed to delete report:', error);
       toast.error('删除报告失败');
     }
-  }, [currentProject, docStore, saveProject]);
+  }, [currentProject, isReady]);
 
-  const generateReport = useCallback(async () => {
+  const generateReport = useCallback(async (variant: ReportVariant = 'original') => {
     if (!currentProject || currentProject.reportList.length === 0) {
       toast.error('请先添加报告！');
       return;
@@ -378,17 +405,24 @@ export function ReportProvider({ children }: { children: ReactNode }) {
     try {
       const result = await invoke('generate', {
         projectName: currentProject.projectName,
-        reportList: currentProject.reportList
+        reportList: currentProject.reportList,
+        variant,
+        customFields: currentProject.customFields ?? [],
+        levelColors: settings.levelColors,
+        redaction: settings.redaction?.enabled ? settings.redaction.policy : undefined,
+        ocr: settings.redaction?.enabled && settings.redaction.ocrImages
+          ? { tesseract_path: settings.redaction.tesseractPath || null }
+          : undefined
       });
       
       toast.success(result as string);
     } catch (error) {
       toast.error(`报告生成失败: ${error}`);
     }
-  }, [currentProject]);
+  }, [currentProject, settings.levelColors, settings.redaction]);
 
   const loadDemoData = useCallback(async () => {
-    if (!docStore) return;
+    if (!isReady) return;
     
     try {
       // 批量保存所有演示项目
@@ -412,7 +446,135 @@ export function ReportProvider({ children }: { children: ReactNode }) {
       console.error('Failed to load demo data:', error);
       toast.error('加载演示数据失败，请重试');
     }
-  }, [docStore, currentProject, saveProject]);
+  }, [isReady, currentProject, saveProject]);
+
+  const exportProject = useCallback(async (projectName: string, password?: string) => {
+    try {
+      const result = await invoke<string>('export_project', { projectName, password });
+      toast.success(result);
+    } catch (error) {
+      toast.error(`导出项目失败: ${error}`);
+    }
+  }, []);
+
+  // 选择要导入的归档文件，用户取消时返回 null
+  const selectArchive = useCallback(async (): Promise<ArchiveFile | null> => {
+    try {
+      return await invoke<ArchiveFile | null>('select_project_archive');
+    } catch (error) {
+      toast.error(`读取归档文件失败: ${error}`);
+      return null;
+    }
+  }, []);
+
+  const importProject = useCallback(async (archive: ArchiveFile, password?: string, conflict?: ImportConflict) => {
+    try {
+      const summary = await invoke<ImportSummary>('import_project', {
+        path: archive.path,
+        password,
+        conflict,
+        author
+      });
+      const loadedProjects = await invoke<ReportProject[]>('load_projects');
+      setProjects(loadedProjects);
+      const imported = loadedProjects.find(p => p.projectName === summary.project_name);
+      if (imported) {
+        setCurrentProject(imported);
+      }
+      toast.success(
+        summary.skipped > 0
+          ? `项目 ${summary.project_name} 导入成功，新增 ${summary.imported} 个报告，跳过 ${summary.skipped} 个`
+          : `项目 ${summary.project_name} 导入成功，共 ${summary.imported} 个报告`
+      );
+    } catch (error) {
+      toast.error(`导入项目失败: ${error}`);
+    }
+  }, [author]);
+
+  const unlockVault = useCallback(async (password: string): Promise<boolean> => {
+    try {
+      await invoke('unlock_vault', { password });
+      await refreshVaultStatus();
+      await loadProjects();
+      setIsReady(true);
+      return true;
+    } catch (error) {
+      toast.error(`解锁失败: ${error}`);
+      return false;
+    }
+  }, [refreshVaultStatus]);
+
+  const lockVault = useCallback(async () => {
+    await invoke('lock_vault');
+    clearProjects();
+  }, []);
+
+  // 恢复备份后重新加载项目，备份的密码与当前不同时需要重新解锁
+  // 备份的加密设置与当前不同时，需要用户确认后传入 allowEncryptionChange
+  const restoreBackup = useCallback(async (id: string, allowEncryptionChange = false): Promise<boolean> => {
+    try {
+      await invoke('restore_backup', { id, allowEncryptionChange });
+      const status = await refreshVaultStatus();
+      if (status.locked) {
+        clearProjects();
+      } else {
+        const loadedProjects = await invoke<ReportProject[]>('load_projects');
+        setProjects(loadedProjects);
+        setCurrentProject(loadedProjects.length > 0 ? loadedProjects[0] : null);
+      }
+      toast.success('备份恢复成功，恢复前的数据已自动备份');
+      return true;
+    } catch (error) {
+      toast.error(`恢复备份失败: ${error}`);
+      return false;
+    }
+  }, [refreshVaultStatus]);
+
+  // 将报告字段回退到某次修改前的值
+  const revertReportField = useCallback(async (internalId: string, changeId: number): Promise<boolean> => {
+    if (!currentProject) return false;
+
+    try {
+      const reverted = await invoke<RiskReportData>('revert_report_field', {
+        projectName: currentProject.projectName,
+        id: internalId,
+        changeId,
+        author
+      });
+      const updatedProject = {
+        ...currentProject,
+        reportList: currentProject.reportList.map(r => r.id === internalId ? reverted : r)
+      };
+      setCurrentProject(updatedProject);
+      setProjects(prev => prev.map(p =>
+        p.projectName === currentProject.projectName ? updatedProject : p
+      ));
+      setLastSaved(new Date());
+      toast.success('字段已回退');
+      return true;
+    } catch (error) {
+      toast.error(`回退失败: ${error}`);
+      return false;
+    }
+  }, [currentProject, author]);
+
+  const setCustomFields = useCallback(async (projectName: string, fields: CustomFieldDef[]): Promise<boolean> => {
+    try {
+      await invoke('set_custom_fields', { projectName, fields });
+      setProjects(prev => prev.map(p =>
+        p.projectName === projectName ? { ...p, customFields: fields } : p
+      ));
+      setCurrentProject(prev =>
+        prev && prev.projectName === projectName ? { ...prev, customFields: fields } : prev
+      );
+      setLastSaved(new Date());
+      toast.success('自定义字段已保存');
+      return true;
+    } catch (error) {
+      toast.error(`保存自定义字段失败: ${error}`);
+      return false;
+    }
+  }, []);
 
   const contextValue: ReportContextType = {
     projects,
@@ -429,7 +591,17 @@ export function ReportProvider({ children }: { children: ReactNode }) {
     deleteReport,
     generateReport,
     loadDemoData,
-    saveProject
+    saveProject,
+    exportProject,
+    selectArchive,
+    importProject,
+    vaultStatus,
+    refreshVaultStatus,
+    unlockVault,
+    lockVault,
+    restoreBackup,
+    revertReportField,
+    setCustomFields
   };
 
   return (
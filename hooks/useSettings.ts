@@ -4,6 +4,7 @@ import { useState, useEffect } from 'react';
 import { Store, load } from '@tauri-apps/plugin-store';
 import { toast } from 'sonner';
 import { config } from '@/lib/config';
+import { LevelColorConfig, RedactionSettings } from '@/lib/types';
 
 // 设置数据类型
 interface SettingsData {
@@ -11,6 +12,9 @@ interface SettingsData {
   hazardTypes: string[];
   industries: string[];
   unitTypes: string[];
+  authorName?: string; // 修改人名称，记录在报告修改历史中
+  levelColors?: LevelColorConfig; // 生成报告时的隐患级别着色
+  redaction?: RedactionSettings; // 生成报告时的敏感信息脱敏
 }
 
 // 默认设置数据
@@ -44,6 +48,17 @@ export function useSettings() {
     }
   };
 
+  // 其他组件修改设置后同步到当前实例
+  useEffect(() => {
+    if (!store) return;
+    const unlisten = store.onKeyChange<SettingsData>('settings', (value) => {
+      if (value) setSettings(value);
+    });
+    return () => {
+      unlisten.then((fn) => fn());
+    };
+  }, [store]);
+
   const loadSettings = async (storeInstance: Store) => {
     try {
       const storedSettings = await storeInstance.get('settings');
@@ -181,8 +196,17 @@ export function useSettings() {
     return success;
   };
 
+  const setAuthorName = async (authorName: string) => {
+    const success = await updateSettings({ authorName: authorName.trim() });
+    if (success) {
+      toast.success('用户名已保存');
+    }
+    return success;
+  };
+
   const resetToDefaults = async () => {
-    const success = await updateSettings(defaultSettings);
+    // 用户名不属于可还原的默认配置
+    const success = await updateSettings({ ...defaultSettings, authorName: settings.authorName });
     if (success) {
       toast.success('设置已重置为默认值');
     }
@@ -199,6 +223,7 @@ export function useSettings() {
     removeIndustry,
     addUnitType,
     removeUnitType,
+    setAuthorName,
     resetToDefaults
   };
 } 
\ No newline at end of file
@@ -1,7 +1,44 @@
-// 截图内容类型，支持文本说明和图片数据
+// 代码块内容（如HTTP请求/响应报文）
+export interface CodeBlock {
+    code: string;
+    language?: string | null;   // 为 "http" 时高亮请求行与头部名称
+    line_numbers?: boolean;     // 是否显示行号
+    highlight?: string | null;  // 需要突出显示的 payload
+}
+
+// 图片尺寸策略：适应宽度 / 原始尺寸（不超过页面宽度）
+export type ImageSizing = 'fit_width' | 'original';
+
+export const IMAGE_SIZING_LABELS: Record<ImageSizing, string> = {
+  fit_width: '适应宽度',
+  original: '原始尺寸',
+};
+
+// 图片内容，caption 渲染为 "图 N-M：说明"
+export interface ScreenshotImage {
+    data: Uint8Array;
+    caption?: string | null;
+    sizing?: ImageSizing;
+    max_height?: number | null; // 最大显示高度（像素）
+    blob?: string | null; // 已保存图片的引用，加载项目时 data 为空，通过 load_image 命令按需读取
+}
+
+// 截图内容类型，支持文本说明、图片数据和代码块
+// 图片内容兼容仅包含字节数组的旧格式
 export type ScreenshotContent = 
-  | { type: 'text'; content: string }      // 文本说明
-  | { type: 'image'; content: Uint8Array }; // 图片数据
+  | { type: 'text'; content: string }                       // 文本说明
+  | { type: 'image'; content: ScreenshotImage | Uint8Array } // 图片数据
+  | { type: 'code'; content: CodeBlock };                   // 代码/HTTP报文
+
+// 获取图片内容的字节数据，已保存的图片在按需读取前为空
+export function getImageData(content: ScreenshotImage | Uint8Array): Uint8Array {
+    return 'data' in content ? content.data : content;
+}
+
+// 获取已保存图片的引用
+export function getImageBlob(content: ScreenshotImage | Uint8Array): string | null {
+    return 'data' in content ? content.blob ?? null : null;
+}
 
 // 风险隐患报告数据结构
 export interface RiskReportData {
@@ -27,9 +64,351 @@ export interface RiskReportData {
     evidence_screenshots: ScreenshotContent[]; // 支持多张证据截图（文本或图片）
     filing_screenshots: ScreenshotContent[];   // 支持多张备案截图（文本或图片）
     remark: string;
+    evidence_columns?: number; // 证据截图每行并排的图片数（1-3）
+    remediation_status?: RemediationStatus; // 修复状态
+    retest_date?: string; // 复测日期
+    retest_screenshots?: ScreenshotContent[]; // 复测证据截图
+    custom_fields?: Record<string, string>; // 项目自定义字段的值（字段名 -> 值）
+    schema_version?: number; // 数据结构版本，由后端保存时写入
 }
 
+// 隐患修复状态
+export type RemediationStatus = 'unfixed' | 'fixed' | 'partially_fixed' | 'risk_accepted';
+
+export const REMEDIATION_STATUS_LABELS: Record<RemediationStatus, string> = {
+  unfixed: '未修复',
+  fixed: '已修复',
+  partially_fixed: '部分修复',
+  risk_accepted: '风险接受',
+};
+
+// 报告类型：原始报告或复测报告
+export type ReportVariant = 'original' | 'retest';
+
+// 隐患级别着色位置（generate 命令的 levelColors 参数）
+export type LevelShadingTarget = 'none' | 'header' | 'cell';
+
+export const LEVEL_SHADING_TARGET_LABELS: Record<LevelShadingTarget, string> = {
+  none: '不着色',
+  header: '表格标题行',
+  cell: '隐患级别单元格',
+};
+
+// 隐患级别颜色配置，颜色为不带 # 的十六进制值
+export interface LevelColorConfig {
+  target: LevelShadingTarget;
+  colors: Record<string, string>;
+}
+
+export const DEFAULT_LEVEL_COLORS: LevelColorConfig = {
+  target: 'cell',
+  colors: {
+    '严重': 'FF0000',
+    '高危': 'FFA500',
+    '中危': 'FFFF00',
+    '低危': '9DC3E6',
+  },
+};
+
 export interface ReportProject {
     projectName: string;
     reportList: RiskReportData[];
-} 
\ No newline at end of file
+    customFields?: CustomFieldDef[]; // 项目声明的自定义字段
+}
+
+// 自定义字段类型
+export type CustomFieldType = 'text' | 'multiline' | 'number' | 'date';
+
+export const CUSTOM_FIELD_TYPE_LABELS: Record<CustomFieldType, string> = {
+  text: '单行文本',
+  multiline: '多行文本',
+  number: '数字',
+  date: '日期',
+};
+
+// 项目声明的自定义字段
+export interface CustomFieldDef {
+  name: string;
+  type: CustomFieldType;
+  required: boolean; // 生成报告时必须填写
+} 
+// 图片标注图形（annotate_image 命令参数），坐标与尺寸以原图像素为单位
+export type AnnotationShape =
+  | { type: 'rect'; x: number; y: number; width: number; height: number; color?: string; thickness?: number }
+  | { type: 'arrow'; from_x: number; from_y: number; to_x: number; to_y: number; color?: string; thickness?: number }
+  | { type: 'text'; x: number; y: number; text: string; color?: string; size?: number; background?: string | null }
+  | { type: 'redact'; x: number; y: number; width: number; height: number; color?: string }
+  | { type: 'blur'; x: number; y: number; width: number; height: number; sigma?: number };
+
+// 敏感信息类型与脱敏策略（generate / redact_reports 命令参数）
+export type SensitiveKind = 'cookie' | 'authorization' | 'jwt' | 'access_key' | 'id_card' | 'mobile';
+export type RedactionAction = 'keep' | 'mask' | 'remove';
+
+export interface RedactionPolicy {
+  default_action?: RedactionAction;
+  actions?: Partial<Record<SensitiveKind, RedactionAction>>;
+}
+
+export const SENSITIVE_KIND_LABELS: Record<SensitiveKind, string> = {
+  cookie: 'Cookie',
+  authorization: 'Authorization',
+  jwt: 'JWT',
+  access_key: 'AK/SK',
+  id_card: '身份证号',
+  mobile: '手机号码',
+};
+
+export const REDACTION_ACTION_LABELS: Record<RedactionAction, string> = {
+  keep: '保留',
+  mask: '部分遮盖',
+  remove: '整体替换',
+};
+
+// 生成报告时的脱敏设置
+export interface RedactionSettings {
+  enabled: boolean;
+  policy: RedactionPolicy;
+  ocrImages?: boolean;     // 使用 OCR 识别并模糊截图中的敏感信息
+  tesseractPath?: string;  // Tesseract 可执行文件路径，为空时从 PATH 查找
+}
+
+export interface RedactionHit {
+  report_id: string;
+  field: string;
+  kind: SensitiveKind;
+  replacement: string;
+}
+
+export interface RedactionSummary {
+  hits: RedactionHit[];
+  counts: Partial<Record<SensitiveKind, number>>;
+  image_regions: number; // 截图中模糊处理的区域数
+}
+
+// 截图敏感信息识别（detect_sensitive_regions 命令）
+export interface OcrOptions {
+  tesseract_path?: string | null;
+  languages?: string;
+  padding?: number;
+}
+
+export interface SensitiveRegion {
+  kind: SensitiveKind;
+  text: string;
+  x: number;
+  y: number;
+  width: number;
+  height: number;
+}
+
+// 网页整页截图配置（capture_webpage 命令参数）
+// 网页截图仅支持 Windows 和 Linux，调用前应通过 capture_supported 命令判断，macOS 上隐藏截图入口
+export interface CaptureOptions {
+  width?: number;
+  height?: number;
+  delay_ms?: number;
+  timeout_secs?: number;
+  caption?: string | null;
+}
+
+// 备案查询（lookup_icp / fill_icp_info 命令）
+export interface IcpRecord {
+  domain: string;
+  case_number: string;
+  entity_name: string;
+  entity_type: string;
+  site_name: string;
+}
+
+export type IcpProviderConfig =
+  | {
+      type: 'http';
+      endpoint: string; // {domain} 会被替换为查询域名
+      headers?: Record<string, string>;
+      fields?: { case_number?: string; entity_name?: string; entity_type?: string; site_name?: string };
+      timeout_secs?: number;
+    }
+  | { type: 'file'; path: string };
+
+// 域名解析补全（enrich_reports 命令）
+export interface ResolverConfig {
+  nameserver?: string | null; // 如 "223.5.5.5" 或 "127.0.0.1:5353"，为空时使用系统解析
+  timeout_ms?: number;
+}
+
+export interface EnrichmentResult {
+  id: string;
+  host: string | null;
+  resolved: string[];
+  reverse: string[];
+  mismatch: boolean;
+  error: string | null;
+}
+
+// IP 归属地补全（fill_cities 命令）
+export interface CityFillOptions {
+  database_path: string; // ip2region xdb 数据库路径
+  overwrite?: boolean;
+  trim_suffix?: boolean;
+}
+
+export interface CityProposal {
+  id: string;
+  ip_address: string;
+  region: { country: string; province: string; city: string; isp: string };
+  city: string;
+  applied: boolean;
+}
+
+// 项目归档导入（import_project 命令），同名项目的处理方式
+export type ImportConflict = 'rename' | 'overwrite' | 'merge';
+
+export interface ImportSummary {
+  project_name: string;
+  imported: number;
+  skipped: number;
+  conflict: boolean;
+}
+
+// 待导入的归档文件（select_project_archive 命令）
+export interface ArchiveFile {
+  path: string;
+  encrypted: boolean; // 是否需要密码
+}
+
+// 项目数据加密状态（vault_status 命令）
+export interface VaultStatus {
+  encrypted: boolean;
+  locked: boolean;
+  autoLockMinutes: number; // 0 表示不自动锁定
+}
+
+// 项目数据备份（list_backups / restore_backup 命令）
+export type BackupReason = 'scheduled' | 'manual' | 'before_delete' | 'before_import' | 'before_restore';
+
+export interface BackupInfo {
+  id: string;
+  reason: BackupReason;
+  created_at: number; // Unix 毫秒
+  size: number;
+  encrypted: boolean; // 备份数据是否已加密
+}
+
+export interface BackupConfig {
+  enabled: boolean;
+  interval_minutes: number;
+  max_versions: number;
+}
+
+// 报告字段修改记录（report_history 命令）
+export interface FieldChange {
+  id: number;
+  report_id: string;
+  field: string;
+  old_value: unknown;
+  new_value: unknown;
+  author: string;
+  changed_at: number; // Unix 秒
+}
+
+// 报告字段的显示名称
+export const REPORT_FIELD_LABELS: Record<string, string> = {
+  hazard_type: '隐患类型',
+  report_name: '隐患名称',
+  hazard_level: '隐患级别',
+  report_id: '隐患编号',
+  target: '隐患URL',
+  vul_name: '漏洞名称',
+  warning_level: '预警级别',
+  city: '归属地市',
+  unit_type: '单位类型',
+  industry: '所属行业',
+  customer_company_name: '单位名称',
+  website_name: '网站名称',
+  domain: '网站域名',
+  ip_address: '网站IP',
+  case_number: '工信备案号',
+  report_time: '发现时间',
+  problem_description: '问题描述',
+  vul_modify_repair: '整改建议',
+  evidence_screenshots: '证据截图',
+  filing_screenshots: '备案截图',
+  remark: '备注',
+  evidence_columns: '证据截图列数',
+  remediation_status: '修复状态',
+  retest_date: '复测日期',
+  retest_screenshots: '复测证据截图',
+  custom_fields: '自定义字段',
+};
+
+// 字段显示名称，自定义字段（custom_fields.<名称>）显示字段名称
+export const fieldLabel = (field: string) =>
+  REPORT_FIELD_LABELS[field] ?? field.replace(/^custom_fields\./, '');
+
+// 修改记录和项目对比中的截图摘要项，图片数据为内容哈希
+type ScreenshotSummaryItem =
+  | { type: 'text'; content: string }
+  | { type: 'image'; content: { data: string; caption?: string } }
+  | { type: 'code'; content: { code: string } };
+
+const previewText = (text: string) => (text.length > 60 ? `${text.slice(0, 60)}…` : text);
+
+const describeSummaryItem = (item: ScreenshotSummaryItem) => {
+  switch (item.type) {
+    case 'image':
+      return item.content.caption
+        ? `图片 ${item.content.data.slice(0, 12)}：${item.content.caption}`
+        : `图片 ${item.content.data.slice(0, 12)}`;
+    case 'text':
+      return `文本：${previewText(item.content)}`;
+    case 'code':
+      return `代码：${previewText(item.content.code)}`;
+    default:
+      return JSON.stringify(item);
+  }
+};
+
+// 修改前后的字段值转换为可读文本，截图逐项显示，图片以内容哈希区分
+export const formatChangeValue = (value: unknown) => {
+  if (Array.isArray(value)) {
+    return value.length === 0
+      ? '（空）'
+      : (value as ScreenshotSummaryItem[])
+          .map((item, index) => `${index + 1}. ${describeSummaryItem(item)}`)
+          .join('；');
+  }
+  if (value === null || value === undefined || value === '') {
+    return '（空）';
+  }
+  return typeof value === 'string' ? value : JSON.stringify(value);
+};
+
+// 参与对比的项目快照：当前项目或项目归档文件
+export type SnapshotSource =
+  | { type: 'project'; name: string }
+  | { type: 'archive'; path: string; password?: string };
+
+export type ChangeKind = 'added' | 'removed' | 'modified';
+
+export interface FieldDiff {
+  field: string;
+  old_value: unknown;
+  new_value: unknown;
+}
+
+export interface ReportDiff {
+  id: string;
+  kind: ChangeKind;
+  report_id: string;
+  report_name: string;
+  changes: FieldDiff[];
+}
+
+export interface ProjectDiff {
+  old_name: string;
+  new_name: string;
+  reports: ReportDiff[]; // 按新版本顺序排列，删除的报告排在最后
+  unchanged: number;
+}
+
+export type DiffFormat = 'markdown' | 'html';
@@ -0,0 +1,622 @@
+use ab_glyph::{Font, FontVec, PxScale, ScaleFont};
+use image::{imageops, Rgba, RgbaImage};
+use serde::{Deserialize, Serialize};
+
+/// 图片标注相关的错误类型
+#[derive(Debug, thiserror::Error)]
+pub enum AnnotateError {
+    #[error("图片解码失败: {0}")]
+    Decode(String),
+    #[error("图片编码失败: {0}")]
+    Encode(String),
+    #[error("颜色值无效: {0}")]
+    InvalidColor(String),
+    #[error("未找到可用于绘制文字的字体")]
+    FontNotFound,
+    #[error("裁剪区域为空或超出图片范围")]
+    EmptyRegion,
+}
+
+/// 标注图形，坐标与尺寸均以原图像素为单位
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(tag = "type")]
+pub enum Shape {
+    /// 矩形框
+    #[serde(rename = "rect")]
+    Rect {
+        x: i32,
+        y: i32,
+        width: u32,
+        height: u32,
+        #[serde(default = "default_color")]
+        color: String,
+        #[serde(default = "default_thickness")]
+        thickness: u32,
+    },
+    /// 箭头，从 (from_x, from_y) 指向 (to_x, to_y)
+    #[serde(rename = "arrow")]
+    Arrow {
+        from_x: i32,
+        from_y: i32,
+        to_x: i32,
+        to_y: i32,
+        #[serde(default = "default_color")]
+        color: String,
+        #[serde(default = "default_thickness")]
+        thickness: u32,
+    },
+    /// 文字标签，(x, y) 为左上角
+    #[serde(rename = "text")]
+    Text {
+        x: i32,
+        y: i32,
+        text: String,
+        #[serde(default = "default_color")]
+        color: String,
+        #[serde(default = "default_font_size")]
+        size: f32,
+        #[serde(default)]
+        background: Option<String>, // 可选的背景色
+    },
+    /// 纯色遮挡块
+    #[serde(rename = "redact")]
+    Redact {
+        x: i32,
+        y: i32,
+        width: u32,
+        height: u32,
+        #[serde(default = "default_redact_color")]
+        color: String,
+    },
+    /// 模糊区域
+    #[serde(rename = "blur")]
+    Blur {
+        x: i32,
+        y: i32,
+        width: u32,
+        height: u32,
+        #[serde(default = "default_blur_sigma")]
+        sigma: f32,
+    },
+}
+
+fn default_color() -> String {
+    "FF0000".to_string()
+}
+
+fn default_redact_color() -> String {
+    "000000".to_string()
+}
+
+fn default_thickness() -> u32 {
+    3
+}
+
+fn default_font_size() -> f32 {
+    24.0
+}
+
+fn default_blur_sigma() -> f32 {
+    8.0
+}
+
+/// 常见系统中文字体路径，按顺序尝试加载
+const FONT_CANDIDATES: &[&str] = &[
+    "C:\\Windows\\Fonts\\msyh.ttc",
+    "C:\\Windows\\Fonts\\simhei.ttf",
+    "C:\\Windows\\Fonts\\simsun.ttc",
+    "/System/Library/Fonts/PingFang.ttc",
+    "/System/Library/Fonts/STHeiti Medium.ttc",
+    "/Library/Fonts/Arial Unicode.ttf",
+    "/usr/share/fonts/opentype/noto/NotoSansCJK-Regular.ttc",
+    "/usr/share/fonts/noto-cjk/NotoSansCJK-Regular.ttc",
+    "/usr/share/fonts/truetype/wqy/wqy-microhei.ttc",
+    "/usr/share/fonts/truetype/dejavu/DejaVuSans.ttf",
+];
+
+/// 在图片上绘制标注图形，返回PNG格式的图片数据
+pub fn annotate_image(image_data: &[u8], shapes: &[Shape]) -> Result<Vec<u8>, AnnotateError> {
+    let mut image = image::load_from_memory(image_data)
+        .map_err(|e| AnnotateError::Decode(e.to_string()))?
+        .to_rgba8();
+
+    // 仅在需要绘制文字时加载字体
+    let font = if shapes.iter().any(|s| matches!(s, Shape::Text { .. })) {
+        Some(load_font()?)
+    } else {
+        None
+    };
+
+    for shape in shapes {
+        match shape {
+            Shape::Rect {
+                x,
+                y,
+                width,
+                height,
+                color,
+                thickness,
+            } => draw_rect(
+                &mut image,
+                *x,
+                *y,
+                *width,
+                *height,
+                parse_color(color)?,
+                *thickness,
+            ),
+            Shape::Arrow {
+                from_x,
+                from_y,
+                to_x,
+                to_y,
+                color,
+                thickness,
+            } => draw_arrow(
+                &mut image,
+                (*from_x, *from_y),
+                (*to_x, *to_y),
+                parse_color(color)?,
+                *thickness,
+            ),
+            Shape::Text {
+                x,
+                y,
+                text,
+                color,
+                size,
+                background,
+            } => {
+                let background = background.as_deref().map(parse_color).transpose()?;
+                if let Some(font) = &font {
+                    draw_text(
+                        &mut image,
+                        font,
+                        (*x, *y),
+                        text,
+                        *size,
+                        parse_color(color)?,
+                        background,
+                    );
+                }
+            }
+            Shape::Redact {
+                x,
+                y,
+                width,
+                height,
+                color,
+            } => fill_rect(&mut image, *x, *y, *width, *height, parse_color(color)?),
+            Shape::Blur {
+                x,
+                y,
+                width,
+                height,
+                sigma,
+            } => blur_region(&mut image, *x, *y, *width, *height, *sigma),
+        }
+    }
+
+    let mut buffer = std::io::Cursor::new(Vec::new());
+    image
+        .write_to(&mut buffer, image::ImageFormat::Png)
+        .map_err(|e| AnnotateError::Encode(e.to_string()))?;
+    Ok(buffer.into_inner())
+}
+
+/// 裁剪图片的指定区域，区域超出图片时取交集，返回PNG格式的图片数据
+pub fn crop_image(
+    image_data: &[u8],
+    x: i32,
+    y: i32,
+    width: u32,
+    height: u32,
+) -> Result<Vec<u8>, AnnotateError> {
+    let image = image::load_from_memory(image_data)
+        .map_err(|e| AnnotateError::Decode(e.to_string()))?
+        .to_rgba8();
+    let (x0, y0, x1, y1) =
+        clip_region(&image, x, y, width, height).ok_or(AnnotateError::EmptyRegion)?;
+    let cropped = imageops::crop_imm(&image, x0, y0, x1 - x0, y1 - y0).to_image();
+
+    let mut buffer = std::io::Cursor::new(Vec::new());
+    cropped
+        .write_to(&mut buffer, image::ImageFormat::Png)
+        .map_err(|e| AnnotateError::Encode(e.to_string()))?;
+    Ok(buffer.into_inner())
+}
+
+/// 解析 "RRGGBB" 或 "RRGGBBAA" 格式的颜色，允许 `#` 前缀
+pub fn parse_color(color: &str) -> Result<Rgba<u8>, AnnotateError> {
+    let hex = color.trim().trim_start_matches('#');
+    let invalid = || AnnotateError::InvalidColor(color.to_string());
+
+    if !(hex.len() == 6 || hex.len() == 8) || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(invalid());
+    }
+
+    let channel = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).map_err(|_| invalid());
+    let alpha = if hex.len() == 8 { channel(6)? } else { u8::MAX };
+    Ok(Rgba([channel(0)?, channel(2)?, channel(4)?, alpha]))
+}
+
+/// 加载系统字体
+fn load_font() -> Result<FontVec, AnnotateError> {
+    FONT_CANDIDATES
+        .iter()
+        .filter_map(|path| std::fs::read(path).ok())
+        .find_map(|data| FontVec::try_from_vec_and_index(data, 0).ok())
+        .ok_or(AnnotateError::FontNotFound)
+}
+
+/// 将区域裁剪到图片范围内，返回 (x0, y0, x1, y1)，区域为空时返回 None
+fn clip_region(
+    image: &RgbaImage,
+    x: i32,
+    y: i32,
+    width: u32,
+    height: u32,
+) -> Option<(u32, u32, u32, u32)> {
+    let x0 = x.max(0) as i64;
+    let y0 = y.max(0) as i64;
+    let x1 = (x as i64 + width as i64).min(image.width() as i64);
+    let y1 = (y as i64 + height as i64).min(image.height() as i64);
+
+    if x0 >= x1 || y0 >= y1 {
+        return None;
+    }
+    Some((x0 as u32, y0 as u32, x1 as u32, y1 as u32))
+}
+
+/// 按透明度混合单个像素，超出图片范围的坐标会被忽略
+fn blend_pixel(image: &mut RgbaImage, x: i32, y: i32, color: Rgba<u8>, coverage: f32) {
+    if x < 0 || y < 0 || x as u32 >= image.width() || y as u32 >= image.height() {
+        return;
+    }
+
+    let alpha = color.0[3] as f32 / 255.0 * coverage.clamp(0.0, 1.0);
+    let pixel = image.get_pixel_mut(x as u32, y as u32);
+    for channel in 0..3 {
+        let blended = color.0[channel] as f32 * alpha + pixel.0[channel] as f32 * (1.0 - alpha);
+        pixel.0[channel] = blended.round() as u8;
+    }
+    pixel.0[3] = pixel.0[3].max((alpha * 255.0).round() as u8);
+}
+
+/// 填充矩形
+fn fill_rect(image: &mut RgbaImage, x: i32, y: i32, width: u32, height: u32, color: Rgba<u8>) {
+    if let Some((x0, y0, x1, y1)) = clip_region(image, x, y, width, height) {
+        for py in y0..y1 {
+            for px in x0..x1 {
+                blend_pixel(image, px as i32, py as i32, color, 1.0);
+            }
+        }
+    }
+}
+
+/// 绘制矩形边框，边框向内绘制，坐标计算均使用饱和运算，超大尺寸不会溢出
+fn draw_rect(
+    image: &mut RgbaImage,
+    x: i32,
+    y: i32,
+    width: u32,
+    height: u32,
+    color: Rgba<u8>,
+    thickness: u32,
+) {
+    let t = thickness.max(1).min(width).min(height);
+    let inner_height = height.saturating_sub(t.saturating_mul(2));
+    fill_rect(image, x, y, width, t, color);
+    fill_rect(
+        image,
+        x,
+        y.saturating_add_unsigned(height - t),
+        width,
+        t,
+        color,
+    );
+    fill_rect(
+        image,
+        x,
+        y.saturating_add_unsigned(t),
+        t,
+        inner_height,
+        color,
+    );
+    fill_rect(
+        image,
+        x.saturating_add_unsigned(width - t),
+        y.saturating_add_unsigned(t),
+        t,
+        inner_height,
+        color,
+    );
+}
+
+/// 绘制指定粗细的线段，坐标差值按 i64 计算，端点远超图片范围时不会溢出
+fn draw_line(
+    image: &mut RgbaImage,
+    from: (i32, i32),
+    to: (i32, i32),
+    color: Rgba<u8>,
+    thickness: u32,
+) {
+    let radius = thickness.max(1) as f32 / 2.0;
+    let (dx, dy) = (delta(from.0, to.0), delta(from.1, to.1));
+    let length_sq = dx * dx + dy * dy;
+
+    let pad = (radius.ceil() as i32).saturating_add(1);
+    let (min_x, max_x) = (
+        from.0.min(to.0).saturating_sub(pad),
+        from.0.max(to.0).saturating_add(pad),
+    );
+    let (min_y, max_y) = (
+        from.1.min(to.1).saturating_sub(pad),
+        from.1.max(to.1).saturating_add(pad),
+    );
+
+    // 计算像素中心到线段的距离，距离小于半径的像素着色
+    for py in min_y.max(0)..=max_y.min(image.height() as i32 - 1) {
+        for px in min_x.max(0)..=max_x.min(image.width() as i32 - 1) {
+            let (vx, vy) = (delta(from.0, px), delta(from.1, py));
+            let t = if length_sq > 0.0 {
+                ((vx * dx + vy * dy) / length_sq).clamp(0.0, 1.0)
+            } else {
+                0.0
+            };
+            let distance = ((vx - t * dx).powi(2) + (vy - t * dy).powi(2)).sqrt();
+            if distance <= radius + 0.5 {
+                blend_pixel(image, px, py, color, (radius + 0.5 - distance).min(1.0));
+            }
+        }
+    }
+}
+
+/// 两个坐标的差值，按 i64 计算避免溢出
+fn delta(from: i32, to: i32) -> f32 {
+    (to as i64 - from as i64) as f32
+}
+
+/// 绘制箭头
+fn draw_arrow(
+    image: &mut RgbaImage,
+    from: (i32, i32),
+    to: (i32, i32),
+    color: Rgba<u8>,
+    thickness: u32,
+) {
+    draw_line(image, from, to, color, thickness);
+
+    let angle = delta(from.1, to.1).atan2(delta(from.0, to.0));
+    let head_length = (thickness.max(1) as f32 * 5.0).max(12.0);
+    for offset in [
+        std::f32::consts::PI * 5.0 / 6.0,
+        -std::f32::consts::PI * 5.0 / 6.0,
+    ] {
+        let end = (
+            to.0.saturating_add((head_length * (angle + offset).cos()).round() as i32),
+            to.1.saturating_add((head_length * (angle + offset).sin()).round() as i32),
+        );
+        draw_line(image, to, end, color, thickness);
+    }
+}
+
+/// 绘制文字标签
+fn draw_text(
+    image: &mut RgbaImage,
+    font: &FontVec,
+    position: (i32, i32),
+    text: &str,
+    size: f32,
+    color: Rgba<u8>,
+    background: Option<Rgba<u8>>,
+) {
+    let scaled = font.as_scaled(PxScale::from(size.max(1.0)));
+    let padding = (size.max(1.0) / 6.0).ceil() as i32;
+
+    // 先计算文字宽度以绘制背景
+    let mut width = 0.0;
+    let mut previous = None;
+    for ch in text.chars() {
+        let id = scaled.glyph_id(ch);
+        if let Some(prev) = previous {
+            width += scaled.kern(prev, id);
+        }
+        width += scaled.h_advance(id);
+        previous = Some(id);
+    }
+
+    if let Some(background) = background {
+        fill_rect(
+            image,
+            position.0.saturating_sub(padding),
+            position.1.saturating_sub(padding),
+            (width.ceil() as u32).saturating_add(2 * padding as u32),
+            (scaled.height().ceil() as u32).saturating_add(2 * padding as u32),
+            background,
+        );
+    }
+
+    let mut caret = ab_glyph::point(position.0 as f32, position.1 as f32 + scaled.ascent());
+    let mut previous = None;
+    for ch in text.chars() {
+        let id = scaled.glyph_id(ch);
+        if let Some(prev) = previous {
+            caret.x += scaled.kern(prev, id);
+        }
+        let glyph = id.with_scale_and_position(scaled.scale(), caret);
+        caret.x += scaled.h_advance(id);
+        previous = Some(id);
+
+        if let Some(outlined) = font.outline_glyph(glyph) {
+            let bounds = outlined.px_bounds();
+            outlined.draw(|gx, gy, coverage| {
+                blend_pixel(
+                    image,
+                    (bounds.min.x as i32).saturating_add(gx as i32),
+                    (bounds.min.y as i32).saturating_add(gy as i32),
+                    color,
+                    coverage,
+                );
+            });
+        }
+    }
+}
+
+/// 模糊指定区域
+fn blur_region(image: &mut RgbaImage, x: i32, y: i32, width: u32, height: u32, sigma: f32) {
+    if let Some((x0, y0, x1, y1)) = clip_region(image, x, y, width, height) {
+        let region = imageops::crop_imm(image, x0, y0, x1 - x0, y1 - y0).to_image();
+        let blurred = imageops::blur(&region, sigma.max(0.5));
+        imageops::replace(image, &blurred, x0 as i64, y0 as i64);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn create_test_image() -> Vec<u8> {
+        let image = RgbaImage::from_fn(100, 60, |x, _| {
+            if x % 2 == 0 {
+                Rgba([255, 255, 255, 255])
+            } else {
+                Rgba([0, 0, 255, 255])
+            }
+        });
+        let mut buffer = std::io::Cursor::new(Vec::new());
+        image
+            .write_to(&mut buffer, image::ImageFormat::Png)
+            .unwrap();
+        buffer.into_inner()
+    }
+
+    #[test]
+    fn test_parse_color() {
+        assert_eq!(parse_color("#FF0000").unwrap(), Rgba([255, 0, 0, 255]));
+        assert_eq!(parse_color("00ff0080").unwrap(), Rgba([0, 255, 0, 128]));
+        assert!(parse_color("red").is_err());
+        assert!(parse_color("#FFF").is_err());
+    }
+
+    #[test]
+    fn test_annotate_shapes() {
+        let shapes = vec![
+            Shape::Redact {
+                x: 10,
+                y: 10,
+                width: 20,
+                height: 10,
+                color: default_redact_color(),
+            },
+            Shape::Rect {
+                x: 50,
+                y: 5,
+                width: 40,
+                height: 30,
+                color: default_color(),
+                thickness: 2,
+            },
+            Shape::Blur {
+                x: 0,
+                y: 40,
+                width: 100,
+                height: 20,
+                sigma: 2.0,
+            },
+            Shape::Arrow {
+                from_x: 0,
+                from_y: 0,
+                to_x: 45,
+                to_y: 30,
+                color: default_color(),
+                thickness: 2,
+            },
+        ];
+
+        let result = annotate_image(&create_test_image(), &shapes).unwrap();
+        let image = image::load_from_memory(&result).unwrap().to_rgba8();
+        assert_eq!(image.dimensions(), (100, 60));
+
+        // 遮挡区域为纯黑色
+        assert_eq!(*image.get_pixel(15, 15), Rgba([0, 0, 0, 255]));
+        // 矩形边框为红色，内部保持不变
+        assert_eq!(*image.get_pixel(50, 20), Rgba([255, 0, 0, 255]));
+        assert_eq!(*image.get_pixel(70, 20), Rgba([255, 255, 255, 255]));
+        // 模糊区域相邻像素趋于一致
+        let (left, right) = (image.get_pixel(50, 50), image.get_pixel(51, 50));
+        assert!((left.0[0] as i32 - right.0[0] as i32).abs() < 64);
+        // 箭头终点被着色
+        assert_eq!(image.get_pixel(45, 30).0[0], 255);
+    }
+
+    #[test]
+    fn test_annotate_invalid_input() {
+        assert!(matches!(
+            annotate_image(&[0, 1, 2], &[]),
+            Err(AnnotateError::Decode(_))
+        ));
+
+        let shapes = vec![Shape::Redact {
+            x: 0,
+            y: 0,
+            width: 10,
+            height: 10,
+            color: "black".to_string(),
+        }];
+        assert!(matches!(
+            annotate_image(&create_test_image(), &shapes),
+            Err(AnnotateError::InvalidColor(_))
+        ));
+    }
+
+    #[test]
+    fn test_annotate_extreme_coordinates() {
+        // 超出 i32 范围的坐标与尺寸不应溢出，图片外的部分被忽略
+        let shapes = vec![
+            Shape::Rect {
+                x: i32::MAX - 5,
+                y: i32::MIN,
+                width: u32::MAX,
+                height: u32::MAX,
+                color: default_color(),
+                thickness: u32::MAX,
+            },
+            Shape::Rect {
+                x: -10,
+                y: -10,
+                width: u32::MAX,
+                height: 40,
+                color: default_color(),
+                thickness: 15,
+            },
+            Shape::Arrow {
+                from_x: i32::MIN,
+                from_y: i32::MIN,
+                to_x: i32::MAX,
+                to_y: i32::MAX,
+                color: default_color(),
+                thickness: u32::MAX,
+            },
+        ];
+
+        let result = annotate_image(&create_test_image(), &shapes).unwrap();
+        let image = image::load_from_memory(&result).unwrap().to_rgba8();
+        assert_eq!(image.dimensions(), (100, 60));
+        // 第二个矩形的上边框覆盖图片顶部
+        assert_eq!(*image.get_pixel(1, 2), Rgba([255, 0, 0, 255]));
+    }
+
+    #[test]
+    fn test_crop_image() {
+        let result = crop_image(&create_test_image(), 90, -5, 20, 30).unwrap();
+        let image = image::load_from_memory(&result).unwrap().to_rgba8();
+        assert_eq!(image.dimensions(), (10, 25));
+        assert_eq!(*image.get_pixel(1, 0), Rgba([0, 0, 255, 255]));
+
+        assert!(matches!(
+            crop_image(&create_test_image(), 200, 0, 10, 10),
+            Err(AnnotateError::EmptyRegion)
+        ));
+    }
+}
@@ -0,0 +1,570 @@
+use crate::crypto::{self, CryptoError, KdfParams};
+use crate::db::ReportProject;
+use crate::report::{content_hash, schema, CustomFieldDef, RiskReportData, ScreenshotContent};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::io::{Cursor, Read, Seek, Write};
+use std::time::{SystemTime, UNIX_EPOCH};
+use zip::write::FileOptions;
+use zip::{CompressionMethod, ZipArchive, ZipWriter};
+
+/// 项目归档文件扩展名
+pub const ARCHIVE_EXTENSION: &str = "rgproj";
+/// 归档格式标识
+const ARCHIVE_FORMAT: &str = "rgproj";
+/// 当前归档格式版本，格式不兼容时递增
+pub const ARCHIVE_VERSION: u32 = 1;
+
+const MANIFEST_FILE: &str = "manifest.json";
+const REPORTS_FILE: &str = "reports.json";
+const SCREENSHOT_DIR: &str = "screenshots";
+/// 归档中单个文件解压后的最大大小，防止构造的归档耗尽内存
+const MAX_ENTRY_SIZE: u64 = 64 * 1024 * 1024;
+/// 导入时解压数据的累计上限，同一截图被多次引用时按引用次数计算
+const MAX_ARCHIVE_SIZE: u64 = 1024 * 1024 * 1024;
+
+/// 项目归档相关的错误类型
+#[derive(Debug, thiserror::Error)]
+pub enum ArchiveError {
+    #[error("归档文件读写失败: {0}")]
+    Zip(#[from] zip::result::ZipError),
+    #[error("归档数据解析失败: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("不是有效的项目归档文件: {0}")]
+    InvalidArchive(String),
+    #[error("归档文件版本 {0} 高于当前支持的版本 {ARCHIVE_VERSION}，请升级应用后再导入")]
+    UnsupportedVersion(u32),
+    #[error("归档中缺少截图文件: {0}")]
+    MissingScreenshot(String),
+    #[error("归档中的文件过大: {0}")]
+    EntryTooLarge(String),
+    #[error("归档解压后的数据总量超过上限（{} MB）", .0 / 1024 / 1024)]
+    ArchiveTooLarge(u64),
+    #[error("归档文件已加密，请输入密码")]
+    PasswordRequired,
+    #[error("{0}")]
+    Crypto(#[from] CryptoError),
+    #[error("IO错误: {0}")]
+    Io(#[from] std::io::Error),
+}
+
+/// 归档清单
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Manifest {
+    pub format: String,
+    pub version: u32,
+    #[serde(rename = "projectName")]
+    pub project_name: String,
+    #[serde(rename = "reportCount")]
+    pub report_count: usize,
+    #[serde(rename = "exportedAt")]
+    pub exported_at: u64, // 导出时间（Unix 秒）
+    #[serde(rename = "appVersion", default)]
+    pub app_version: String,
+    #[serde(rename = "customFields", default)]
+    pub custom_fields: Vec<CustomFieldDef>, // 项目声明的自定义字段
+}
+
+/// 截图文件名：内容哈希加扩展名，相同图片只保存一份
+fn screenshot_path(data: &[u8]) -> String {
+    let extension = image::guess_format(data)
+        .ok()
+        .and_then(|format| format.extensions_str().first().copied())
+        .unwrap_or("bin");
+    format!("{}/{}.{}", SCREENSHOT_DIR, content_hash(data), extension)
+}
+
+/// 导出项目归档：manifest.json、reports.json 及 screenshots 目录下的图片文件。
+/// reports.json 中图片的 data 为空，file 字段指向归档内的截图文件
+pub fn export_project<W: Write + Seek>(
+    project: &ReportProject,
+    writer: W,
+) -> Result<(), ArchiveError> {
+    let mut zip = ZipWriter::new(writer);
+    let deflated = FileOptions::default().compression_method(CompressionMethod::Deflated);
+    // 图片本身已压缩，直接存储
+    let stored = FileOptions::default().compression_method(CompressionMethod::Stored);
+
+    let manifest = Manifest {
+        format: ARCHIVE_FORMAT.to_string(),
+        version: ARCHIVE_VERSION,
+        project_name: project.project_name.clone(),
+        report_count: project.report_list.len(),
+        exported_at: SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |duration| duration.as_secs()),
+        app_version: env!("CARGO_PKG_VERSION").to_string(),
+        custom_fields: project.custom_fields.clone(),
+    };
+    zip.start_file(MANIFEST_FILE, deflated)?;
+    zip.write_all(&serde_json::to_vec_pretty(&manifest)?)?;
+
+    let mut written: Vec<String> = Vec::new();
+    let mut reports = Vec::with_capacity(project.report_list.len());
+    for report in &project.report_list {
+        let mut stripped = report.clone();
+        stripped.schema_version = schema::SCHEMA_VERSION;
+        let mut files: Vec<(&str, usize, String)> = Vec::new();
+        for (field, screenshots) in stripped.screenshot_groups_mut() {
+            for (index, content) in screenshots.iter_mut().enumerate() {
+                let ScreenshotContent::Image(image) = content else {
+                    continue;
+                };
+                let data = std::mem::take(&mut image.data);
+                let path = screenshot_path(&data);
+                if !written.contains(&path) {
+                    zip.start_file(path.as_str(), stored)?;
+                    zip.write_all(&data)?;
+                    written.push(path.clone());
+                }
+                files.push((field, index, path));
+            }
+        }
+
+        let mut value = serde_json::to_value(&stripped)?;
+        for (field, index, path) in files {
+            value[field][index]["content"]["file"] = serde_json::Value::String(path);
+        }
+        reports.push(value);
+    }
+    zip.start_file(REPORTS_FILE, deflated)?;
+    zip.write_all(&serde_json::to_vec_pretty(&reports)?)?;
+
+    zip.finish()?;
+    Ok(())
+}
+
+/// 读取归档中的文件，解压后超过 limit 字节时返回错误。
+/// 不信任文件头中声明的大小，实际读取的数据同样受 limit 限制
+fn read_entry<R: Read + Seek>(
+    zip: &mut ZipArchive<R>,
+    name: &str,
+    limit: u64,
+) -> Result<Option<Vec<u8>>, ArchiveError> {
+    let file = match zip.by_name(name) {
+        Ok(file) => file,
+        Err(zip::result::ZipError::FileNotFound) => return Ok(None),
+        Err(e) => return Err(e.into()),
+    };
+    if file.size() > limit {
+        return Err(ArchiveError::EntryTooLarge(name.to_string()));
+    }
+    let mut buffer = Vec::new();
+    file.take(limit + 1).read_to_end(&mut buffer)?;
+    if buffer.len() as u64 > limit {
+        return Err(ArchiveError::EntryTooLarge(name.to_string()));
+    }
+    Ok(Some(buffer))
+}
+
+/// 导入时剩余可读取的数据量，每个文件限制大小的同时限制累计总量
+struct ReadBudget {
+    limit: u64,
+    remaining: u64,
+}
+
+impl ReadBudget {
+    fn new(limit: u64) -> Self {
+        Self {
+            limit,
+            remaining: limit,
+        }
+    }
+
+    /// 扣除已读取的数据量，超出总量时返回错误
+    fn consume(&mut self, len: usize) -> Result<(), ArchiveError> {
+        self.remaining = self
+            .remaining
+            .checked_sub(len as u64)
+            .ok_or(ArchiveError::ArchiveTooLarge(self.limit))?;
+        Ok(())
+    }
+
+    /// 读取归档中的文件并计入总量
+    fn read<R: Read + Seek>(
+        &mut self,
+        zip: &mut ZipArchive<R>,
+        name: &str,
+    ) -> Result<Option<Vec<u8>>, ArchiveError> {
+        let data = read_entry(zip, name, MAX_ENTRY_SIZE)?;
+        if let Some(data) = &data {
+            self.consume(data.len())?;
+        }
+        Ok(data)
+    }
+}
+
+/// 读取项目归档，返回清单及完整的项目数据
+pub fn import_project<R: Read + Seek>(
+    reader: R,
+) -> Result<(Manifest, ReportProject), ArchiveError> {
+    import_with_budget(reader, ReadBudget::new(MAX_ARCHIVE_SIZE))
+}
+
+fn import_with_budget<R: Read + Seek>(
+    reader: R,
+    mut budget: ReadBudget,
+) -> Result<(Manifest, ReportProject), ArchiveError> {
+    let mut zip = ZipArchive::new(reader)?;
+
+    let manifest = budget
+        .read(&mut zip, MANIFEST_FILE)?
+        .ok_or_else(|| ArchiveError::InvalidArchive(format!("缺少 {}", MANIFEST_FILE)))?;
+    let manifest: Manifest = serde_json::from_slice(&manifest)?;
+    if manifest.format != ARCHIVE_FORMAT {
+        return Err(ArchiveError::InvalidArchive(format!(
+            "未知的格式标识 {}",
+            manifest.format
+        )));
+    }
+    if manifest.version > ARCHIVE_VERSION {
+        return Err(ArchiveError::UnsupportedVersion(manifest.version));
+    }
+
+    let reports = budget
+        .read(&mut zip, REPORTS_FILE)?
+        .ok_or_else(|| ArchiveError::InvalidArchive(format!("缺少 {}", REPORTS_FILE)))?;
+    let values: Vec<serde_json::Value> = serde_json::from_slice(&reports)?;
+
+    // 同一截图可能被多个报告引用，读取后缓存
+    let mut screenshots: HashMap<String, Vec<u8>> = HashMap::new();
+    let mut report_list = Vec::with_capacity(values.len());
+    for value in values {
+        let mut report = schema::from_value(value.clone())?;
+        for (field, screenshots_mut) in report.screenshot_groups_mut() {
+            for (index, content) in screenshots_mut.iter_mut().enumerate() {
+                let ScreenshotContent::Image(image) = content else {
+                    continue;
+                };
+                // 图片数据直接内嵌在 reports.json 中时无需读取文件
+                let Some(path) = value[field][index]["content"]["file"].as_str() else {
+                    continue;
+                };
+                if !screenshots.contains_key(path) {
+                    let data = read_entry(&mut zip, path, MAX_ENTRY_SIZE)?
+                        .ok_or_else(|| ArchiveError::MissingScreenshot(path.to_string()))?;
+                    screenshots.insert(path.to_string(), data);
+                }
+                // 每次引用都会复制图片数据，按引用计入总量
+                budget.consume(screenshots[path].len())?;
+                image.data = screenshots[path].clone();
+            }
+        }
+        report_list.push(report);
+    }
+
+    let project = ReportProject {
+        project_name: manifest.project_name.clone(),
+        report_list,
+        custom_fields: manifest.custom_fields.clone(),
+    };
+    Ok((manifest, project))
+}
+
+/// 导出项目归档，设置密码时整个归档使用密码加密
+pub fn export_to_bytes(
+    project: &ReportProject,
+    password: Option<&str>,
+) -> Result<Vec<u8>, ArchiveError> {
+    let mut buffer = Cursor::new(Vec::new());
+    export_project(project, &mut buffer)?;
+    let data = buffer.into_inner();
+    match password.filter(|password| !password.is_empty()) {
+        Some(password) => Ok(crypto::seal_with_password(
+            password,
+            &data,
+            &KdfParams::default(),
+        )?),
+        None => Ok(data),
+    }
+}
+
+/// 读取可能经过密码加密的项目归档
+pub fn import_from_bytes(
+    data: Vec<u8>,
+    password: Option<&str>,
+) -> Result<(Manifest, ReportProject), ArchiveError> {
+    let data = if crypto::is_sealed(&data) {
+        let password = password
+            .filter(|password| !password.is_empty())
+            .ok_or(ArchiveError::PasswordRequired)?;
+        crypto::open_with_password(password, &data)?
+    } else {
+        data
+    };
+    import_project(Cursor::new(data))
+}
+
+/// 待导入的归档文件
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct ArchiveFile {
+    pub path: String,
+    pub encrypted: bool, // 是否需要密码
+}
+
+/// 导入同名项目时的处理方式
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq)]
+pub enum ImportConflict {
+    #[default]
+    #[serde(rename = "rename")]
+    Rename, // 以新名称导入，如 "项目 (2)"
+    #[serde(rename = "overwrite")]
+    Overwrite, // 覆盖已有项目
+    #[serde(rename = "merge")]
+    Merge, // 合并到已有项目，跳过内部ID已存在的报告
+}
+
+/// 导入结果
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct ImportSummary {
+    pub project_name: String, // 最终写入的项目名称
+    pub imported: usize,      // 导入的报告数量
+    pub skipped: usize,       // 合并时跳过的报告数量
+    pub conflict: bool,       // 是否存在同名项目
+}
+
+/// 返回不与已有项目重名的名称
+fn unique_name(name: &str, existing: &[String]) -> String {
+    (2..)
+        .map(|n| format!("{} ({})", name, n))
+        .find(|candidate| !existing.contains(candidate))
+        .unwrap_or_else(|| name.to_string())
+}
+
+/// 按冲突处理方式生成最终要保存的项目
+/// existing 为已有项目名称，current 为同名的已有项目（不存在时为 None）
+pub fn resolve_conflict(
+    mut imported: ReportProject,
+    existing: &[String],
+    current: Option<ReportProject>,
+    strategy: ImportConflict,
+) -> (ReportProject, ImportSummary) {
+    let total = imported.report_list.len();
+    let Some(mut current) = current else {
+        let summary = ImportSummary {
+            project_name: imported.project_name.clone(),
+            imported: total,
+            skipped: 0,
+            conflict: false,
+        };
+        return (imported, summary);
+    };
+
+    match strategy {
+        ImportConflict::Rename => {
+            imported.project_name = unique_name(&imported.project_name, existing);
+        }
+        ImportConflict::Overwrite => {}
+        ImportConflict::Merge => {
+            let new_reports: Vec<RiskReportData> = imported
+                .report_list
+                .into_iter()
+                .filter(|report| !current.report_list.iter().any(|r| r.id == report.id))
+                .collect();
+            let summary = ImportSummary {
+                project_name: current.project_name.clone(),
+                imported: new_reports.len(),
+                skipped: total - new_reports.len(),
+                conflict: true,
+            };
+            current.report_list.extend(new_reports);
+            // 合并归档中声明而已有项目没有的自定义字段
+            for field in imported.custom_fields {
+                if !current.custom_fields.iter().any(|f| f.name == field.name) {
+                    current.custom_fields.push(field);
+                }
+            }
+            return (current, summary);
+        }
+    }
+
+    let summary = ImportSummary {
+        project_name: imported.project_name.clone(),
+        imported: total,
+        skipped: 0,
+        conflict: true,
+    };
+    (imported, summary)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::report::ScreenshotImage;
+
+    fn sample_project() -> ReportProject {
+        let image = ScreenshotContent::Image(ScreenshotImage {
+            data: vec![0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A, 1, 2, 3],
+            caption: Some("备案信息".to_string()),
+            ..Default::default()
+        });
+        let report = |id: &str| RiskReportData {
+            id: id.to_string(),
+            report_id: format!("R-{}", id),
+            evidence_screenshots: vec![ScreenshotContent::Text("说明".to_string()), image.clone()],
+            filing_screenshots: vec![image.clone()],
+            schema_version: schema::SCHEMA_VERSION,
+            ..Default::default()
+        };
+        ReportProject {
+            project_name: "项目A".to_string(),
+            report_list: vec![report("a"), report("b")],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_archive_roundtrip() {
+        let project = sample_project();
+        let mut buffer = Cursor::new(Vec::new());
+        export_project(&project, &mut buffer).unwrap();
+
+        // 相同截图只保存一份
+        let mut zip = ZipArchive::new(Cursor::new(buffer.get_ref().clone())).unwrap();
+        let screenshot_files = zip
+            .file_names()
+            .filter(|name| name.starts_with(SCREENSHOT_DIR))
+            .count();
+        assert_eq!(screenshot_files, 1);
+        let reports = read_entry(&mut zip, REPORTS_FILE, MAX_ENTRY_SIZE)
+            .unwrap()
+            .unwrap();
+        assert!(String::from_utf8(reports).unwrap().contains(".png"));
+
+        buffer.set_position(0);
+        let (manifest, imported) = import_project(buffer).unwrap();
+        assert_eq!(manifest.version, ARCHIVE_VERSION);
+        assert_eq!(manifest.report_count, 2);
+        assert_eq!(imported.project_name, "项目A");
+        assert_eq!(
+            serde_json::to_value(&imported).unwrap(),
+            serde_json::to_value(&project).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_encrypted_archive_requires_password() {
+        let params = KdfParams {
+            memory_kib: 64,
+            iterations: 1,
+            parallelism: 1,
+        };
+        let sealed = crypto::seal_with_password("secret", b"PK", &params).unwrap();
+        assert!(matches!(
+            import_from_bytes(sealed.clone(), None),
+            Err(ArchiveError::PasswordRequired)
+        ));
+        assert!(matches!(
+            import_from_bytes(sealed, Some("wrong")),
+            Err(ArchiveError::Crypto(CryptoError::Decrypt))
+        ));
+    }
+
+    #[test]
+    fn test_archive_version_check() {
+        let mut buffer = Cursor::new(Vec::new());
+        {
+            let mut zip = ZipWriter::new(&mut buffer);
+            zip.start_file(MANIFEST_FILE, FileOptions::default())
+                .unwrap();
+            let manifest = serde_json::json!({
+                "format": ARCHIVE_FORMAT,
+                "version": ARCHIVE_VERSION + 1,
+                "projectName": "项目A",
+                "reportCount": 0,
+                "exportedAt": 0
+            });
+            zip.write_all(manifest.to_string().as_bytes()).unwrap();
+            zip.finish().unwrap();
+        }
+        buffer.set_position(0);
+        assert!(matches!(
+            import_project(buffer),
+            Err(ArchiveError::UnsupportedVersion(v)) if v == ARCHIVE_VERSION + 1
+        ));
+    }
+
+    #[test]
+    fn test_read_entry_limit() {
+        let mut buffer = Cursor::new(Vec::new());
+        {
+            let mut zip = ZipWriter::new(&mut buffer);
+            zip.start_file(REPORTS_FILE, FileOptions::default())
+                .unwrap();
+            zip.write_all(&[b' '; 100]).unwrap();
+            zip.finish().unwrap();
+        }
+        buffer.set_position(0);
+        let mut zip = ZipArchive::new(buffer).unwrap();
+        assert_eq!(
+            read_entry(&mut zip, REPORTS_FILE, 100)
+                .unwrap()
+                .unwrap()
+                .len(),
+            100
+        );
+        assert!(matches!(
+            read_entry(&mut zip, REPORTS_FILE, 99),
+            Err(ArchiveError::EntryTooLarge(_))
+        ));
+        assert!(read_entry(&mut zip, MANIFEST_FILE, 99).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_import_budget() {
+        let mut buffer = Cursor::new(Vec::new());
+        export_project(&sample_project(), &mut buffer).unwrap();
+        let data = buffer.into_inner();
+        let (_, project) = import_project(Cursor::new(data.clone())).unwrap();
+        let mut budget = 0;
+        let mut zip = ZipArchive::new(Cursor::new(data.clone())).unwrap();
+        for name in [MANIFEST_FILE, REPORTS_FILE] {
+            budget += read_entry(&mut zip, name, MAX_ENTRY_SIZE)
+                .unwrap()
+                .unwrap()
+                .len() as u64;
+        }
+        // 同一截图在两个报告中共被引用4次，按引用次数计入
+        let image_len = match &project.report_list[0].filing_screenshots[0] {
+            ScreenshotContent::Image(image) => image.data.len() as u64,
+            _ => unreachable!(),
+        };
+        budget += image_len * 4;
+
+        assert!(import_with_budget(Cursor::new(data.clone()), ReadBudget::new(budget)).is_ok());
+        assert!(matches!(
+            import_with_budget(Cursor::new(data), ReadBudget::new(budget - 1)),
+            Err(ArchiveError::ArchiveTooLarge(limit)) if limit == budget - 1
+        ));
+    }
+
+    #[test]
+    fn test_resolve_conflict() {
+        let existing = vec!["项目A".to_string(), "项目A (2)".to_string()];
+        let mut current = sample_project();
+        current.report_list.truncate(1);
+
+        let (project, summary) = resolve_conflict(
+            sample_project(),
+            &existing,
+            Some(current.clone()),
+            ImportConflict::Rename,
+        );
+        assert_eq!(project.project_name, "项目A (3)");
+        assert!(summary.conflict);
+
+        let (project, summary) = resolve_conflict(
+            sample_project(),
+            &existing,
+            Some(current),
+            ImportConflict::Merge,
+        );
+        assert_eq!(project.project_name, "项目A");
+        assert_eq!(project.report_list.len(), 2);
+        assert_eq!((summary.imported, summary.skipped), (1, 1));
+
+        let (_, summary) = resolve_conflict(sample_project(), &[], None, ImportConflict::Overwrite);
+        assert!(!summary.conflict);
+        assert_eq!(summary.imported, 2);
+    }
+}
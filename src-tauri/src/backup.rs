@@ -0,0 +1,479 @@
+use crate::db::{Database, DbError};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, MutexGuard};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// 应用数据目录下的备份目录
+pub const BACKUP_DIR: &str = "backups";
+const CONFIG_FILE: &str = "config.json";
+const STATE_FILE: &str = "state.json";
+const BACKUP_PREFIX: &str = "backup-";
+const BACKUP_EXTENSION: &str = ".db";
+
+/// 备份相关的错误类型
+#[derive(Debug, thiserror::Error)]
+pub enum BackupError {
+    #[error("{0}")]
+    Database(#[from] DbError),
+    #[error("备份配置解析失败: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("备份不存在: {0}")]
+    NotFound(String),
+    #[error(
+        "备份的加密设置与当前数据不同，恢复后将随备份关闭或启用加密，或需要使用备份时的密码解锁"
+    )]
+    EncryptionMismatch,
+    #[error("IO错误: {0}")]
+    Io(#[from] std::io::Error),
+}
+
+/// 创建备份的原因
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BackupReason {
+    #[serde(rename = "scheduled")]
+    Scheduled, // 定时备份
+    #[serde(rename = "manual")]
+    Manual, // 手动备份
+    #[serde(rename = "before_delete")]
+    BeforeDelete, // 删除项目前
+    #[serde(rename = "before_import")]
+    BeforeImport, // 导入项目前
+    #[serde(rename = "before_restore")]
+    BeforeRestore, // 恢复备份前
+}
+
+impl BackupReason {
+    const ALL: [BackupReason; 5] = [
+        BackupReason::Scheduled,
+        BackupReason::Manual,
+        BackupReason::BeforeDelete,
+        BackupReason::BeforeImport,
+        BackupReason::BeforeRestore,
+    ];
+
+    fn as_str(&self) -> &'static str {
+        match self {
+            BackupReason::Scheduled => "scheduled",
+            BackupReason::Manual => "manual",
+            BackupReason::BeforeDelete => "before_delete",
+            BackupReason::BeforeImport => "before_import",
+            BackupReason::BeforeRestore => "before_restore",
+        }
+    }
+
+    fn parse(value: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|reason| reason.as_str() == value)
+    }
+}
+
+/// 备份配置
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct BackupConfig {
+    pub enabled: bool,         // 是否启用定时备份
+    pub interval_minutes: u32, // 定时备份间隔（分钟）
+    pub max_versions: usize,   // 保留的备份数量，超出后删除最旧的备份
+}
+
+impl Default for BackupConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            interval_minutes: 30,
+            max_versions: 20,
+        }
+    }
+}
+
+/// 备份文件信息
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct BackupInfo {
+    pub id: String, // 备份文件名
+    pub reason: BackupReason,
+    pub created_at: u64, // 创建时间（Unix 毫秒）
+    pub size: u64,       // 文件大小（字节）
+    pub encrypted: bool, // 备份数据是否已加密
+}
+
+/// 备份文件名：backup-<创建时间毫秒>-<原因>.db
+fn backup_file_name(created_at: u64, reason: BackupReason) -> String {
+    format!(
+        "{}{}-{}{}",
+        BACKUP_PREFIX,
+        created_at,
+        reason.as_str(),
+        BACKUP_EXTENSION
+    )
+}
+
+/// 解析备份文件名，不是备份文件时返回 None
+fn parse_file_name(name: &str) -> Option<(u64, BackupReason)> {
+    let stem = name
+        .strip_prefix(BACKUP_PREFIX)?
+        .strip_suffix(BACKUP_EXTENSION)?;
+    let (created_at, reason) = stem.split_once('-')?;
+    Some((created_at.parse().ok()?, BackupReason::parse(reason)?))
+}
+
+fn now_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |duration| duration.as_millis() as u64)
+}
+
+/// 上次备份的时间及数据状态，保存在备份目录中，重启后仍可判断数据是否有变化
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(default)]
+struct BackupState {
+    last_backup: Option<u64>, // 上次备份时间（Unix 毫秒）
+    digest: Option<String>,   // 上次备份时数据库内容的摘要
+}
+
+/// 读取 JSON 格式的设置文件，文件不存在或损坏时使用默认值，不影响应用启动
+fn read_json_or_default<T: Default + serde::de::DeserializeOwned>(
+    path: &Path,
+) -> Result<T, BackupError> {
+    if !path.exists() {
+        return Ok(T::default());
+    }
+    Ok(
+        serde_json::from_str(&std::fs::read_to_string(path)?).unwrap_or_else(|e| {
+            log::warn!("{} 解析失败，使用默认值: {}", path.display(), e);
+            T::default()
+        }),
+    )
+}
+
+/// 项目数据备份服务，在备份目录中保存数据库快照并轮转旧版本
+pub struct BackupManager {
+    dir: PathBuf,
+    config: Mutex<BackupConfig>,
+    state: Mutex<BackupState>,
+}
+
+impl BackupManager {
+    /// 打开备份目录，不存在时自动创建
+    pub fn open(dir: PathBuf) -> Result<Self, BackupError> {
+        std::fs::create_dir_all(&dir)?;
+        let config = read_json_or_default(&dir.join(CONFIG_FILE))?;
+        let state = read_json_or_default(&dir.join(STATE_FILE))?;
+        Ok(Self {
+            dir,
+            config: Mutex::new(config),
+            state: Mutex::new(state),
+        })
+    }
+
+    /// 备份状态。创建和删除备份期间持有该锁，保证备份操作依次执行
+    fn state(&self) -> MutexGuard<'_, BackupState> {
+        self.state
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// 当前备份配置
+    pub fn config(&self) -> BackupConfig {
+        self.config
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .clone()
+    }
+
+    /// 保存备份配置，并按新的保留数量清理旧备份
+    pub fn set_config(&self, config: BackupConfig) -> Result<(), BackupError> {
+        std::fs::write(
+            self.dir.join(CONFIG_FILE),
+            serde_json::to_string_pretty(&config)?,
+        )?;
+        *self
+            .config
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner()) = config;
+        self.rotate()
+    }
+
+    /// 立即创建备份
+    pub fn create(
+        &self,
+        database: &Database,
+        reason: BackupReason,
+    ) -> Result<BackupInfo, BackupError> {
+        let mut state = self.state();
+        let digest = database.content_digest()?;
+        self.write_backup(&mut state, database, reason, digest)
+    }
+
+    /// 定时备份：到达备份间隔且数据与上次备份时不同时创建备份
+    pub fn run_scheduled(&self, database: &Database) -> Result<Option<BackupInfo>, BackupError> {
+        let config = self.config();
+        if !config.enabled {
+            return Ok(None);
+        }
+        let mut state = self.state();
+        let interval = u64::from(config.interval_minutes) * 60 * 1000;
+        if state
+            .last_backup
+            .is_some_and(|last| now_millis().saturating_sub(last) < interval)
+        {
+            return Ok(None);
+        }
+        let digest = database.content_digest()?;
+        if state.digest.as_ref() == Some(&digest) {
+            return Ok(None);
+        }
+        self.write_backup(&mut state, database, BackupReason::Scheduled, digest)
+            .map(Some)
+    }
+
+    /// 写入备份文件并记录备份时的数据状态
+    fn write_backup(
+        &self,
+        state: &mut BackupState,
+        database: &Database,
+        reason: BackupReason,
+        digest: String,
+    ) -> Result<BackupInfo, BackupError> {
+        // 保证创建时间递增，同一毫秒内的备份也能按顺序排列
+        let latest = self.list()?.first().map(|backup| backup.created_at);
+        let created_at = latest.map_or(now_millis(), |latest| now_millis().max(latest + 1));
+        let id = backup_file_name(created_at, reason);
+
+        // 先写入临时文件，避免中断时留下不完整的备份
+        let temp_path = self.dir.join(format!("{}.tmp", id));
+        if let Err(e) = database.backup_to(&temp_path) {
+            let _ = std::fs::remove_file(&temp_path);
+            return Err(e.into());
+        }
+        let path = self.dir.join(&id);
+        std::fs::rename(&temp_path, &path)?;
+
+        state.last_backup = Some(created_at);
+        state.digest = Some(digest);
+        self.save_state(state);
+
+        self.rotate()?;
+        log::info!("已创建备份: {}", id);
+        Ok(BackupInfo {
+            id,
+            reason,
+            created_at,
+            size: std::fs::metadata(&path)?.len(),
+            encrypted: backup_encrypted(&path),
+        })
+    }
+
+    /// 保存备份状态。备份文件已经写入，保存失败只会导致下次多做一次备份
+    fn save_state(&self, state: &BackupState) {
+        let result = serde_json::to_string_pretty(state)
+            .map_err(BackupError::from)
+            .and_then(|json| Ok(std::fs::write(self.dir.join(STATE_FILE), json)?));
+        if let Err(e) = result {
+            log::warn!("保存备份状态失败: {}", e);
+        }
+    }
+
+    /// 列出所有备份，最新的在前
+    pub fn list(&self) -> Result<Vec<BackupInfo>, BackupError> {
+        let mut backups = Vec::new();
+        for entry in std::fs::read_dir(&self.dir)? {
+            let entry = entry?;
+            let id = entry.file_name().to_string_lossy().to_string();
+            if let Some((created_at, reason)) = parse_file_name(&id) {
+                backups.push(BackupInfo {
+                    reason,
+                    created_at,
+                    size: entry.metadata()?.len(),
+                    encrypted: backup_encrypted(&entry.path()),
+                    id,
+                });
+            }
+        }
+        backups.sort_by_key(|backup| std::cmp::Reverse(backup.created_at));
+        Ok(backups)
+    }
+
+    /// 从备份恢复项目数据，恢复前先备份当前数据以便撤销。
+    /// 备份的加密设置与当前数据不同时，需要用户确认（allow_encryption_change）后才能恢复
+    pub fn restore(
+        &self,
+        database: &Database,
+        id: &str,
+        allow_encryption_change: bool,
+    ) -> Result<BackupInfo, BackupError> {
+        let path = self.backup_path(id)?;
+        if !allow_encryption_change && !database.same_encryption(&path)? {
+            return Err(BackupError::EncryptionMismatch);
+        }
+        let snapshot = self.create(database, BackupReason::BeforeRestore)?;
+        database.restore_from(&path)?;
+        log::info!("已从备份恢复: {}", id);
+        Ok(snapshot)
+    }
+
+    /// 删除所有备份。启用加密或修改密码后调用，旧备份中的明文或旧密码加密的数据不再保留。
+    /// 同时清除备份状态，下次定时检查时以当前数据重新备份
+    pub fn remove_all(&self) -> Result<usize, BackupError> {
+        let mut state = self.state();
+        let backups = self.list()?;
+        for backup in &backups {
+            remove_backup(&self.dir.join(&backup.id))?;
+        }
+        *state = BackupState::default();
+        self.save_state(&state);
+        log::info!("已删除全部 {} 个备份", backups.len());
+        Ok(backups.len())
+    }
+
+    /// 校验备份ID并返回文件路径，只接受备份目录中的备份文件名
+    fn backup_path(&self, id: &str) -> Result<PathBuf, BackupError> {
+        let path = self.dir.join(id);
+        if parse_file_name(id).is_none() || !path.is_file() {
+            return Err(BackupError::NotFound(id.to_string()));
+        }
+        Ok(path)
+    }
+
+    /// 删除超出保留数量的旧备份
+    fn rotate(&self) -> Result<(), BackupError> {
+        let max_versions = self.config().max_versions.max(1);
+        for backup in self.list()?.into_iter().skip(max_versions) {
+            remove_backup(&self.dir.join(&backup.id))?;
+            log::info!("已删除旧备份: {}", backup.id);
+        }
+        Ok(())
+    }
+}
+
+/// 备份文件是否已加密，无法读取时按未加密处理
+fn backup_encrypted(path: &Path) -> bool {
+    crate::db::is_encrypted_file(path).unwrap_or_else(|e| {
+        log::warn!("读取备份 {} 的加密状态失败: {}", path.display(), e);
+        false
+    })
+}
+
+/// 删除备份文件及 SQLite 可能留下的附属文件
+fn remove_backup(path: &Path) -> Result<(), BackupError> {
+    std::fs::remove_file(path)?;
+    for suffix in ["-wal", "-shm", "-journal"] {
+        let mut side = path.as_os_str().to_owned();
+        side.push(suffix);
+        let _ = std::fs::remove_file(PathBuf::from(side));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::ReportProject;
+
+    fn project(name: &str) -> ReportProject {
+        ReportProject {
+            project_name: name.to_string(),
+            report_list: Vec::new(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_parse_file_name() {
+        let name = backup_file_name(1700000000000, BackupReason::BeforeDelete);
+        assert_eq!(name, "backup-1700000000000-before_delete.db");
+        assert_eq!(
+            parse_file_name(&name),
+            Some((1700000000000, BackupReason::BeforeDelete))
+        );
+        assert_eq!(parse_file_name("backup-1-unknown.db"), None);
+        assert_eq!(parse_file_name("../reports.db"), None);
+    }
+
+    #[test]
+    fn test_open_with_corrupt_config() {
+        let dir = std::env::temp_dir().join(format!("rg_backup_config_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join(CONFIG_FILE), "{not json").unwrap();
+        let manager = BackupManager::open(dir.clone()).unwrap();
+        assert_eq!(manager.config(), BackupConfig::default());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_backup_rotate_and_restore() {
+        let dir = std::env::temp_dir().join(format!("rg_backup_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let manager = BackupManager::open(dir.clone()).unwrap();
+        manager
+            .set_config(BackupConfig {
+                max_versions: 2,
+                ..Default::default()
+            })
+            .unwrap();
+
+        let database = Database::open_in_memory().unwrap();
+        database.save_project(&project("项目A"), "").unwrap();
+        let first = manager.create(&database, BackupReason::Manual).unwrap();
+
+        // 数据没有变化时跳过定时备份，重新打开后仍然记得上次备份时的数据状态
+        let manager = BackupManager::open(dir.clone()).unwrap();
+        manager.state().last_backup = None;
+        assert!(manager.run_scheduled(&database).unwrap().is_none());
+
+        database.delete_project("项目A").unwrap();
+        database.save_project(&project("项目B"), "").unwrap();
+        assert_ne!(
+            manager.state().digest,
+            Some(database.content_digest().unwrap())
+        );
+        manager.restore(&database, &first.id, false).unwrap();
+        assert_eq!(database.project_names().unwrap(), vec!["项目A".to_string()]);
+
+        // 恢复前的数据保存为新备份
+        let backups = manager.list().unwrap();
+        assert_eq!(backups.len(), 2);
+        assert_eq!(backups[0].reason, BackupReason::BeforeRestore);
+
+        manager.create(&database, BackupReason::Manual).unwrap();
+        let backups = manager.list().unwrap();
+        assert_eq!(backups.len(), 2);
+        assert!(backups.iter().all(|backup| backup.id != first.id));
+        assert!(matches!(
+            manager.restore(&database, &first.id, false),
+            Err(BackupError::NotFound(_))
+        ));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_backup_encryption_change() {
+        let dir = std::env::temp_dir().join(format!("rg_backup_vault_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let manager = BackupManager::open(dir.clone()).unwrap();
+        let database = Database::open_in_memory().unwrap();
+        database.save_project(&project("项目A"), "").unwrap();
+        let plain = manager.create(&database, BackupReason::Manual).unwrap();
+        assert!(!plain.encrypted);
+
+        // 加密状态不同的备份需要确认后才能恢复
+        database.enable_encryption("password", 0).unwrap();
+        assert!(matches!(
+            manager.restore(&database, &plain.id, false),
+            Err(BackupError::EncryptionMismatch)
+        ));
+        let encrypted = manager.create(&database, BackupReason::Manual).unwrap();
+        assert!(encrypted.encrypted);
+        manager.restore(&database, &encrypted.id, false).unwrap();
+
+        manager.restore(&database, &plain.id, true).unwrap();
+        assert!(!database.vault_status().encrypted);
+
+        assert_eq!(manager.remove_all().unwrap(), 4);
+        assert!(manager.list().unwrap().is_empty());
+        assert_eq!(manager.state().digest, None);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
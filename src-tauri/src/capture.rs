@@ -0,0 +1,460 @@
+use crate::report::{ScreenshotContent, ScreenshotImage};
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::time::Duration;
+use tauri::webview::{PageLoadEvent, WebviewWindowBuilder};
+use tauri::{AppHandle, Url, WebviewWindow};
+use tokio::sync::mpsc;
+
+/// 网页截图相关的错误类型
+#[derive(Debug, thiserror::Error)]
+pub enum CaptureError {
+    #[error("目标地址无效: {0}")]
+    InvalidTarget(String),
+    #[error("创建截图窗口失败: {0}")]
+    Window(String),
+    #[error("等待页面响应超时（{0}秒）")]
+    Timeout(u64),
+    #[error("页面截图失败: {0}")]
+    Snapshot(String),
+    #[error("页面脚本执行失败: {0}")]
+    Script(String),
+    #[error("页面中未找到元素: {0}")]
+    ElementNotFound(String),
+    #[error("当前平台暂不支持网页截图（仅支持 Windows 和 Linux）")]
+    Unsupported,
+}
+
+/// 网页截图配置
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct CaptureOptions {
+    pub width: f64,              // 视口宽度（逻辑像素）
+    pub height: f64,             // 视口高度（逻辑像素），整页截图不受此限制
+    pub delay_ms: u64,           // 页面加载完成后的等待时间，用于渲染异步内容
+    pub timeout_secs: u64,       // 页面加载超时时间
+    pub caption: Option<String>, // 图片说明，为空时不添加
+}
+
+impl Default for CaptureOptions {
+    fn default() -> Self {
+        Self {
+            width: 1280.0,
+            height: 800.0,
+            delay_ms: 1000,
+            timeout_secs: 30,
+            caption: None,
+        }
+    }
+}
+
+/// 当前平台是否支持网页截图，前端据此隐藏截图入口
+pub const SUPPORTED: bool = cfg!(any(target_os = "windows", target_os = "linux"));
+
+/// 截图窗口序号，保证窗口标签唯一
+static CAPTURE_WINDOW_ID: AtomicU32 = AtomicU32::new(0);
+
+/// 解析截图目标，未指定协议时默认使用 http
+pub fn parse_target(target: &str) -> Result<Url, CaptureError> {
+    let target = target.trim();
+    let with_scheme = if target.contains("://") {
+        target.to_string()
+    } else {
+        format!("http://{}", target)
+    };
+
+    match with_scheme.parse::<Url>() {
+        Ok(url) if matches!(url.scheme(), "http" | "https") && url.host().is_some() => Ok(url),
+        _ => Err(CaptureError::InvalidTarget(target.to_string())),
+    }
+}
+
+/// 在不可见的窗口中打开目标网页，等待加载完成后截取整页图片
+pub async fn capture_webpage(
+    app: &AppHandle,
+    target: &str,
+    options: &CaptureOptions,
+) -> Result<ScreenshotContent, CaptureError> {
+    if !SUPPORTED {
+        return Err(CaptureError::Unsupported);
+    }
+    let url = parse_target(target)?;
+    let label = format!(
+        "capture-{}",
+        CAPTURE_WINDOW_ID.fetch_add(1, Ordering::Relaxed)
+    );
+    let (loaded_tx, mut loaded_rx) = mpsc::unbounded_channel();
+
+    // 窗口放在屏幕外而非隐藏，隐藏的 WebView 在部分平台上不会渲染页面
+    let window = WebviewWindowBuilder::new(app, &label, tauri::WebviewUrl::External(url))
+        .title("网页截图")
+        .inner_size(options.width, options.height)
+        .position(-10000.0, -10000.0)
+        .decorations(false)
+        .skip_taskbar(true)
+        .focused(false)
+        .on_page_load(move |_, payload| {
+            if payload.event() == PageLoadEvent::Finished {
+                let _ = loaded_tx.send(());
+            }
+        })
+        .build()
+        .map_err(|e| CaptureError::Window(e.to_string()))?;
+    // 超时、出错或命令被取消时均在离开作用域时关闭窗口
+    let window = WindowGuard(window);
+
+    tokio::time::timeout(Duration::from_secs(options.timeout_secs), loaded_rx.recv())
+        .await
+        .map_err(|_| CaptureError::Timeout(options.timeout_secs))?;
+    tokio::time::sleep(Duration::from_millis(options.delay_ms)).await;
+    let data = snapshot(&window.0, options.timeout_secs).await?;
+
+    Ok(ScreenshotContent::Image(ScreenshotImage {
+        data,
+        caption: options.caption.clone(),
+        ..Default::default()
+    }))
+}
+
+/// 截图窗口的守卫，析构时销毁窗口
+struct WindowGuard(WebviewWindow);
+
+impl Drop for WindowGuard {
+    fn drop(&mut self) {
+        if let Err(e) = self.0.destroy() {
+            log::warn!("关闭截图窗口失败: {}", e);
+        }
+    }
+}
+
+/// 截取窗口中网页的完整内容，返回PNG图片数据
+pub async fn snapshot(window: &WebviewWindow, timeout_secs: u64) -> Result<Vec<u8>, CaptureError> {
+    let (tx, rx) = mpsc::unbounded_channel();
+    platform::snapshot(window, tx)?;
+    receive(rx, timeout_secs).await
+}
+
+/// 在窗口中执行脚本，返回脚本结果的JSON值
+pub async fn evaluate(
+    window: &WebviewWindow,
+    script: &str,
+    timeout_secs: u64,
+) -> Result<serde_json::Value, CaptureError> {
+    let (tx, rx) = mpsc::unbounded_channel();
+    platform::evaluate(window, script.to_string(), tx)?;
+    let json = receive(rx, timeout_secs).await?;
+    serde_json::from_str(&json).map_err(|e| CaptureError::Script(e.to_string()))
+}
+
+/// 页面元素在整页截图中的位置
+#[derive(Deserialize, Debug, Clone)]
+struct ElementRect {
+    x: f64,
+    y: f64,
+    width: f64,
+    height: f64,
+    page_width: f64, // 页面总宽度，用于换算截图像素
+}
+
+/// 截取窗口整页内容，指定选择器时裁剪到对应元素
+pub async fn capture_window(
+    window: &WebviewWindow,
+    selector: Option<&str>,
+    timeout_secs: u64,
+) -> Result<Vec<u8>, CaptureError> {
+    let data = snapshot(window, timeout_secs).await?;
+    let Some(selector) = selector.filter(|s| !s.trim().is_empty()) else {
+        return Ok(data);
+    };
+
+    let script = format!(
+        "(() => {{ const el = document.querySelector({}); if (!el) return null; \
+         const r = el.getBoundingClientRect(); const doc = document.documentElement; \
+         return {{ x: r.left + window.scrollX, y: r.top + window.scrollY, width: r.width, height: r.height, \
+         page_width: Math.max(doc.scrollWidth, document.body ? document.body.scrollWidth : 0) }}; }})()",
+        serde_json::Value::from(selector)
+    );
+    let value = evaluate(window, &script, timeout_secs).await?;
+    let rect: ElementRect = serde_json::from_value(value)
+        .map_err(|_| CaptureError::ElementNotFound(selector.to_string()))?;
+
+    // 按截图宽度与页面宽度之比换算设备像素
+    let (image_width, _) = image::io::Reader::new(std::io::Cursor::new(&data))
+        .with_guessed_format()
+        .ok()
+        .and_then(|reader| reader.into_dimensions().ok())
+        .ok_or_else(|| CaptureError::Snapshot("截图数据无效".to_string()))?;
+    let scale = if rect.page_width > 0.0 {
+        image_width as f64 / rect.page_width
+    } else {
+        1.0
+    };
+
+    crate::annotate::crop_image(
+        &data,
+        (rect.x * scale).floor() as i32,
+        (rect.y * scale).floor() as i32,
+        (rect.width * scale).ceil() as u32,
+        (rect.height * scale).ceil() as u32,
+    )
+    .map_err(|e| CaptureError::Snapshot(e.to_string()))
+}
+
+/// 等待平台回调返回结果
+async fn receive<T>(
+    mut rx: mpsc::UnboundedReceiver<Result<T, CaptureError>>,
+    timeout_secs: u64,
+) -> Result<T, CaptureError> {
+    tokio::time::timeout(Duration::from_secs(timeout_secs), rx.recv())
+        .await
+        .map_err(|_| CaptureError::Timeout(timeout_secs))?
+        .unwrap_or_else(|| Err(CaptureError::Snapshot("平台回调未返回结果".to_string())))
+}
+
+type SnapshotSender = mpsc::UnboundedSender<Result<Vec<u8>, CaptureError>>;
+type ScriptSender = mpsc::UnboundedSender<Result<String, CaptureError>>;
+
+/// Windows：通过 WebView2 DevTools 协议截取整页
+#[cfg(target_os = "windows")]
+mod platform {
+    use super::{CaptureError, ScriptSender, SnapshotSender};
+    use tauri::WebviewWindow;
+    use webview2_com::Microsoft::Web::WebView2::Win32::ICoreWebView2;
+    use webview2_com::{CallDevToolsProtocolMethodCompletedHandler, ExecuteScriptCompletedHandler};
+    use windows::core::HSTRING;
+
+    pub fn snapshot(window: &WebviewWindow, tx: SnapshotSender) -> Result<(), CaptureError> {
+        window
+            .with_webview(move |webview| {
+                let fail = tx.clone();
+                let result = unsafe { webview.controller().CoreWebView2() }
+                    .and_then(|core| request_layout_metrics(core, tx));
+                if let Err(e) = result {
+                    let _ = fail.send(Err(CaptureError::Snapshot(e.to_string())));
+                }
+            })
+            .map_err(|e| CaptureError::Snapshot(e.to_string()))
+    }
+
+    pub fn evaluate(
+        window: &WebviewWindow,
+        script: String,
+        tx: ScriptSender,
+    ) -> Result<(), CaptureError> {
+        window
+            .with_webview(move |webview| {
+                let fail = tx.clone();
+                let handler =
+                    ExecuteScriptCompletedHandler::create(Box::new(move |result, json| {
+                        let _ = tx.send(
+                            result
+                                .map(|_| json)
+                                .map_err(|e| CaptureError::Script(e.to_string())),
+                        );
+                        Ok(())
+                    }));
+                let result =
+                    unsafe { webview.controller().CoreWebView2() }.and_then(|core| unsafe {
+                        core.ExecuteScript(&HSTRING::from(script), &handler)
+                    });
+                if let Err(e) = result {
+                    let _ = fail.send(Err(CaptureError::Script(e.to_string())));
+                }
+            })
+            .map_err(|e| CaptureError::Script(e.to_string()))
+    }
+
+    /// 先获取页面完整尺寸，再按该尺寸截图
+    fn request_layout_metrics(
+        core: ICoreWebView2,
+        tx: SnapshotSender,
+    ) -> windows::core::Result<()> {
+        let target = core.clone();
+        let handler =
+            CallDevToolsProtocolMethodCompletedHandler::create(Box::new(move |result, json| {
+                let fail = tx.clone();
+                let capture = result
+                    .map_err(|e| CaptureError::Snapshot(e.to_string()))
+                    .and_then(|_| page_size(&json))
+                    .map(|(width, height)| {
+                        request_screenshot(target, width, height, tx)
+                            .map_err(|e| CaptureError::Snapshot(e.to_string()))
+                    });
+                if let Err(e) | Ok(Err(e)) = capture {
+                    let _ = fail.send(Err(e));
+                }
+                Ok(())
+            }));
+
+        unsafe {
+            core.CallDevToolsProtocolMethod(
+                &HSTRING::from("Page.getLayoutMetrics"),
+                &HSTRING::from("{}"),
+                &handler,
+            )
+        }
+    }
+
+    fn request_screenshot(
+        core: ICoreWebView2,
+        width: f64,
+        height: f64,
+        tx: SnapshotSender,
+    ) -> windows::core::Result<()> {
+        let params = serde_json::json!({
+            "format": "png",
+            "captureBeyondViewport": true,
+            "clip": { "x": 0, "y": 0, "width": width, "height": height, "scale": 1 },
+        });
+        let handler =
+            CallDevToolsProtocolMethodCompletedHandler::create(Box::new(move |result, json| {
+                let _ = tx.send(
+                    result
+                        .map_err(|e| CaptureError::Snapshot(e.to_string()))
+                        .and_then(|_| decode_screenshot(&json)),
+                );
+                Ok(())
+            }));
+
+        unsafe {
+            core.CallDevToolsProtocolMethod(
+                &HSTRING::from("Page.captureScreenshot"),
+                &HSTRING::from(params.to_string()),
+                &handler,
+            )
+        }
+    }
+
+    /// 从 Page.getLayoutMetrics 的结果中读取页面内容尺寸
+    fn page_size(json: &str) -> Result<(f64, f64), CaptureError> {
+        let metrics: serde_json::Value =
+            serde_json::from_str(json).map_err(|e| CaptureError::Snapshot(e.to_string()))?;
+        let size = metrics
+            .get("cssContentSize")
+            .or_else(|| metrics.get("contentSize"))
+            .ok_or_else(|| CaptureError::Snapshot("无法获取页面尺寸".to_string()))?;
+        let read = |key: &str| size.get(key).and_then(serde_json::Value::as_f64);
+        match (read("width"), read("height")) {
+            (Some(width), Some(height)) => Ok((width.ceil(), height.ceil())),
+            _ => Err(CaptureError::Snapshot("无法获取页面尺寸".to_string())),
+        }
+    }
+
+    /// 解析 Page.captureScreenshot 返回的 base64 图片数据
+    fn decode_screenshot(json: &str) -> Result<Vec<u8>, CaptureError> {
+        use base64::Engine;
+
+        let value: serde_json::Value =
+            serde_json::from_str(json).map_err(|e| CaptureError::Snapshot(e.to_string()))?;
+        let data = value
+            .get("data")
+            .and_then(serde_json::Value::as_str)
+            .ok_or_else(|| CaptureError::Snapshot("截图结果为空".to_string()))?;
+        base64::engine::general_purpose::STANDARD
+            .decode(data)
+            .map_err(|e| CaptureError::Snapshot(e.to_string()))
+    }
+}
+
+/// Linux：通过 WebKitGTK 截取整个文档
+#[cfg(target_os = "linux")]
+mod platform {
+    use super::{CaptureError, ScriptSender, SnapshotSender};
+    use javascriptcore::ValueExt;
+    use tauri::WebviewWindow;
+    use webkit2gtk::{gio, SnapshotOptions, SnapshotRegion, WebViewExt};
+
+    pub fn snapshot(window: &WebviewWindow, tx: SnapshotSender) -> Result<(), CaptureError> {
+        window
+            .with_webview(move |webview| {
+                webview.inner().snapshot(
+                    SnapshotRegion::FullDocument,
+                    SnapshotOptions::NONE,
+                    None::<&gio::Cancellable>,
+                    move |result| {
+                        let _ = tx.send(
+                            result
+                                .map_err(|e| CaptureError::Snapshot(e.to_string()))
+                                .and_then(surface_to_png),
+                        );
+                    },
+                );
+            })
+            .map_err(|e| CaptureError::Snapshot(e.to_string()))
+    }
+
+    pub fn evaluate(
+        window: &WebviewWindow,
+        script: String,
+        tx: ScriptSender,
+    ) -> Result<(), CaptureError> {
+        window
+            .with_webview(move |webview| {
+                webview.inner().evaluate_javascript(
+                    &script,
+                    None,
+                    None,
+                    None::<&gio::Cancellable>,
+                    move |result| {
+                        let _ = tx.send(
+                            result
+                                .map(|value| {
+                                    value
+                                        .to_json(0)
+                                        .map_or_else(|| "null".to_string(), |json| json.to_string())
+                                })
+                                .map_err(|e| CaptureError::Script(e.to_string())),
+                        );
+                    },
+                );
+            })
+            .map_err(|e| CaptureError::Script(e.to_string()))
+    }
+
+    fn surface_to_png(surface: cairo::Surface) -> Result<Vec<u8>, CaptureError> {
+        let mut png = Vec::new();
+        surface
+            .write_to_png(&mut png)
+            .map_err(|e| CaptureError::Snapshot(e.to_string()))?;
+        Ok(png)
+    }
+}
+
+/// 其他平台暂不支持。macOS 需通过 WKWebView 的 takeSnapshotWithConfiguration 实现，
+/// 尚未接入，截图命令在该平台直接返回 Unsupported
+#[cfg(not(any(target_os = "windows", target_os = "linux")))]
+mod platform {
+    use super::{CaptureError, ScriptSender, SnapshotSender};
+    use tauri::WebviewWindow;
+
+    pub fn snapshot(_window: &WebviewWindow, _tx: SnapshotSender) -> Result<(), CaptureError> {
+        Err(CaptureError::Unsupported)
+    }
+
+    pub fn evaluate(
+        _window: &WebviewWindow,
+        _script: String,
+        _tx: ScriptSender,
+    ) -> Result<(), CaptureError> {
+        Err(CaptureError::Unsupported)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_target() {
+        assert_eq!(
+            parse_target("example.com/login").unwrap().as_str(),
+            "http://example.com/login"
+        );
+        assert_eq!(
+            parse_target(" https://example.com ").unwrap().as_str(),
+            "https://example.com/"
+        );
+        assert!(parse_target("file:///etc/passwd").is_err());
+        assert!(parse_target("").is_err());
+    }
+}
@@ -0,0 +1,243 @@
+use argon2::{Algorithm, Argon2, Params, Version};
+use chacha20poly1305::aead::{Aead, KeyInit, Payload};
+use chacha20poly1305::{Key, XChaCha20Poly1305, XNonce};
+use hkdf::Hkdf;
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use zeroize::Zeroizing;
+
+/// 加解密相关的错误类型
+#[derive(Debug, thiserror::Error)]
+pub enum CryptoError {
+    #[error("密钥派生失败: {0}")]
+    KeyDerivation(String),
+    #[error("数据加密失败")]
+    Encrypt,
+    #[error("密码错误或数据已损坏")]
+    Decrypt,
+    #[error("加密数据格式无效")]
+    InvalidFormat,
+    #[error("随机数生成失败: {0}")]
+    Random(String),
+}
+
+const KEY_LENGTH: usize = 32;
+pub const SALT_LENGTH: usize = 16;
+const NONCE_LENGTH: usize = 24;
+
+/// 派生图片标识子密钥使用的标签，与加密密钥相互独立
+const BLOB_ID_LABEL: &[u8] = b"blob-id";
+
+/// 密码保护文件的标识，后接密钥派生参数、盐和密文
+const SEALED_MAGIC: &[u8; 8] = b"RGSEALv1";
+const SEALED_HEADER_LENGTH: usize = SEALED_MAGIC.len() + 12 + SALT_LENGTH;
+
+/// 读取外部文件时允许的密钥派生参数上限，防止构造的文件耗尽内存
+const MAX_MEMORY_KIB: u32 = 1024 * 1024;
+const MAX_ITERATIONS: u32 = 64;
+const MAX_PARALLELISM: u32 = 16;
+
+/// Argon2id 密钥派生参数
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(default)]
+pub struct KdfParams {
+    pub memory_kib: u32,  // 内存开销（KiB）
+    pub iterations: u32,  // 迭代次数
+    pub parallelism: u32, // 并行度
+}
+
+impl Default for KdfParams {
+    fn default() -> Self {
+        Self {
+            memory_kib: 64 * 1024,
+            iterations: 3,
+            parallelism: 1,
+        }
+    }
+}
+
+/// 生成随机字节，用于盐和随机数
+pub fn random_bytes<const N: usize>() -> Result<[u8; N], CryptoError> {
+    let mut buffer = [0u8; N];
+    getrandom::getrandom(&mut buffer).map_err(|e| CryptoError::Random(e.to_string()))?;
+    Ok(buffer)
+}
+
+/// 由密码派生的对称密钥，使用 XChaCha20-Poly1305 加密，释放时清零
+pub struct Cipher {
+    key: Zeroizing<[u8; KEY_LENGTH]>,
+    blob_id_key: Zeroizing<[u8; KEY_LENGTH]>, // 经 HKDF 派生，只用于计算图片标识
+}
+
+impl Cipher {
+    /// 使用 Argon2id 从密码派生密钥
+    pub fn derive(password: &str, salt: &[u8], params: &KdfParams) -> Result<Self, CryptoError> {
+        let params = Params::new(
+            params.memory_kib,
+            params.iterations,
+            params.parallelism,
+            Some(KEY_LENGTH),
+        )
+        .map_err(|e| CryptoError::KeyDerivation(e.to_string()))?;
+        let mut key = Zeroizing::new([0u8; KEY_LENGTH]);
+        Argon2::new(Algorithm::Argon2id, Version::V0x13, params)
+            .hash_password_into(password.as_bytes(), salt, &mut *key)
+            .map_err(|e| CryptoError::KeyDerivation(e.to_string()))?;
+
+        let mut blob_id_key = Zeroizing::new([0u8; KEY_LENGTH]);
+        Hkdf::<Sha256>::new(None, &*key)
+            .expand(BLOB_ID_LABEL, &mut *blob_id_key)
+            .map_err(|e| CryptoError::KeyDerivation(e.to_string()))?;
+        Ok(Self { key, blob_id_key })
+    }
+
+    /// 加密数据，输出为 随机数 + 密文。aad 为附加认证数据，解密时必须一致
+    pub fn encrypt(&self, plaintext: &[u8], aad: &[u8]) -> Result<Vec<u8>, CryptoError> {
+        let nonce = random_bytes::<NONCE_LENGTH>()?;
+        let ciphertext = XChaCha20Poly1305::new(Key::from_slice(&*self.key))
+            .encrypt(
+                XNonce::from_slice(&nonce),
+                Payload {
+                    msg: plaintext,
+                    aad,
+                },
+            )
+            .map_err(|_| CryptoError::Encrypt)?;
+
+        let mut output = Vec::with_capacity(NONCE_LENGTH + ciphertext.len());
+        output.extend_from_slice(&nonce);
+        output.extend_from_slice(&ciphertext);
+        Ok(output)
+    }
+
+    /// 解密 encrypt 的输出
+    pub fn decrypt(&self, data: &[u8], aad: &[u8]) -> Result<Vec<u8>, CryptoError> {
+        if data.len() < NONCE_LENGTH {
+            return Err(CryptoError::InvalidFormat);
+        }
+        let (nonce, ciphertext) = data.split_at(NONCE_LENGTH);
+        XChaCha20Poly1305::new(Key::from_slice(&*self.key))
+            .decrypt(
+                XNonce::from_slice(nonce),
+                Payload {
+                    msg: ciphertext,
+                    aad,
+                },
+            )
+            .map_err(|_| CryptoError::Decrypt)
+    }
+
+    /// 带密钥的内容哈希（HMAC-SHA256），加密存储时代替明文哈希，避免通过哈希推断图片内容
+    pub fn keyed_hash(&self, data: &[u8]) -> String {
+        let mut mac = <Hmac<Sha256> as Mac>::new_from_slice(&*self.blob_id_key)
+            .expect("HMAC 接受任意长度的密钥");
+        mac.update(data);
+        format!("{:x}", mac.finalize().into_bytes())
+    }
+}
+
+/// 判断数据是否为密码保护文件
+pub fn is_sealed(data: &[u8]) -> bool {
+    data.starts_with(SEALED_MAGIC)
+}
+
+/// 使用密码加密整个文件（如导出的项目归档），派生参数和盐保存在文件头中
+pub fn seal_with_password(
+    password: &str,
+    plaintext: &[u8],
+    params: &KdfParams,
+) -> Result<Vec<u8>, CryptoError> {
+    let salt = random_bytes::<SALT_LENGTH>()?;
+    let mut header = Vec::with_capacity(SEALED_HEADER_LENGTH);
+    header.extend_from_slice(SEALED_MAGIC);
+    header.extend_from_slice(&params.memory_kib.to_le_bytes());
+    header.extend_from_slice(&params.iterations.to_le_bytes());
+    header.extend_from_slice(&params.parallelism.to_le_bytes());
+    header.extend_from_slice(&salt);
+
+    // 文件头作为附加认证数据，篡改参数会导致解密失败
+    let ciphertext = Cipher::derive(password, &salt, params)?.encrypt(plaintext, &header)?;
+    header.extend_from_slice(&ciphertext);
+    Ok(header)
+}
+
+/// 解密 seal_with_password 的输出
+pub fn open_with_password(password: &str, data: &[u8]) -> Result<Vec<u8>, CryptoError> {
+    if !is_sealed(data) || data.len() < SEALED_HEADER_LENGTH {
+        return Err(CryptoError::InvalidFormat);
+    }
+    let (header, ciphertext) = data.split_at(SEALED_HEADER_LENGTH);
+    let read_u32 = |offset: usize| {
+        let bytes = &header[offset..offset + 4];
+        u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]])
+    };
+    let magic = SEALED_MAGIC.len();
+    let params = KdfParams {
+        memory_kib: read_u32(magic),
+        iterations: read_u32(magic + 4),
+        parallelism: read_u32(magic + 8),
+    };
+    if params.memory_kib > MAX_MEMORY_KIB
+        || params.iterations > MAX_ITERATIONS
+        || params.parallelism > MAX_PARALLELISM
+    {
+        return Err(CryptoError::InvalidFormat);
+    }
+    let salt = &header[magic + 12..];
+
+    Cipher::derive(password, salt, &params)?.decrypt(ciphertext, header)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 测试使用较小的派生参数
+    const TEST_PARAMS: KdfParams = KdfParams {
+        memory_kib: 64,
+        iterations: 1,
+        parallelism: 1,
+    };
+
+    #[test]
+    fn test_cipher_roundtrip() {
+        let cipher = Cipher::derive("密码123", b"0123456789abcdef", &TEST_PARAMS).unwrap();
+        let encrypted = cipher.encrypt("漏洞详情".as_bytes(), b"reports").unwrap();
+        assert_eq!(
+            cipher.decrypt(&encrypted, b"reports").unwrap(),
+            "漏洞详情".as_bytes()
+        );
+        // 附加认证数据不一致时无法解密
+        assert!(matches!(
+            cipher.decrypt(&encrypted, b"blobs"),
+            Err(CryptoError::Decrypt)
+        ));
+
+        let other = Cipher::derive("其他密码", b"0123456789abcdef", &TEST_PARAMS).unwrap();
+        assert!(other.decrypt(&encrypted, b"reports").is_err());
+        assert_ne!(cipher.keyed_hash(b"a"), other.keyed_hash(b"a"));
+        assert_eq!(cipher.keyed_hash(b"a"), cipher.keyed_hash(b"a"));
+        assert_eq!(cipher.keyed_hash(b"a").len(), 64);
+    }
+
+    #[test]
+    fn test_seal_with_password() {
+        let sealed = seal_with_password("secret", b"archive", &TEST_PARAMS).unwrap();
+        assert!(is_sealed(&sealed));
+        assert_eq!(open_with_password("secret", &sealed).unwrap(), b"archive");
+        assert!(matches!(
+            open_with_password("wrong", &sealed),
+            Err(CryptoError::Decrypt)
+        ));
+
+        // 篡改文件头中的参数
+        let mut tampered = sealed.clone();
+        tampered[SEALED_MAGIC.len() + 4] ^= 1;
+        assert!(open_with_password("secret", &tampered).is_err());
+        assert!(matches!(
+            open_with_password("secret", b"PK\x03\x04"),
+            Err(CryptoError::InvalidFormat)
+        ));
+    }
+}
@@ -0,0 +1,159 @@
+use super::{find_project, open, seal, touch_project, Database, DbError};
+use crate::crypto::Cipher;
+use crate::report::CustomFieldDef;
+use rusqlite::types::Value as SqlValue;
+use rusqlite::{params, Connection, OptionalExtension, Transaction};
+
+const FIELDS_AAD: &[u8] = b"project_fields.data";
+
+/// 检查字段声明：字段名不能为空且不能重复，字段名需已去除首尾空白
+fn validate(fields: &[CustomFieldDef]) -> Result<(), DbError> {
+    for (index, field) in fields.iter().enumerate() {
+        let name = field.name.as_str();
+        if name.is_empty() {
+            return Err(DbError::InvalidCustomField(format!(
+                "第{}个字段名称为空",
+                index + 1
+            )));
+        }
+        if fields[..index].iter().any(|other| other.name == name) {
+            return Err(DbError::InvalidCustomField(format!(
+                "字段名称重复: {}",
+                name
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// 读取项目声明的自定义字段
+pub(super) fn load_custom_fields(
+    conn: &Connection,
+    cipher: Option<&Cipher>,
+    project_id: i64,
+) -> Result<Vec<CustomFieldDef>, DbError> {
+    let data = conn
+        .prepare_cached("SELECT data FROM project_fields WHERE project_id = ?1")?
+        .query_row(params![project_id], |row| row.get::<_, SqlValue>(0))
+        .optional()?;
+    match data {
+        Some(data) => Ok(serde_json::from_slice(&open(
+            cipher,
+            (&data).into(),
+            FIELDS_AAD,
+        )?)?),
+        None => Ok(Vec::new()),
+    }
+}
+
+/// 保存项目声明的自定义字段，字段名去除首尾空白后保存，为空时删除记录
+pub(super) fn save_custom_fields(
+    tx: &Transaction,
+    cipher: Option<&Cipher>,
+    project_id: i64,
+    fields: &[CustomFieldDef],
+) -> Result<(), DbError> {
+    let fields: Vec<CustomFieldDef> = fields
+        .iter()
+        .map(|field| CustomFieldDef {
+            name: field.name.trim().to_string(),
+            ..field.clone()
+        })
+        .collect();
+    validate(&fields)?;
+    if fields.is_empty() {
+        tx.execute(
+            "DELETE FROM project_fields WHERE project_id = ?1",
+            params![project_id],
+        )?;
+        return Ok(());
+    }
+    tx.execute(
+        "INSERT OR REPLACE INTO project_fields (project_id, data) VALUES (?1, ?2)",
+        params![
+            project_id,
+            seal(cipher, serde_json::to_vec(&fields)?, FIELDS_AAD)?
+        ],
+    )?;
+    Ok(())
+}
+
+impl Database {
+    /// 设置项目声明的自定义字段，已填写的字段值保留在报告中
+    pub fn set_custom_fields(
+        &self,
+        project_name: &str,
+        fields: &[CustomFieldDef],
+    ) -> Result<(), DbError> {
+        let vault = self.unlocked()?;
+        let mut conn = self.conn();
+        let tx = conn.transaction()?;
+        let project_id = find_project(&tx, project_name)?;
+        save_custom_fields(&tx, vault.cipher(), project_id, fields)?;
+        touch_project(&tx, project_id)?;
+        tx.commit()?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::ReportProject;
+    use crate::report::CustomFieldType;
+
+    fn field(name: &str) -> CustomFieldDef {
+        CustomFieldDef {
+            name: name.to_string(),
+            field_type: CustomFieldType::Text,
+            required: false,
+        }
+    }
+
+    #[test]
+    fn test_custom_fields() {
+        let database = Database::open_in_memory().unwrap();
+        database
+            .save_project(
+                &ReportProject {
+                    project_name: "项目A".to_string(),
+                    custom_fields: vec![field("端口")],
+                    ..Default::default()
+                },
+                "",
+            )
+            .unwrap();
+        assert_eq!(
+            database.load_project("项目A").unwrap().custom_fields,
+            vec![field("端口")]
+        );
+
+        database
+            .set_custom_fields("项目A", &[field("端口"), field("CVE编号")])
+            .unwrap();
+        database.enable_encryption("secret", 0).unwrap();
+        assert_eq!(
+            database.load_projects().unwrap()[0].custom_fields,
+            vec![field("端口"), field("CVE编号")]
+        );
+
+        assert!(matches!(
+            database.set_custom_fields("项目A", &[field("端口"), field(" 端口 ")]),
+            Err(DbError::InvalidCustomField(_))
+        ));
+        // 字段名去除首尾空白后保存
+        database
+            .set_custom_fields("项目A", &[field(" 系统名称 ")])
+            .unwrap();
+        assert_eq!(
+            database.load_project("项目A").unwrap().custom_fields,
+            vec![field("系统名称")]
+        );
+        database.set_custom_fields("项目A", &[]).unwrap();
+        assert!(database
+            .load_project("项目A")
+            .unwrap()
+            .custom_fields
+            .is_empty());
+    }
+}
@@ -0,0 +1,357 @@
+use super::{find_report, open, save_report_with_history, seal, Database, DbError};
+use crate::crypto::Cipher;
+use crate::report::{
+    content_hash, RiskReportData, ScreenshotContent, ScreenshotImage, SCREENSHOT_FIELDS,
+};
+use rusqlite::types::Value as SqlValue;
+use rusqlite::{params, Connection, Transaction};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+const HISTORY_AAD: &[u8] = b"report_history.change";
+
+/// 不记录修改历史的字段
+const IGNORED_FIELDS: [&str; 2] = ["id", "schema_version"];
+
+/// 报告字段的一次修改
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct FieldChange {
+    pub id: i64,
+    pub report_id: String, // 报告内部ID
+    pub field: String,
+    pub old_value: Value,
+    pub new_value: Value,
+    pub author: String,  // 修改人，来自应用设置
+    pub changed_at: i64, // 修改时间（Unix 秒）
+}
+
+/// 加密保存的修改内容
+#[derive(Serialize, Deserialize)]
+struct StoredChange {
+    old_value: Value,
+    new_value: Value,
+}
+
+/// 截图列表的摘要，图片数据替换为内容哈希
+fn screenshot_summary(screenshots: &[ScreenshotContent]) -> Result<Value, DbError> {
+    screenshots
+        .iter()
+        .map(|content| {
+            let ScreenshotContent::Image(image) = content else {
+                return Ok(serde_json::to_value(content)?);
+            };
+            let mut value = serde_json::to_value(ScreenshotContent::Image(ScreenshotImage {
+                data: Vec::new(),
+                ..image.clone()
+            }))?;
+            value["content"]["data"] = Value::String(content_hash(&image.data));
+            Ok(value)
+        })
+        .collect::<Result<Vec<_>, DbError>>()
+        .map(Value::Array)
+}
+
+/// 报告各字段的值，截图字段替换为摘要
+fn field_values(report: &RiskReportData) -> Result<Map<String, Value>, DbError> {
+    let mut stripped = report.clone();
+    let screenshots: Vec<(&str, Vec<ScreenshotContent>)> = stripped
+        .screenshot_groups_mut()
+        .into_iter()
+        .map(|(field, contents)| (field, std::mem::take(contents)))
+        .collect();
+    let Value::Object(mut fields) = serde_json::to_value(&stripped)? else {
+        return Ok(Map::new());
+    };
+    for (field, contents) in screenshots {
+        fields.insert(field.to_string(), screenshot_summary(&contents)?);
+    }
+    Ok(fields)
+}
+
+/// 比较报告的两个版本，返回有变化的字段及修改前后的值
+pub fn diff_fields(
+    old: &RiskReportData,
+    new: &RiskReportData,
+) -> Result<Vec<(String, Value, Value)>, DbError> {
+    let old = field_values(old)?;
+    let new = field_values(new)?;
+    let mut fields: Vec<&String> = old.keys().chain(new.keys()).collect();
+    fields.sort();
+    fields.dedup();
+
+    Ok(fields
+        .into_iter()
+        .filter(|field| !IGNORED_FIELDS.contains(&field.as_str()))
+        .filter_map(|field| {
+            let old_value = old.get(field).cloned().unwrap_or(Value::Null);
+            let new_value = new.get(field).cloned().unwrap_or(Value::Null);
+            (old_value != new_value).then(|| (field.clone(), old_value, new_value))
+        })
+        .collect())
+}
+
+/// 记录报告修改前后的字段差异，返回记录的字段数
+pub(super) fn record_changes(
+    tx: &Transaction,
+    cipher: Option<&Cipher>,
+    project_id: i64,
+    old: &RiskReportData,
+    new: &RiskReportData,
+    author: &str,
+) -> Result<usize, DbError> {
+    let changes = diff_fields(old, new)?;
+    let changed_at = super::now();
+    for (field, old_value, new_value) in &changes {
+        let change = serde_json::to_vec(&StoredChange {
+            old_value: old_value.clone(),
+            new_value: new_value.clone(),
+        })?;
+        tx.execute(
+            "INSERT INTO report_history (project_id, report_id, field, change, author, changed_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            params![
+                project_id,
+                new.id,
+                field,
+                seal(cipher, change, HISTORY_AAD)?,
+                author,
+                changed_at
+            ],
+        )?;
+    }
+    Ok(changes.len())
+}
+
+/// 使用新密钥重写所有修改记录
+pub(super) fn reseal_history(
+    tx: &Transaction,
+    old: Option<&Cipher>,
+    new: Option<&Cipher>,
+) -> Result<(), DbError> {
+    let rows = tx
+        .prepare("SELECT id, change FROM report_history")?
+        .query_map([], |row| {
+            Ok((row.get::<_, i64>(0)?, row.get::<_, SqlValue>(1)?))
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+    for (id, change) in rows {
+        let change = open(old, (&change).into(), HISTORY_AAD)?;
+        tx.execute(
+            "UPDATE report_history SET change = ?1 WHERE id = ?2",
+            params![seal(new, change, HISTORY_AAD)?, id],
+        )?;
+    }
+    Ok(())
+}
+
+/// 读取报告的修改记录，最新的在前
+fn load_history(
+    conn: &Connection,
+    cipher: Option<&Cipher>,
+    project_id: i64,
+    report_id: &str,
+) -> Result<Vec<FieldChange>, DbError> {
+    let rows = conn
+        .prepare(
+            "SELECT id, field, change, author, changed_at FROM report_history
+             WHERE project_id = ?1 AND report_id = ?2 ORDER BY id DESC",
+        )?
+        .query_map(params![project_id, report_id], |row| {
+            Ok((
+                row.get::<_, i64>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, SqlValue>(2)?,
+                row.get::<_, String>(3)?,
+                row.get::<_, i64>(4)?,
+            ))
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    rows.into_iter()
+        .map(|(id, field, change, author, changed_at)| {
+            let change: StoredChange =
+                serde_json::from_slice(&open(cipher, (&change).into(), HISTORY_AAD)?)?;
+            Ok(FieldChange {
+                id,
+                report_id: report_id.to_string(),
+                field,
+                old_value: change.old_value,
+                new_value: change.new_value,
+                author,
+                changed_at,
+            })
+        })
+        .collect()
+}
+
+impl Database {
+    /// 报告的修改记录，最新的在前
+    pub fn report_history(
+        &self,
+        project_name: &str,
+        report_id: &str,
+    ) -> Result<Vec<FieldChange>, DbError> {
+        let vault = self.unlocked()?;
+        let conn = self.conn();
+        let project_id = super::find_project(&conn, project_name)?;
+        load_history(&conn, vault.cipher(), project_id, report_id)
+    }
+
+    /// 将字段回退到某次修改前的值，回退本身也记录为一次修改。返回回退后的报告
+    pub fn revert_field(
+        &self,
+        project_name: &str,
+        report_id: &str,
+        change_id: i64,
+        author: &str,
+    ) -> Result<RiskReportData, DbError> {
+        let vault = self.unlocked()?;
+        let mut conn = self.conn();
+        let tx = conn.transaction()?;
+        let project_id = super::find_project(&tx, project_name)?;
+        let change = load_history(&tx, vault.cipher(), project_id, report_id)?
+            .into_iter()
+            .find(|change| change.id == change_id)
+            .ok_or(DbError::ChangeNotFound(change_id))?;
+        // 截图字段只记录摘要（图片以内容哈希表示），不支持回退
+        if SCREENSHOT_FIELDS
+            .iter()
+            .any(|(field, _)| *field == change.field)
+        {
+            return Err(DbError::FieldNotRevertible(change.field));
+        }
+
+        let current = find_report(&tx, vault.cipher(), project_id, report_id)?
+            .ok_or_else(|| DbError::ReportNotFound(report_id.to_string()))?;
+        let Value::Object(mut fields) = serde_json::to_value(&current)? else {
+            return Err(DbError::FieldNotRevertible(change.field));
+        };
+        fields.insert(change.field, change.old_value);
+        let reverted: RiskReportData = serde_json::from_value(Value::Object(fields))?;
+
+        save_report_with_history(&tx, vault.cipher(), project_id, &reverted, author)?;
+        tx.commit()?;
+        Ok(reverted)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::ReportProject;
+
+    #[test]
+    fn test_diff_fields() {
+        let old = RiskReportData {
+            id: "a".to_string(),
+            hazard_level: "高危".to_string(),
+            ..Default::default()
+        };
+        let mut new = RiskReportData {
+            hazard_level: "中危".to_string(),
+            ..old.clone()
+        };
+        new.evidence_screenshots
+            .push(ScreenshotContent::Image(ScreenshotImage {
+                data: vec![1, 2, 3],
+                ..Default::default()
+            }));
+
+        let changes = diff_fields(&old, &new).unwrap();
+        let fields: Vec<&str> = changes.iter().map(|(field, _, _)| field.as_str()).collect();
+        assert_eq!(fields, vec!["evidence_screenshots", "hazard_level"]);
+        assert_eq!(changes[1].1, Value::String("高危".to_string()));
+        // 图片数据以哈希表示
+        assert_eq!(
+            changes[0].2[0]["content"]["data"],
+            Value::String(content_hash(&[1, 2, 3]))
+        );
+    }
+
+    #[test]
+    fn test_history_and_revert() {
+        let database = Database::open_in_memory().unwrap();
+        let mut report = RiskReportData {
+            id: "a".to_string(),
+            hazard_level: "高危".to_string(),
+            ..Default::default()
+        };
+        database
+            .save_project(
+                &ReportProject {
+                    project_name: "项目A".to_string(),
+                    report_list: vec![report.clone()],
+                    ..Default::default()
+                },
+                "",
+            )
+            .unwrap();
+
+        report.hazard_level = "中危".to_string();
+        database.save_report("项目A", &report, "张三").unwrap();
+        // 内容未变化时不记录
+        database.save_report("项目A", &report, "张三").unwrap();
+
+        let history = database.report_history("项目A", "a").unwrap();
+        assert_eq!(history.len(), 1);
+        assert_eq!(history[0].field, "hazard_level");
+        assert_eq!(history[0].author, "张三");
+        assert_eq!(history[0].new_value, Value::String("中危".to_string()));
+
+        let reverted = database
+            .revert_field("项目A", "a", history[0].id, "李四")
+            .unwrap();
+        assert_eq!(reverted.hazard_level, "高危");
+        assert_eq!(
+            database.load_project("项目A").unwrap().report_list[0].hazard_level,
+            "高危"
+        );
+        let history = database.report_history("项目A", "a").unwrap();
+        assert_eq!(history.len(), 2);
+        assert_eq!(history[0].author, "李四");
+    }
+
+    #[test]
+    fn test_save_project_records_history() {
+        let database = Database::open_in_memory().unwrap();
+        let report = |id: &str, level: &str| RiskReportData {
+            id: id.to_string(),
+            hazard_level: level.to_string(),
+            ..Default::default()
+        };
+        let project = |reports: Vec<RiskReportData>| ReportProject {
+            project_name: "项目A".to_string(),
+            report_list: reports,
+            ..Default::default()
+        };
+        database
+            .save_project(
+                &project(vec![report("a", "高危"), report("b", "中危")]),
+                "张三",
+            )
+            .unwrap();
+        assert!(database.report_history("项目A", "a").unwrap().is_empty());
+
+        // 修改、调整顺序并删除报告
+        database
+            .save_project(
+                &project(vec![report("c", "低危"), report("a", "中危")]),
+                "李四",
+            )
+            .unwrap();
+        let history = database.report_history("项目A", "a").unwrap();
+        assert_eq!(history.len(), 1);
+        assert_eq!(history[0].field, "hazard_level");
+        assert_eq!(history[0].author, "李四");
+        assert_eq!(history[0].old_value, Value::String("高危".to_string()));
+
+        let ids: Vec<String> = database
+            .load_project("项目A")
+            .unwrap()
+            .report_list
+            .into_iter()
+            .map(|report| report.id)
+            .collect();
+        assert_eq!(ids, vec!["c".to_string(), "a".to_string()]);
+    }
+}
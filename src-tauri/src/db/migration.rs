@@ -0,0 +1,350 @@
+use super::{read_meta, write_meta, Database, DbError, ReportProject};
+use crate::report::content_hash;
+use rusqlite::{params, Connection, Transaction};
+use serde::Serialize;
+use std::path::Path;
+
+/// 旧版存储导入完成的标记
+const STORE_MIGRATED_KEY: &str = "store_migrated";
+
+/// 旧版前端项目存储文件，位于应用数据目录下。
+///
+/// 保留策略：导入数据库后原文件保持不变，便于回退到旧版本；启用加密时由
+/// remove_legacy_store 删除，避免加密后仍残留明文项目数据。其他代码不应直接读写该文件
+pub const LEGACY_STORE_FILE: &str = "docStore.json";
+
+/// 单个版本的结构升级
+type Migration = fn(&Transaction) -> Result<(), DbError>;
+
+/// 数据库结构版本，保存在 PRAGMA user_version 中。每个版本对应 MIGRATIONS 中的一步
+const MIGRATIONS: [Migration; 3] = [create_base_tables, hash_blobs, add_history_and_fields];
+
+/// 将数据库结构升级到当前版本。未记录版本的旧数据库按表结构推断所处版本
+pub(super) fn migrate(conn: &mut Connection) -> Result<(), DbError> {
+    let mut version: usize = conn.query_row("PRAGMA user_version", [], |row| row.get(0))?;
+    if version == 0 {
+        version = detect_legacy_version(conn)?;
+    }
+    if version > MIGRATIONS.len() {
+        return Err(DbError::SchemaTooNew(version));
+    }
+    if version == MIGRATIONS.len() {
+        return Ok(());
+    }
+
+    // 重建表时需要暂时关闭外键约束，该设置在事务内无效
+    conn.execute_batch("PRAGMA foreign_keys = OFF;")?;
+    let result = (|| {
+        let tx = conn.transaction()?;
+        for (index, step) in MIGRATIONS.iter().enumerate().skip(version) {
+            step(&tx)?;
+            tx.pragma_update(None, "user_version", index + 1)?;
+        }
+        tx.commit()?;
+        Ok(())
+    })();
+    conn.execute_batch("PRAGMA foreign_keys = ON;")?;
+    result
+}
+
+/// 推断未记录版本的数据库所处的版本：空库为 0，图片按自增ID保存的为 1，其余为 2
+fn detect_legacy_version(conn: &Connection) -> Result<usize, DbError> {
+    let columns = conn
+        .prepare("SELECT name FROM pragma_table_info('blobs')")?
+        .query_map([], |row| row.get::<_, String>(0))?
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(if columns.is_empty() {
+        0
+    } else if columns.iter().any(|column| column == "id") {
+        1
+    } else {
+        2
+    })
+}
+
+/// 版本 1：项目、报告、截图及图片数据表
+fn create_base_tables(tx: &Transaction) -> Result<(), DbError> {
+    tx.execute_batch(
+        "CREATE TABLE meta (
+            key TEXT PRIMARY KEY,
+            value TEXT NOT NULL
+        );
+        CREATE TABLE projects (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            name TEXT NOT NULL UNIQUE,
+            created_at INTEGER NOT NULL,
+            updated_at INTEGER NOT NULL
+        );
+        CREATE TABLE reports (
+            rowid INTEGER PRIMARY KEY,
+            project_id INTEGER NOT NULL REFERENCES projects(id) ON DELETE CASCADE,
+            id TEXT NOT NULL,
+            position INTEGER NOT NULL,
+            data TEXT NOT NULL,
+            UNIQUE (project_id, id)
+        );
+        CREATE TABLE blobs (
+            id INTEGER PRIMARY KEY,
+            data BLOB NOT NULL
+        );
+        CREATE TABLE screenshots (
+            report_rowid INTEGER NOT NULL REFERENCES reports(rowid) ON DELETE CASCADE,
+            field TEXT NOT NULL,
+            position INTEGER NOT NULL,
+            content TEXT NOT NULL,
+            blob_id INTEGER REFERENCES blobs(id)
+        );
+        CREATE INDEX idx_screenshots_report ON screenshots(report_rowid);",
+    )?;
+    Ok(())
+}
+
+/// 版本 2：图片改为按内容哈希保存，相同图片合并为一份。
+/// 该版本之前尚不支持加密，图片均为明文
+fn hash_blobs(tx: &Transaction) -> Result<(), DbError> {
+    tx.execute_batch(
+        "CREATE TABLE blobs_v2 (
+            hash TEXT PRIMARY KEY,
+            data BLOB NOT NULL
+        );
+        CREATE TEMP TABLE blob_ids (
+            id INTEGER PRIMARY KEY,
+            hash TEXT NOT NULL
+        );",
+    )?;
+    {
+        let mut select = tx.prepare("SELECT id, data FROM blobs")?;
+        let mut insert_blob =
+            tx.prepare("INSERT OR IGNORE INTO blobs_v2 (hash, data) VALUES (?1, ?2)")?;
+        let mut insert_id = tx.prepare("INSERT INTO blob_ids (id, hash) VALUES (?1, ?2)")?;
+        let mut rows = select.query([])?;
+        while let Some(row) = rows.next()? {
+            let id: i64 = row.get(0)?;
+            let data: Vec<u8> = row.get(1)?;
+            let hash = content_hash(&data);
+            insert_blob.execute(params![hash, data])?;
+            insert_id.execute(params![id, hash])?;
+        }
+    }
+    tx.execute_batch(
+        "CREATE TABLE screenshots_v2 (
+            report_rowid INTEGER NOT NULL REFERENCES reports(rowid) ON DELETE CASCADE,
+            field TEXT NOT NULL,
+            position INTEGER NOT NULL,
+            content TEXT NOT NULL,
+            blob_hash TEXT REFERENCES blobs(hash)
+        );
+        INSERT INTO screenshots_v2 (report_rowid, field, position, content, blob_hash)
+            SELECT s.report_rowid, s.field, s.position, s.content, m.hash
+            FROM screenshots s LEFT JOIN blob_ids m ON m.id = s.blob_id;
+        DROP TABLE screenshots;
+        DROP TABLE blobs;
+        DROP TABLE blob_ids;
+        ALTER TABLE blobs_v2 RENAME TO blobs;
+        ALTER TABLE screenshots_v2 RENAME TO screenshots;
+        CREATE INDEX idx_screenshots_report ON screenshots(report_rowid);
+        CREATE INDEX idx_screenshots_blob ON screenshots(blob_hash);",
+    )?;
+    Ok(())
+}
+
+/// 版本 3：报告修改记录及项目自定义字段。
+/// 未记录版本的旧数据库可能已创建这些表，因此允许已存在
+fn add_history_and_fields(tx: &Transaction) -> Result<(), DbError> {
+    tx.execute_batch(
+        "CREATE TABLE IF NOT EXISTS report_history (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            project_id INTEGER NOT NULL REFERENCES projects(id) ON DELETE CASCADE,
+            report_id TEXT NOT NULL,
+            field TEXT NOT NULL,
+            change TEXT NOT NULL,
+            author TEXT NOT NULL,
+            changed_at INTEGER NOT NULL
+        );
+        CREATE INDEX IF NOT EXISTS idx_report_history_report
+            ON report_history(project_id, report_id);
+        CREATE TABLE IF NOT EXISTS project_fields (
+            project_id INTEGER PRIMARY KEY REFERENCES projects(id) ON DELETE CASCADE,
+            data TEXT NOT NULL
+        );
+        CREATE INDEX IF NOT EXISTS idx_screenshots_blob ON screenshots(blob_hash);",
+    )?;
+    Ok(())
+}
+
+/// 删除数据目录下的旧版明文存储，在启用加密后调用。文件不存在时直接返回
+pub fn remove_legacy_store(data_dir: &Path) -> std::io::Result<()> {
+    match std::fs::remove_file(data_dir.join(LEGACY_STORE_FILE)) {
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e),
+        _ => Ok(()),
+    }
+}
+
+/// 旧版存储导入结果
+#[derive(Serialize, Debug, Clone, Default, PartialEq)]
+pub struct MigrationSummary {
+    pub projects: usize,      // 导入的项目数
+    pub reports: usize,       // 导入的报告数
+    pub skipped: Vec<String>, // 已存在或格式无效而跳过的项目
+}
+
+/// 导入数据目录下旧版存储中的项目，已导入过时返回 None。保留策略见 LEGACY_STORE_FILE
+pub(super) fn migrate_from_store(
+    database: &Database,
+    data_dir: &Path,
+) -> Result<Option<MigrationSummary>, DbError> {
+    if read_meta(&database.conn(), STORE_MIGRATED_KEY)?.is_some() {
+        return Ok(None);
+    }
+    let store_path = data_dir.join(LEGACY_STORE_FILE);
+
+    let mut summary = MigrationSummary::default();
+    if store_path.exists() {
+        let content = std::fs::read_to_string(&store_path)?;
+        let store: serde_json::Map<String, serde_json::Value> = serde_json::from_str(&content)?;
+        let existing = database.project_names()?;
+
+        for (key, value) in store {
+            let project = match ReportProject::from_json(value) {
+                Ok(project) if !existing.contains(&project.project_name) => project,
+                Ok(_) => {
+                    summary.skipped.push(key);
+                    continue;
+                }
+                Err(e) => {
+                    log::warn!("项目 {} 格式无效，跳过导入: {}", key, e);
+                    summary.skipped.push(key);
+                    continue;
+                }
+            };
+            database.save_project(&project, "")?;
+            summary.projects += 1;
+            summary.reports += project.report_list.len();
+        }
+    }
+
+    write_meta(&database.conn(), STORE_MIGRATED_KEY, "1")?;
+    Ok(Some(summary))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::report::{RiskReportData, ScreenshotContent, ScreenshotImage};
+
+    #[test]
+    fn test_migrate_legacy_schema() {
+        // 版本 1 的数据库：图片按自增ID保存，未记录版本
+        let mut conn = Connection::open_in_memory().unwrap();
+        let tx = conn.transaction().unwrap();
+        create_base_tables(&tx).unwrap();
+        tx.commit().unwrap();
+        let report = RiskReportData {
+            id: "a".to_string(),
+            report_id: "R-a".to_string(),
+            ..Default::default()
+        };
+        let image = serde_json::to_string(&ScreenshotContent::Image(ScreenshotImage::default()));
+        let text = serde_json::to_string(&ScreenshotContent::Text("说明".to_string()));
+        conn.execute(
+            "INSERT INTO projects (name, created_at, updated_at) VALUES ('项目A', 0, 0)",
+            [],
+        )
+        .unwrap();
+        conn.execute(
+            "INSERT INTO reports (project_id, id, position, data) VALUES (1, 'a', 0, ?1)",
+            params![serde_json::to_string(&report).unwrap()],
+        )
+        .unwrap();
+        conn.execute_batch("INSERT INTO blobs (id, data) VALUES (1, x'010203'), (2, x'010203');")
+            .unwrap();
+        conn.execute(
+            "INSERT INTO screenshots (report_rowid, field, position, content, blob_id)
+             VALUES (1, 'evidence', 0, ?1, 1), (1, 'evidence', 1, ?1, 2), (1, 'filing', 0, ?2, NULL)",
+            params![image.unwrap(), text.unwrap()],
+        )
+        .unwrap();
+
+        let database = Database::from_connection(conn).unwrap();
+        let conn = database.conn();
+        let version: usize = conn
+            .query_row("PRAGMA user_version", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(version, MIGRATIONS.len());
+        let blobs: i64 = conn
+            .query_row("SELECT COUNT(*) FROM blobs", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(blobs, 1);
+        drop(conn);
+
+        let report = database
+            .load_project("项目A")
+            .unwrap()
+            .report_list
+            .remove(0);
+        assert_eq!(report.report_id, "R-a");
+        assert_eq!(report.evidence_screenshots.len(), 2);
+        assert!(report.evidence_screenshots.iter().all(|content| matches!(
+            content,
+            ScreenshotContent::Image(image) if image.data == vec![1, 2, 3]
+        )));
+        assert!(matches!(
+            &report.filing_screenshots[0],
+            ScreenshotContent::Text(text) if text == "说明"
+        ));
+        assert!(database.report_history("项目A", "a").unwrap().is_empty());
+
+        // 高于当前版本的数据库拒绝打开
+        let conn = Connection::open_in_memory().unwrap();
+        conn.pragma_update(None, "user_version", 99).unwrap();
+        assert!(matches!(
+            Database::from_connection(conn),
+            Err(DbError::SchemaTooNew(99))
+        ));
+    }
+
+    #[test]
+    fn test_migrate_from_store() {
+        let dir = std::env::temp_dir().join(format!("rg_migration_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let store_path = dir.join(LEGACY_STORE_FILE);
+        let store = serde_json::json!({
+            "项目A": {
+                "projectName": "项目A",
+                "reportList": [{
+                    "id": "a",
+                    "hazard_type": "", "report_name": "", "hazard_level": "", "report_id": "",
+                    "target": "", "vul_name": "", "warning_level": "", "city": "",
+                    "unit_type": "", "industry": "", "customer_company_name": "",
+                    "website_name": "", "domain": "", "ip_address": "", "case_number": "",
+                    "report_time": "", "problem_description": "", "vul_modify_repair": "",
+                    "evidence_screenshots": [{ "type": "image", "content": [1, 2, 3] }],
+                    "filing_screenshots": [],
+                    "remark": ""
+                }]
+            },
+            "项目B": { "projectName": "项目B" }
+        });
+        std::fs::write(&store_path, store.to_string()).unwrap();
+
+        let database = Database::open_in_memory().unwrap();
+        let summary = database.migrate_from_store(&dir).unwrap().unwrap();
+        assert_eq!(summary.projects, 1);
+        assert_eq!(summary.reports, 1);
+        assert_eq!(summary.skipped, vec!["项目B".to_string()]);
+        assert_eq!(
+            database.load_project("项目A").unwrap().report_list[0]
+                .evidence_screenshots
+                .len(),
+            1
+        );
+
+        // 只导入一次，原文件保留到启用加密时删除
+        assert!(database.migrate_from_store(&dir).unwrap().is_none());
+        assert!(store_path.exists());
+        remove_legacy_store(&dir).unwrap();
+        assert!(!store_path.exists());
+        remove_legacy_store(&dir).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,904 @@
+use crate::crypto::{Cipher, CryptoError};
+use crate::report::{content_hash, schema, CustomFieldDef, RiskReportData, ScreenshotContent};
+use rusqlite::backup::Progress;
+use rusqlite::types::{Value, ValueRef};
+use rusqlite::{params, Connection, DatabaseName, OptionalExtension, Transaction};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::path::Path;
+use std::sync::{Mutex, MutexGuard};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+mod fields;
+mod history;
+mod migration;
+mod vault;
+
+pub use history::{diff_fields, FieldChange};
+pub use migration::{remove_legacy_store, MigrationSummary};
+pub use vault::{is_encrypted_file, VaultStatus};
+
+/// 数据库文件名，位于应用数据目录下
+pub const DATABASE_FILE: &str = "reports.db";
+
+/// 数据库相关的错误类型
+#[derive(Debug, thiserror::Error)]
+pub enum DbError {
+    #[error("数据库错误: {0}")]
+    Sqlite(#[from] rusqlite::Error),
+    #[error("数据序列化失败: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("项目不存在: {0}")]
+    ProjectNotFound(String),
+    #[error("项目名称已存在: {0}")]
+    ProjectExists(String),
+    #[error("项目名称不能为空")]
+    EmptyProjectName,
+    #[error("报告不存在: {0}")]
+    ReportNotFound(String),
+    #[error("图片不存在: {0}")]
+    ImageNotFound(String),
+    #[error("修改记录不存在: {0}")]
+    ChangeNotFound(i64),
+    #[error("字段不支持回退: {0}")]
+    FieldNotRevertible(String),
+    #[error("自定义字段无效: {0}")]
+    InvalidCustomField(String),
+    #[error("项目数据已锁定，请输入密码解锁")]
+    Locked,
+    #[error("密码错误")]
+    WrongPassword,
+    #[error("密码不能为空")]
+    EmptyPassword,
+    #[error("项目数据未启用加密")]
+    NotEncrypted,
+    #[error("项目数据已启用加密")]
+    AlreadyEncrypted,
+    #[error("{0}")]
+    Crypto(#[from] CryptoError),
+    #[error("数据库版本 {0} 高于当前应用支持的版本，请升级应用")]
+    SchemaTooNew(usize),
+    #[error("IO错误: {0}")]
+    Io(#[from] std::io::Error),
+}
+
+/// 项目数据，字段名与前端 ReportProject 保持一致
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct ReportProject {
+    #[serde(rename = "projectName")]
+    pub project_name: String,
+    #[serde(rename = "reportList")]
+    pub report_list: Vec<RiskReportData>,
+    #[serde(rename = "customFields", default)]
+    pub custom_fields: Vec<CustomFieldDef>, // 项目声明的自定义字段
+}
+
+impl ReportProject {
+    /// 解析项目 JSON，报告数据按版本升级为当前结构
+    pub fn from_json(value: serde_json::Value) -> Result<Self, serde_json::Error> {
+        #[derive(Deserialize)]
+        struct RawProject {
+            #[serde(rename = "projectName")]
+            project_name: String,
+            #[serde(rename = "reportList")]
+            report_list: Vec<serde_json::Value>,
+            #[serde(rename = "customFields", default)]
+            custom_fields: Vec<CustomFieldDef>,
+        }
+
+        let raw: RawProject = serde_json::from_value(value)?;
+        Ok(Self {
+            project_name: raw.project_name,
+            report_list: raw
+                .report_list
+                .into_iter()
+                .map(schema::from_value)
+                .collect::<Result<_, _>>()?,
+            custom_fields: raw.custom_fields,
+        })
+    }
+}
+
+/// 加密数据的附加认证数据，防止密文在不同字段间被替换
+const REPORT_AAD: &[u8] = b"reports.data";
+const SCREENSHOT_AAD: &[u8] = b"screenshots.content";
+const BLOB_AAD: &[u8] = b"blobs.data";
+
+/// 项目数据库，截图图片按内容哈希保存，相同图片只存储一份。
+/// 启用加密后报告、截图和图片数据以密文保存，项目名称用于索引，仍以明文保存
+pub struct Database {
+    conn: Mutex<Connection>,
+    vault: Mutex<vault::Vault>,
+}
+
+impl Database {
+    /// 打开数据库文件，不存在时自动创建
+    pub fn open(path: &Path) -> Result<Self, DbError> {
+        Self::from_connection(Connection::open(path)?)
+    }
+
+    /// 打开内存数据库，用于测试
+    pub fn open_in_memory() -> Result<Self, DbError> {
+        Self::from_connection(Connection::open_in_memory()?)
+    }
+
+    fn from_connection(mut conn: Connection) -> Result<Self, DbError> {
+        // secure_delete 在删除数据时覆盖原内容，避免启用加密后文件中残留明文
+        conn.execute_batch(
+            "PRAGMA foreign_keys = ON; PRAGMA journal_mode = WAL; PRAGMA secure_delete = ON;",
+        )?;
+        migration::migrate(&mut conn)?;
+        let vault = vault::Vault::load(&conn)?;
+        Ok(Self {
+            conn: Mutex::new(conn),
+            vault: Mutex::new(vault),
+        })
+    }
+
+    fn conn(&self) -> MutexGuard<'_, Connection> {
+        // 持锁线程 panic 后连接本身仍然可用
+        self.conn
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// 加载所有项目，按创建顺序排列。图片只返回引用，数据通过 load_image 按需读取
+    pub fn load_projects(&self) -> Result<Vec<ReportProject>, DbError> {
+        let vault = self.unlocked()?;
+        let conn = self.conn();
+        let mut statement = conn.prepare("SELECT id, name FROM projects ORDER BY id")?;
+        let projects = statement
+            .query_map([], |row| {
+                Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?))
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        projects
+            .into_iter()
+            .map(|(id, name)| {
+                Ok(ReportProject {
+                    project_name: name,
+                    report_list: load_reports(&conn, vault.cipher(), id, false)?,
+                    custom_fields: fields::load_custom_fields(&conn, vault.cipher(), id)?,
+                })
+            })
+            .collect()
+    }
+
+    /// 读取单张已保存的图片
+    pub fn load_image(&self, hash: &str) -> Result<Vec<u8>, DbError> {
+        let vault = self.unlocked()?;
+        read_blob(&self.conn(), vault.cipher(), hash)
+    }
+
+    /// 为只包含引用的图片补全数据，用于生成报告等需要图片内容的操作
+    pub fn fill_images(&self, reports: &mut [RiskReportData]) -> Result<(), DbError> {
+        let vault = self.unlocked()?;
+        let conn = self.conn();
+        for report in reports {
+            fill_report_images(&conn, vault.cipher(), report)?;
+        }
+        Ok(())
+    }
+
+    /// 所有项目名称，按创建顺序排列
+    pub fn project_names(&self) -> Result<Vec<String>, DbError> {
+        let _vault = self.unlocked()?;
+        let conn = self.conn();
+        let mut statement = conn.prepare("SELECT name FROM projects ORDER BY id")?;
+        let names = statement
+            .query_map([], |row| row.get(0))?
+            .collect::<Result<Vec<String>, _>>()?;
+        Ok(names)
+    }
+
+    /// 加载单个项目，包含完整的图片数据
+    pub fn load_project(&self, name: &str) -> Result<ReportProject, DbError> {
+        let vault = self.unlocked()?;
+        let conn = self.conn();
+        let project_id = find_project(&conn, name)?;
+        Ok(ReportProject {
+            project_name: name.to_string(),
+            report_list: load_reports(&conn, vault.cipher(), project_id, true)?,
+            custom_fields: fields::load_custom_fields(&conn, vault.cipher(), project_id)?,
+        })
+    }
+
+    /// 按内部ID加载单个报告，包含完整的图片数据
+    pub fn load_report(&self, project_name: &str, id: &str) -> Result<RiskReportData, DbError> {
+        let vault = self.unlocked()?;
+        let conn = self.conn();
+        let project_id = find_project(&conn, project_name)?;
+        find_report(&conn, vault.cipher(), project_id, id)?
+            .ok_or_else(|| DbError::ReportNotFound(id.to_string()))
+    }
+
+    /// 保存整个项目，项目不存在时创建，报告列表整体替换。
+    /// 已有的报告按字段记录修改历史，author 为修改人
+    pub fn save_project(&self, project: &ReportProject, author: &str) -> Result<(), DbError> {
+        let name = project.project_name.trim();
+        if name.is_empty() {
+            return Err(DbError::EmptyProjectName);
+        }
+
+        let vault = self.unlocked()?;
+        let mut conn = self.conn();
+        let tx = conn.transaction()?;
+        let mut reports = project.report_list.clone();
+        for report in &mut reports {
+            fill_report_images(&tx, vault.cipher(), report)?;
+        }
+        let project_id = match find_project(&tx, name) {
+            Ok(id) => {
+                // 删除不在新列表中的报告
+                let existing: Vec<String> = tx
+                    .prepare("SELECT id FROM reports WHERE project_id = ?1")?
+                    .query_map(params![id], |row| row.get(0))?
+                    .collect::<Result<_, _>>()?;
+                for removed in existing
+                    .iter()
+                    .filter(|existing| !reports.iter().any(|report| &report.id == *existing))
+                {
+                    let released = blob_hashes(&tx, id, Some(removed))?;
+                    tx.execute(
+                        "DELETE FROM reports WHERE project_id = ?1 AND id = ?2",
+                        params![id, removed],
+                    )?;
+                    release_blobs(&tx, &released)?;
+                }
+                touch_project(&tx, id)?;
+                id
+            }
+            Err(DbError::ProjectNotFound(_)) => insert_project(&tx, name)?,
+            Err(e) => return Err(e),
+        };
+
+        // 逐个保存报告，已有的报告记录修改历史，之后按新列表调整顺序
+        for (position, report) in reports.iter().enumerate() {
+            save_report_with_history(&tx, vault.cipher(), project_id, report, author)?;
+            tx.execute(
+                "UPDATE reports SET position = ?1 WHERE project_id = ?2 AND id = ?3",
+                params![position as i64, project_id, report.id],
+            )?;
+        }
+        fields::save_custom_fields(&tx, vault.cipher(), project_id, &project.custom_fields)?;
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// 删除项目及其全部报告
+    pub fn delete_project(&self, name: &str) -> Result<(), DbError> {
+        let _vault = self.unlocked()?;
+        let mut conn = self.conn();
+        let tx = conn.transaction()?;
+        let project_id = find_project(&tx, name)?;
+        let released = blob_hashes(&tx, project_id, None)?;
+        tx.execute("DELETE FROM projects WHERE id = ?1", params![project_id])?;
+        release_blobs(&tx, &released)?;
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// 重命名项目
+    pub fn rename_project(&self, old_name: &str, new_name: &str) -> Result<(), DbError> {
+        let new_name = new_name.trim();
+        if new_name.is_empty() {
+            return Err(DbError::EmptyProjectName);
+        }
+
+        let _vault = self.unlocked()?;
+        let conn = self.conn();
+        let project_id = find_project(&conn, old_name)?;
+        if new_name != old_name && find_project(&conn, new_name).is_ok() {
+            return Err(DbError::ProjectExists(new_name.to_string()));
+        }
+        conn.execute(
+            "UPDATE projects SET name = ?1, updated_at = ?2 WHERE id = ?3",
+            params![new_name, now(), project_id],
+        )?;
+        Ok(())
+    }
+
+    /// 新增或更新单个报告（按内部ID匹配），新增的报告追加到末尾。
+    /// 更新时按字段记录修改历史，author 为修改人
+    pub fn save_report(
+        &self,
+        project_name: &str,
+        report: &RiskReportData,
+        author: &str,
+    ) -> Result<(), DbError> {
+        let vault = self.unlocked()?;
+        let mut conn = self.conn();
+        let tx = conn.transaction()?;
+        let project_id = find_project(&tx, project_name)?;
+        let mut report = report.clone();
+        fill_report_images(&tx, vault.cipher(), &mut report)?;
+        save_report_with_history(&tx, vault.cipher(), project_id, &report, author)?;
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// 删除单个报告
+    pub fn delete_report(&self, project_name: &str, id: &str) -> Result<(), DbError> {
+        let _vault = self.unlocked()?;
+        let mut conn = self.conn();
+        let tx = conn.transaction()?;
+        let project_id = find_project(&tx, project_name)?;
+        let released = blob_hashes(&tx, project_id, Some(id))?;
+        tx.execute(
+            "DELETE FROM reports WHERE project_id = ?1 AND id = ?2",
+            params![project_id, id],
+        )?;
+        touch_project(&tx, project_id)?;
+        release_blobs(&tx, &released)?;
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// 数据库内容的摘要，数据不变时摘要不变，用于判断距上次备份数据是否有变化。
+    /// 直接读取保存的数据，锁定状态下也可以计算
+    pub fn content_digest(&self) -> Result<String, DbError> {
+        let conn = self.conn();
+        let mut hasher = Sha256::new();
+        for query in DIGEST_QUERIES {
+            let mut stmt = conn.prepare(query)?;
+            let columns = stmt.column_count();
+            let mut rows = stmt.query([])?;
+            while let Some(row) = rows.next()? {
+                for index in 0..columns {
+                    match row.get_ref(index)? {
+                        ValueRef::Null => hasher.update([0]),
+                        ValueRef::Integer(value) => {
+                            hasher.update([1]);
+                            hasher.update(value.to_le_bytes());
+                        }
+                        ValueRef::Real(value) => {
+                            hasher.update([2]);
+                            hasher.update(value.to_le_bytes());
+                        }
+                        ValueRef::Text(data) | ValueRef::Blob(data) => {
+                            hasher.update([3]);
+                            hasher.update((data.len() as u64).to_le_bytes());
+                            hasher.update(data);
+                        }
+                    }
+                }
+            }
+            // 分隔各表的数据
+            hasher.update([0xff]);
+        }
+        Ok(format!("{:x}", hasher.finalize()))
+    }
+
+    /// 将整个数据库复制到指定文件。加密数据以密文复制，锁定状态下也可以备份
+    pub fn backup_to(&self, path: &Path) -> Result<(), DbError> {
+        self.conn().backup(DatabaseName::Main, path, None)?;
+        Ok(())
+    }
+
+    /// 使用备份文件替换当前数据库。备份的加密配置与当前不同时需要重新解锁
+    pub fn restore_from(&self, path: &Path) -> Result<(), DbError> {
+        let mut vault = self.unlocked()?;
+        let mut conn = self.conn();
+        conn.restore(DatabaseName::Main, path, None::<fn(Progress)>)?;
+        migration::migrate(&mut conn)?;
+        vault.reload(&conn)
+    }
+
+    /// 从数据目录下的旧版存储导入项目，只在首次启动时执行一次
+    pub fn migrate_from_store(&self, data_dir: &Path) -> Result<Option<MigrationSummary>, DbError> {
+        migration::migrate_from_store(self, data_dir)
+    }
+}
+
+/// 计算内容摘要时读取的数据。图片按内容哈希保存，只需读取哈希
+const DIGEST_QUERIES: [&str; 7] = [
+    "SELECT key, value FROM meta ORDER BY key",
+    "SELECT id, name, created_at, updated_at FROM projects ORDER BY id",
+    "SELECT rowid, project_id, id, position, data FROM reports ORDER BY rowid",
+    "SELECT hash FROM blobs ORDER BY hash",
+    "SELECT report_rowid, field, position, content, blob_hash FROM screenshots
+     ORDER BY report_rowid, field, position",
+    "SELECT id, project_id, report_id, field, change, author, changed_at
+     FROM report_history ORDER BY id",
+    "SELECT project_id, data FROM project_fields ORDER BY project_id",
+];
+
+fn read_meta(conn: &Connection, key: &str) -> Result<Option<String>, DbError> {
+    Ok(conn
+        .query_row(
+            "SELECT value FROM meta WHERE key = ?1",
+            params![key],
+            |row| row.get(0),
+        )
+        .optional()?)
+}
+
+fn write_meta(conn: &Connection, key: &str, value: &str) -> Result<(), DbError> {
+    conn.execute(
+        "INSERT INTO meta (key, value) VALUES (?1, ?2)
+         ON CONFLICT(key) DO UPDATE SET value = excluded.value",
+        params![key, value],
+    )?;
+    Ok(())
+}
+
+/// 按需加密写入的数据，未启用加密时文本以 TEXT 保存
+fn seal(cipher: Option<&Cipher>, data: Vec<u8>, aad: &[u8]) -> Result<Value, DbError> {
+    match cipher {
+        Some(cipher) => Ok(Value::Blob(cipher.encrypt(&data, aad)?)),
+        None => {
+            Ok(String::from_utf8(data).map_or_else(|e| Value::Blob(e.into_bytes()), Value::Text))
+        }
+    }
+}
+
+/// 读取 seal 写入的数据。启用加密后只接受能够解密的密文，不会把明文行当作有效数据
+fn open(cipher: Option<&Cipher>, value: ValueRef, aad: &[u8]) -> Result<Vec<u8>, DbError> {
+    match (cipher, value) {
+        (Some(cipher), ValueRef::Blob(data)) => Ok(cipher.decrypt(data, aad)?),
+        (None, ValueRef::Text(data) | ValueRef::Blob(data)) => Ok(data.to_vec()),
+        _ => Err(DbError::Crypto(CryptoError::InvalidFormat)),
+    }
+}
+
+/// 当前时间（Unix 秒）
+fn now() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |duration| duration.as_secs() as i64)
+}
+
+fn find_project(conn: &Connection, name: &str) -> Result<i64, DbError> {
+    conn.query_row(
+        "SELECT id FROM projects WHERE name = ?1",
+        params![name],
+        |row| row.get(0),
+    )
+    .optional()?
+    .ok_or_else(|| DbError::ProjectNotFound(name.to_string()))
+}
+
+fn insert_project(tx: &Transaction, name: &str) -> Result<i64, DbError> {
+    let timestamp = now();
+    tx.execute(
+        "INSERT INTO projects (name, created_at, updated_at) VALUES (?1, ?2, ?2)",
+        params![name, timestamp],
+    )?;
+    Ok(tx.last_insert_rowid())
+}
+
+fn touch_project(tx: &Transaction, project_id: i64) -> Result<(), DbError> {
+    tx.execute(
+        "UPDATE projects SET updated_at = ?1 WHERE id = ?2",
+        params![now(), project_id],
+    )?;
+    Ok(())
+}
+
+/// 新增或替换报告，替换时记录与原报告的字段差异
+fn save_report_with_history(
+    tx: &Transaction,
+    cipher: Option<&Cipher>,
+    project_id: i64,
+    report: &RiskReportData,
+    author: &str,
+) -> Result<(), DbError> {
+    let existing: Option<i64> = tx
+        .query_row(
+            "SELECT position FROM reports WHERE project_id = ?1 AND id = ?2",
+            params![project_id, report.id],
+            |row| row.get(0),
+        )
+        .optional()?;
+    let mut released = Vec::new();
+    let position = match existing {
+        Some(position) => {
+            if let Some(old) = find_report(tx, cipher, project_id, &report.id)? {
+                history::record_changes(tx, cipher, project_id, &old, report, author)?;
+            }
+            released = blob_hashes(tx, project_id, Some(&report.id))?;
+            tx.execute(
+                "DELETE FROM reports WHERE project_id = ?1 AND id = ?2",
+                params![project_id, report.id],
+            )?;
+            position
+        }
+        None => tx.query_row(
+            "SELECT COALESCE(MAX(position) + 1, 0) FROM reports WHERE project_id = ?1",
+            params![project_id],
+            |row| row.get(0),
+        )?,
+    };
+
+    insert_report(tx, cipher, project_id, position, report)?;
+    touch_project(tx, project_id)?;
+    release_blobs(tx, &released)?;
+    Ok(())
+}
+
+/// 写入报告，截图拆分到 screenshots 表，图片数据按内容哈希写入 blobs 表。
+/// 只包含引用的图片需先经 fill_report_images 补全数据
+fn insert_report(
+    tx: &Transaction,
+    cipher: Option<&Cipher>,
+    project_id: i64,
+    position: i64,
+    report: &RiskReportData,
+) -> Result<(), DbError> {
+    let mut stored = report.clone();
+    stored.schema_version = schema::SCHEMA_VERSION;
+    let screenshots: Vec<(&str, Vec<ScreenshotContent>)> = stored
+        .screenshot_groups_mut()
+        .into_iter()
+        .map(|(field, contents)| (stored_field(field), std::mem::take(contents)))
+        .collect();
+
+    tx.execute(
+        "INSERT INTO reports (project_id, id, position, data) VALUES (?1, ?2, ?3, ?4)",
+        params![
+            project_id,
+            report.id,
+            position,
+            seal(cipher, serde_json::to_vec(&stored)?, REPORT_AAD)?
+        ],
+    )?;
+    let report_rowid = tx.last_insert_rowid();
+
+    for (field, screenshots) in screenshots {
+        for (index, mut content) in screenshots.into_iter().enumerate() {
+            let blob_hash = match &mut content {
+                ScreenshotContent::Image(image) => {
+                    image.blob = None;
+                    let data = std::mem::take(&mut image.data);
+                    let hash = match cipher {
+                        Some(cipher) => cipher.keyed_hash(&data),
+                        None => content_hash(&data),
+                    };
+                    let data = match cipher {
+                        Some(cipher) => cipher.encrypt(&data, BLOB_AAD)?,
+                        None => data,
+                    };
+                    tx.execute(
+                        "INSERT OR IGNORE INTO blobs (hash, data) VALUES (?1, ?2)",
+                        params![hash, data],
+                    )?;
+                    Some(hash)
+                }
+                _ => None,
+            };
+            tx.execute(
+                "INSERT INTO screenshots (report_rowid, field, position, content, blob_hash)
+                 VALUES (?1, ?2, ?3, ?4, ?5)",
+                params![
+                    report_rowid,
+                    field,
+                    index as i64,
+                    seal(cipher, serde_json::to_vec(&content)?, SCREENSHOT_AAD)?,
+                    blob_hash
+                ],
+            )?;
+        }
+    }
+    Ok(())
+}
+
+/// 读取项目下的全部报告并还原截图，with_images 为 false 时图片只包含引用
+fn load_reports(
+    conn: &Connection,
+    cipher: Option<&Cipher>,
+    project_id: i64,
+    with_images: bool,
+) -> Result<Vec<RiskReportData>, DbError> {
+    let mut statement =
+        conn.prepare("SELECT rowid, data FROM reports WHERE project_id = ?1 ORDER BY position")?;
+    let rows = statement
+        .query_map(params![project_id], |row| {
+            Ok((row.get::<_, i64>(0)?, row.get::<_, Value>(1)?))
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    rows.into_iter()
+        .map(|(rowid, data)| read_report(conn, cipher, rowid, &data, with_images))
+        .collect()
+}
+
+/// 按内部ID读取单个报告
+fn find_report(
+    conn: &Connection,
+    cipher: Option<&Cipher>,
+    project_id: i64,
+    id: &str,
+) -> Result<Option<RiskReportData>, DbError> {
+    let row = conn
+        .query_row(
+            "SELECT rowid, data FROM reports WHERE project_id = ?1 AND id = ?2",
+            params![project_id, id],
+            |row| Ok((row.get::<_, i64>(0)?, row.get::<_, Value>(1)?)),
+        )
+        .optional()?;
+    row.map(|(rowid, data)| read_report(conn, cipher, rowid, &data, true))
+        .transpose()
+}
+
+/// screenshots 表中的截图字段名，为报告字段名去掉 "_screenshots" 后缀（evidence、filing、retest）
+fn stored_field(field: &str) -> &str {
+    field.strip_suffix("_screenshots").unwrap_or(field)
+}
+
+/// 解析报告数据并还原截图
+fn read_report(
+    conn: &Connection,
+    cipher: Option<&Cipher>,
+    rowid: i64,
+    data: &Value,
+    with_images: bool,
+) -> Result<RiskReportData, DbError> {
+    let data = open(cipher, data.into(), REPORT_AAD)?;
+    let mut report = schema::from_slice(&data)?;
+    let contents = conn
+        .prepare_cached(
+            "SELECT field, content, blob_hash FROM screenshots
+             WHERE report_rowid = ?1 ORDER BY field, position",
+        )?
+        .query_map(params![rowid], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, Value>(1)?,
+                row.get::<_, Option<String>>(2)?,
+            ))
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let mut groups = report.screenshot_groups_mut();
+    for (field, content, blob_hash) in contents {
+        let content = open(cipher, (&content).into(), SCREENSHOT_AAD)?;
+        let mut content: ScreenshotContent = serde_json::from_slice(&content)?;
+        if let (ScreenshotContent::Image(image), Some(hash)) = (&mut content, blob_hash) {
+            if with_images {
+                image.data = read_blob(conn, cipher, &hash)?;
+            } else {
+                image.blob = Some(hash);
+            }
+        }
+        // 无法识别的字段归入证据截图
+        let index = groups
+            .iter()
+            .position(|(name, _)| stored_field(name) == field)
+            .unwrap_or(0);
+        groups[index].1.push(content);
+    }
+    Ok(report)
+}
+
+/// 读取并解密单张图片
+fn read_blob(conn: &Connection, cipher: Option<&Cipher>, hash: &str) -> Result<Vec<u8>, DbError> {
+    let data: Vec<u8> = conn
+        .prepare_cached("SELECT data FROM blobs WHERE hash = ?1")?
+        .query_row(params![hash], |row| row.get(0))
+        .optional()?
+        .ok_or_else(|| DbError::ImageNotFound(hash.to_string()))?;
+    match cipher {
+        Some(cipher) => Ok(cipher.decrypt(&data, BLOB_AAD)?),
+        None => Ok(data),
+    }
+}
+
+/// 为数据为空、只包含引用的图片读取数据
+fn fill_report_images(
+    conn: &Connection,
+    cipher: Option<&Cipher>,
+    report: &mut RiskReportData,
+) -> Result<(), DbError> {
+    for (_, screenshots) in report.screenshot_groups_mut() {
+        for content in screenshots.iter_mut() {
+            if let ScreenshotContent::Image(image) = content {
+                if let Some(hash) = image.blob.take() {
+                    if image.data.is_empty() {
+                        image.data = read_blob(conn, cipher, &hash)?;
+                    }
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// 项目（或其中单个报告）引用的图片哈希，在删除截图前读取
+fn blob_hashes(
+    conn: &Connection,
+    project_id: i64,
+    report_id: Option<&str>,
+) -> Result<Vec<String>, DbError> {
+    let hashes = conn
+        .prepare_cached(
+            "SELECT DISTINCT s.blob_hash FROM screenshots s
+             JOIN reports r ON r.rowid = s.report_rowid
+             WHERE r.project_id = ?1 AND (?2 IS NULL OR r.id = ?2) AND s.blob_hash IS NOT NULL",
+        )?
+        .query_map(params![project_id, report_id], |row| row.get(0))?
+        .collect::<Result<Vec<String>, _>>()?;
+    Ok(hashes)
+}
+
+/// 删除不再被任何截图引用的图片，只检查本次移除了引用的图片，避免全表扫描
+fn release_blobs(tx: &Transaction, hashes: &[String]) -> Result<(), DbError> {
+    let mut statement = tx.prepare_cached(
+        "DELETE FROM blobs WHERE hash = ?1
+         AND NOT EXISTS (SELECT 1 FROM screenshots WHERE blob_hash = ?1)",
+    )?;
+    for hash in hashes {
+        statement.execute(params![hash])?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::report::ScreenshotImage;
+
+    fn sample_report(id: &str) -> RiskReportData {
+        RiskReportData {
+            id: id.to_string(),
+            report_id: format!("R-{}", id),
+            evidence_screenshots: vec![
+                ScreenshotContent::Text("步骤一".to_string()),
+                ScreenshotContent::Image(ScreenshotImage {
+                    data: vec![1, 2, 3],
+                    caption: Some("登录页".to_string()),
+                    ..Default::default()
+                }),
+            ],
+            filing_screenshots: vec![ScreenshotContent::Image(ScreenshotImage {
+                data: vec![4, 5],
+                ..Default::default()
+            })],
+            ..Default::default()
+        }
+    }
+
+    fn blob_count(db: &Database) -> i64 {
+        db.conn()
+            .query_row("SELECT COUNT(*) FROM blobs", [], |row| row.get(0))
+            .unwrap()
+    }
+
+    #[test]
+    fn test_project_roundtrip() {
+        let db = Database::open_in_memory().unwrap();
+        db.save_project(
+            &ReportProject {
+                project_name: "项目A".to_string(),
+                report_list: vec![sample_report("a"), sample_report("b")],
+                ..Default::default()
+            },
+            "",
+        )
+        .unwrap();
+
+        let project = db.load_project("项目A").unwrap();
+        assert_eq!(project.report_list.len(), 2);
+        let report = &project.report_list[0];
+        assert_eq!(report.report_id, "R-a");
+        assert!(matches!(
+            &report.evidence_screenshots[1],
+            ScreenshotContent::Image(image) if image.data == vec![1, 2, 3]
+                && image.caption.as_deref() == Some("登录页")
+        ));
+        assert!(matches!(
+            &report.filing_screenshots[0],
+            ScreenshotContent::Image(image) if image.data == vec![4, 5]
+        ));
+        // 两个报告中的相同图片只存储一份
+        assert_eq!(blob_count(&db), 2);
+
+        // 更新报告保持原有顺序，仍被引用的图片不会被清理
+        let mut updated = sample_report("a");
+        updated.filing_screenshots.clear();
+        db.save_report("项目A", &updated, "").unwrap();
+        let project = db.load_project("项目A").unwrap();
+        assert_eq!(project.report_list[0].id, "a");
+        assert!(project.report_list[0].filing_screenshots.is_empty());
+        assert_eq!(blob_count(&db), 2);
+
+        // 单独加载报告时包含图片数据
+        let report = db.load_report("项目A", "b").unwrap();
+        assert_eq!(report.report_id, "R-b");
+        assert!(matches!(
+            &report.evidence_screenshots[1],
+            ScreenshotContent::Image(image) if image.data == vec![1, 2, 3]
+        ));
+
+        db.delete_report("项目A", "b").unwrap();
+        assert!(matches!(
+            db.load_report("项目A", "b"),
+            Err(DbError::ReportNotFound(_))
+        ));
+        assert_eq!(blob_count(&db), 1);
+        assert_eq!(db.load_project("项目A").unwrap().report_list.len(), 1);
+    }
+
+    #[test]
+    fn test_image_references() {
+        let db = Database::open_in_memory().unwrap();
+        db.save_project(
+            &ReportProject {
+                project_name: "项目A".to_string(),
+                report_list: vec![sample_report("a")],
+                ..Default::default()
+            },
+            "",
+        )
+        .unwrap();
+
+        // 加载项目列表时图片只包含引用，数据按需读取
+        let mut report = db.load_projects().unwrap()[0].report_list[0].clone();
+        let ScreenshotContent::Image(image) = &report.evidence_screenshots[1] else {
+            panic!("应为图片");
+        };
+        assert!(image.data.is_empty());
+        let hash = image.blob.clone().unwrap();
+        assert_eq!(db.load_image(&hash).unwrap(), vec![1, 2, 3]);
+
+        // 保存只包含引用的报告，图片保持不变且不产生截图修改记录
+        report.remark = "已复核".to_string();
+        db.save_report("项目A", &report, "").unwrap();
+        let fields: Vec<String> = db
+            .report_history("项目A", "a")
+            .unwrap()
+            .into_iter()
+            .map(|change| change.field)
+            .collect();
+        assert_eq!(fields, vec!["remark".to_string()]);
+        assert_eq!(blob_count(&db), 2);
+
+        let mut reports = vec![report.clone()];
+        db.fill_images(&mut reports).unwrap();
+        assert!(matches!(
+            &reports[0].evidence_screenshots[1],
+            ScreenshotContent::Image(image) if image.data == vec![1, 2, 3] && image.blob.is_none()
+        ));
+
+        if let ScreenshotContent::Image(image) = &mut report.filing_screenshots[0] {
+            image.blob = Some("missing".to_string());
+        }
+        assert!(matches!(
+            db.save_report("项目A", &report, ""),
+            Err(DbError::ImageNotFound(_))
+        ));
+    }
+
+    #[test]
+    fn test_project_rename_and_delete() {
+        let db = Database::open_in_memory().unwrap();
+        for name in ["项目A", "项目B"] {
+            db.save_project(
+                &ReportProject {
+                    project_name: name.to_string(),
+                    report_list: vec![sample_report("a")],
+                    ..Default::default()
+                },
+                "",
+            )
+            .unwrap();
+        }
+
+        assert!(matches!(
+            db.rename_project("项目A", "项目B"),
+            Err(DbError::ProjectExists(_))
+        ));
+        db.rename_project("项目A", "项目C").unwrap();
+        assert!(db.load_project("项目C").is_ok());
+
+        db.delete_project("项目C").unwrap();
+        let names: Vec<String> = db
+            .load_projects()
+            .unwrap()
+            .into_iter()
+            .map(|p| p.project_name)
+            .collect();
+        assert_eq!(names, vec!["项目B".to_string()]);
+        assert_eq!(blob_count(&db), 2);
+    }
+}
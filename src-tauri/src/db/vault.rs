@@ -0,0 +1,392 @@
+use super::{insert_report, load_reports, read_meta, write_meta, Database, DbError};
+use crate::crypto::{random_bytes, Cipher, CryptoError, KdfParams, SALT_LENGTH};
+use rusqlite::{params, Connection, OpenFlags};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::sync::MutexGuard;
+use std::time::{Duration, Instant};
+
+/// 加密配置在 meta 表中的键
+const ENCRYPTION_KEY: &str = "encryption";
+/// 用于校验密码的明文，加密后保存在加密配置中
+const VERIFIER: &[u8] = b"ReportGenerator";
+const VERIFIER_AAD: &[u8] = b"verifier";
+
+/// 新密码使用的密钥派生参数，测试时使用较小的参数
+#[cfg(not(test))]
+fn kdf_params() -> KdfParams {
+    KdfParams::default()
+}
+
+#[cfg(test)]
+fn kdf_params() -> KdfParams {
+    KdfParams {
+        memory_kib: 64,
+        iterations: 1,
+        parallelism: 1,
+    }
+}
+
+/// 加密配置，只保存盐、派生参数和校验数据，不保存密钥
+#[derive(Serialize, Deserialize, Debug, Clone)]
+struct EncryptionMeta {
+    salt: Vec<u8>,
+    kdf: KdfParams,
+    verifier: Vec<u8>,
+    #[serde(default)]
+    auto_lock_minutes: u32, // 无操作自动锁定的分钟数，0 表示不自动锁定
+}
+
+impl EncryptionMeta {
+    /// 为新密码生成加密配置，返回配置和派生的密钥
+    fn create(password: &str, auto_lock_minutes: u32) -> Result<(Self, Cipher), DbError> {
+        if password.is_empty() {
+            return Err(DbError::EmptyPassword);
+        }
+        let salt = random_bytes::<SALT_LENGTH>()?.to_vec();
+        let kdf = kdf_params();
+        let cipher = Cipher::derive(password, &salt, &kdf)?;
+        let verifier = cipher.encrypt(VERIFIER, VERIFIER_AAD)?;
+        let meta = Self {
+            salt,
+            kdf,
+            verifier,
+            auto_lock_minutes,
+        };
+        Ok((meta, cipher))
+    }
+
+    /// 是否与另一配置使用同一密码派生的密钥
+    fn same_key(&self, other: &Self) -> bool {
+        self.salt == other.salt && self.verifier == other.verifier
+    }
+
+    /// 校验密码，正确时返回派生的密钥
+    fn verify(&self, password: &str) -> Result<Cipher, DbError> {
+        let cipher = Cipher::derive(password, &self.salt, &self.kdf)?;
+        match cipher.decrypt(&self.verifier, VERIFIER_AAD) {
+            Ok(plaintext) if plaintext == VERIFIER => Ok(cipher),
+            Ok(_) | Err(CryptoError::Decrypt) => Err(DbError::WrongPassword),
+            Err(e) => Err(e.into()),
+        }
+    }
+}
+
+/// 数据库加密状态
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct VaultStatus {
+    pub encrypted: bool, // 是否启用加密
+    pub locked: bool,    // 是否处于锁定状态
+    #[serde(rename = "autoLockMinutes")]
+    pub auto_lock_minutes: u32,
+}
+
+/// 数据库的密钥状态，锁定时丢弃密钥
+pub(super) struct Vault {
+    meta: Option<EncryptionMeta>,
+    cipher: Option<Cipher>,
+    last_activity: Instant,
+}
+
+impl Vault {
+    /// 读取加密配置，启用加密的数据库以锁定状态打开
+    pub(super) fn load(conn: &Connection) -> Result<Self, DbError> {
+        let meta = read_meta(conn, ENCRYPTION_KEY)?
+            .map(|value| serde_json::from_str(&value))
+            .transpose()?;
+        Ok(Self {
+            meta,
+            cipher: None,
+            last_activity: Instant::now(),
+        })
+    }
+
+    /// 数据库被替换后重新读取加密配置，密钥不变时保持解锁状态
+    pub(super) fn reload(&mut self, conn: &Connection) -> Result<(), DbError> {
+        let reloaded = Self::load(conn)?;
+        let same_key = match (&self.meta, &reloaded.meta) {
+            (Some(current), Some(reloaded)) => current.same_key(reloaded),
+            _ => false,
+        };
+        self.meta = reloaded.meta;
+        if !same_key {
+            self.cipher = None;
+        }
+        Ok(())
+    }
+
+    /// 当前使用的密钥，未启用加密时为 None
+    pub(super) fn cipher(&self) -> Option<&Cipher> {
+        self.cipher.as_ref()
+    }
+
+    fn status(&self) -> VaultStatus {
+        VaultStatus {
+            encrypted: self.meta.is_some(),
+            locked: self.meta.is_some() && self.cipher.is_none(),
+            auto_lock_minutes: self.meta.as_ref().map_or(0, |meta| meta.auto_lock_minutes),
+        }
+    }
+}
+
+/// 以只读方式读取数据库文件的加密配置
+fn read_file_meta(path: &Path) -> Result<Option<EncryptionMeta>, DbError> {
+    let conn = Connection::open_with_flags(path, OpenFlags::SQLITE_OPEN_READ_ONLY)?;
+    Ok(Vault::load(&conn)?.meta)
+}
+
+/// 数据库文件是否启用了加密，用于展示备份文件的加密状态
+pub fn is_encrypted_file(path: &Path) -> Result<bool, DbError> {
+    Ok(read_file_meta(path)?.is_some())
+}
+
+impl Database {
+    fn vault(&self) -> MutexGuard<'_, Vault> {
+        self.vault
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// 获取已解锁的密钥状态并刷新活动时间，锁定时返回错误
+    pub(super) fn unlocked(&self) -> Result<MutexGuard<'_, Vault>, DbError> {
+        let mut vault = self.vault();
+        if vault.status().locked {
+            return Err(DbError::Locked);
+        }
+        vault.last_activity = Instant::now();
+        Ok(vault)
+    }
+
+    /// 当前加密状态
+    pub fn vault_status(&self) -> VaultStatus {
+        self.vault().status()
+    }
+
+    /// 数据库文件的加密配置是否与当前数据库一致：都未加密，或使用同一密码加密。
+    /// 不一致时用该文件替换当前数据库会关闭或启用加密，或需要使用其他密码解锁
+    pub fn same_encryption(&self, path: &Path) -> Result<bool, DbError> {
+        let other = read_file_meta(path)?;
+        Ok(match (&self.vault().meta, &other) {
+            (None, None) => true,
+            (Some(current), Some(other)) => current.same_key(other),
+            _ => false,
+        })
+    }
+
+    /// 使用密码解锁
+    pub fn unlock(&self, password: &str) -> Result<(), DbError> {
+        let mut vault = self.vault();
+        let meta = vault.meta.as_ref().ok_or(DbError::NotEncrypted)?;
+        if vault.cipher.is_none() {
+            vault.cipher = Some(meta.verify(password)?);
+        }
+        vault.last_activity = Instant::now();
+        Ok(())
+    }
+
+    /// 立即锁定，丢弃内存中的密钥
+    pub fn lock(&self) {
+        let mut vault = self.vault();
+        if vault.meta.is_some() {
+            vault.cipher = None;
+        }
+    }
+
+    /// 超过自动锁定时间没有访问数据时锁定，返回是否执行了锁定
+    pub fn lock_if_idle(&self) -> bool {
+        let mut vault = self.vault();
+        let minutes = vault.status().auto_lock_minutes;
+        let idle = vault.last_activity.elapsed() >= Duration::from_secs(u64::from(minutes) * 60);
+        if vault.cipher.is_some() && minutes > 0 && idle {
+            vault.cipher = None;
+            return true;
+        }
+        false
+    }
+
+    /// 启用加密，使用新密钥重写所有项目数据
+    pub fn enable_encryption(&self, password: &str, auto_lock_minutes: u32) -> Result<(), DbError> {
+        let mut vault = self.unlocked()?;
+        if vault.meta.is_some() {
+            return Err(DbError::AlreadyEncrypted);
+        }
+        let (meta, cipher) = EncryptionMeta::create(password, auto_lock_minutes)?;
+        self.rewrite_all(&mut vault, Some(meta), Some(cipher))
+    }
+
+    /// 关闭加密，所有项目数据恢复为明文保存
+    pub fn disable_encryption(&self, password: &str) -> Result<(), DbError> {
+        let mut vault = self.unlocked()?;
+        vault
+            .meta
+            .as_ref()
+            .ok_or(DbError::NotEncrypted)?
+            .verify(password)?;
+        self.rewrite_all(&mut vault, None, None)
+    }
+
+    /// 修改密码，使用新盐派生的密钥重写所有项目数据
+    pub fn change_password(&self, old_password: &str, new_password: &str) -> Result<(), DbError> {
+        let mut vault = self.unlocked()?;
+        let current = vault.meta.as_ref().ok_or(DbError::NotEncrypted)?;
+        current.verify(old_password)?;
+        let (meta, cipher) = EncryptionMeta::create(new_password, current.auto_lock_minutes)?;
+        self.rewrite_all(&mut vault, Some(meta), Some(cipher))
+    }
+
+    /// 设置无操作自动锁定的分钟数，0 表示不自动锁定
+    pub fn set_auto_lock(&self, minutes: u32) -> Result<(), DbError> {
+        let mut vault = self.unlocked()?;
+        let meta = vault.meta.as_mut().ok_or(DbError::NotEncrypted)?;
+        meta.auto_lock_minutes = minutes;
+        write_meta(&self.conn(), ENCRYPTION_KEY, &serde_json::to_string(meta)?)?;
+        Ok(())
+    }
+
+    /// 使用新密钥重写所有报告、截图和图片，并保存对应的加密配置
+    fn rewrite_all(
+        &self,
+        vault: &mut Vault,
+        meta: Option<EncryptionMeta>,
+        cipher: Option<Cipher>,
+    ) -> Result<(), DbError> {
+        let mut conn = self.conn();
+        let project_ids = conn
+            .prepare("SELECT id FROM projects ORDER BY id")?
+            .query_map([], |row| row.get::<_, i64>(0))?
+            .collect::<Result<Vec<_>, _>>()?;
+        let projects = project_ids
+            .into_iter()
+            .map(|id| {
+                Ok((
+                    id,
+                    load_reports(&conn, vault.cipher(), id, true)?,
+                    super::fields::load_custom_fields(&conn, vault.cipher(), id)?,
+                ))
+            })
+            .collect::<Result<Vec<_>, DbError>>()?;
+
+        let tx = conn.transaction()?;
+        super::history::reseal_history(&tx, vault.cipher(), cipher.as_ref())?;
+        tx.execute_batch("DELETE FROM reports; DELETE FROM blobs;")?;
+        for (project_id, reports, fields) in &projects {
+            for (position, report) in reports.iter().enumerate() {
+                insert_report(&tx, cipher.as_ref(), *project_id, position as i64, report)?;
+            }
+            super::fields::save_custom_fields(&tx, cipher.as_ref(), *project_id, fields)?;
+        }
+        match &meta {
+            Some(meta) => write_meta(&tx, ENCRYPTION_KEY, &serde_json::to_string(meta)?)?,
+            None => {
+                tx.execute("DELETE FROM meta WHERE key = ?1", params![ENCRYPTION_KEY])?;
+            }
+        }
+        tx.commit()?;
+
+        // 压缩数据库并合并 WAL，清除文件中残留的旧数据
+        conn.execute_batch("VACUUM; PRAGMA wal_checkpoint(TRUNCATE);")?;
+
+        vault.meta = meta;
+        vault.cipher = cipher;
+        vault.last_activity = Instant::now();
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::ReportProject;
+    use crate::report::{RiskReportData, ScreenshotContent, ScreenshotImage};
+
+    fn sample_project() -> ReportProject {
+        let mut report = RiskReportData {
+            id: "a".to_string(),
+            vul_name: "SQL注入".to_string(),
+            ..Default::default()
+        };
+        report
+            .evidence_screenshots
+            .push(ScreenshotContent::Image(ScreenshotImage {
+                data: vec![1, 2, 3],
+                ..Default::default()
+            }));
+        ReportProject {
+            project_name: "项目A".to_string(),
+            report_list: vec![report],
+            ..Default::default()
+        }
+    }
+
+    fn load(database: &Database) -> Result<serde_json::Value, DbError> {
+        Ok(serde_json::to_value(database.load_project("项目A")?)?)
+    }
+
+    /// 原始报告数据中是否包含明文
+    fn contains_plaintext(database: &Database) -> bool {
+        database
+            .conn()
+            .query_row(
+                "SELECT COUNT(*) FROM reports WHERE CAST(data AS TEXT) LIKE '%SQL注入%'",
+                [],
+                |row| row.get::<_, i64>(0),
+            )
+            .unwrap()
+            > 0
+    }
+
+    #[test]
+    fn test_encryption_lifecycle() {
+        let database = Database::open_in_memory().unwrap();
+        database.save_project(&sample_project(), "").unwrap();
+        let project = load(&database).unwrap();
+        assert!(contains_plaintext(&database));
+
+        database.enable_encryption("secret", 0).unwrap();
+        assert!(!contains_plaintext(&database));
+        assert_eq!(load(&database).unwrap(), project);
+        assert!(matches!(
+            database.enable_encryption("other", 0),
+            Err(DbError::AlreadyEncrypted)
+        ));
+
+        database.lock();
+        assert!(database.vault_status().locked);
+        assert!(matches!(database.load_projects(), Err(DbError::Locked)));
+        assert!(matches!(
+            database.unlock("wrong"),
+            Err(DbError::WrongPassword)
+        ));
+        database.unlock("secret").unwrap();
+        assert_eq!(load(&database).unwrap(), project);
+
+        // 加密状态下被替换为明文的数据视为无效
+        let plaintext = serde_json::to_string(&sample_project().report_list[0]).unwrap();
+        let original: rusqlite::types::Value = database
+            .conn()
+            .query_row("SELECT data FROM reports", [], |row| row.get(0))
+            .unwrap();
+        database
+            .conn()
+            .execute("UPDATE reports SET data = ?1", params![plaintext])
+            .unwrap();
+        assert!(matches!(
+            load(&database),
+            Err(DbError::Crypto(CryptoError::InvalidFormat))
+        ));
+        database
+            .conn()
+            .execute("UPDATE reports SET data = ?1", params![original])
+            .unwrap();
+
+        database.change_password("secret", "new").unwrap();
+        database.lock();
+        assert!(database.unlock("secret").is_err());
+        database.unlock("new").unwrap();
+
+        database.disable_encryption("new").unwrap();
+        assert!(contains_plaintext(&database));
+        assert!(!database.vault_status().encrypted);
+        assert_eq!(load(&database).unwrap(), project);
+    }
+}
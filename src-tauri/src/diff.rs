@@ -0,0 +1,534 @@
+use crate::archive::{self, ArchiveError};
+use crate::db::{diff_fields, Database, DbError, ReportProject};
+use crate::report::{RemediationStatus, RiskReportData};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+
+/// 变更记录中显示的字段名称，顺序与报告表格一致
+const FIELD_LABELS: [(&str, &str); 25] = [
+    ("hazard_type", "隐患类型"),
+    ("report_name", "隐患名称"),
+    ("hazard_level", "隐患级别"),
+    ("report_id", "隐患编号"),
+    ("target", "隐患URL"),
+    ("vul_name", "漏洞名称"),
+    ("warning_level", "预警级别"),
+    ("city", "归属地市"),
+    ("unit_type", "单位类型"),
+    ("industry", "所属行业"),
+    ("customer_company_name", "单位名称"),
+    ("website_name", "网站名称"),
+    ("domain", "网站域名"),
+    ("ip_address", "网站IP"),
+    ("case_number", "工信备案号"),
+    ("report_time", "发现时间"),
+    ("problem_description", "问题描述"),
+    ("vul_modify_repair", "整改建议"),
+    ("evidence_screenshots", "证据截图"),
+    ("filing_screenshots", "备案截图"),
+    ("remark", "备注"),
+    ("evidence_columns", "证据截图列数"),
+    ("remediation_status", "修复状态"),
+    ("retest_date", "复测日期"),
+    ("retest_screenshots", "复测证据截图"),
+];
+
+/// 自定义字段在报告数据中的字段名
+const CUSTOM_FIELDS: &str = "custom_fields";
+
+/// 项目对比相关的错误类型
+#[derive(Debug, thiserror::Error)]
+pub enum DiffError {
+    #[error("{0}")]
+    Database(#[from] DbError),
+    #[error("{0}")]
+    Archive(#[from] ArchiveError),
+    #[error("IO错误: {0}")]
+    Io(#[from] std::io::Error),
+}
+
+/// 参与对比的项目快照来源。两个来源都在后端读取，图片数据完整，截图按内容哈希对比。
+/// 只对比项目数据，不解析已生成的 Word 文档；需要对比交付的报告时，请在生成时同时导出项目归档
+#[derive(Deserialize, Debug, Clone)]
+#[serde(tag = "type")]
+pub enum SnapshotSource {
+    #[serde(rename = "project")]
+    Project { name: String }, // 当前数据库中的项目
+    #[serde(rename = "archive")]
+    Archive {
+        path: String,
+        #[serde(default)]
+        password: Option<String>,
+    }, // 项目归档文件（.rgproj）
+}
+
+impl SnapshotSource {
+    /// 读取快照对应的项目数据
+    pub fn load(&self, database: &Database) -> Result<ReportProject, DiffError> {
+        match self {
+            SnapshotSource::Project { name } => Ok(database.load_project(name)?),
+            SnapshotSource::Archive { path, password } => {
+                let data = std::fs::read(path)?;
+                let (_, project) = archive::import_from_bytes(data, password.as_deref())?;
+                Ok(project)
+            }
+        }
+    }
+}
+
+/// 报告的变化类型
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChangeKind {
+    #[serde(rename = "added")]
+    Added, // 新增
+    #[serde(rename = "removed")]
+    Removed, // 删除
+    #[serde(rename = "modified")]
+    Modified, // 修改
+}
+
+/// 单个字段的差异
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct FieldDiff {
+    pub field: String,
+    pub old_value: Value,
+    pub new_value: Value,
+}
+
+/// 单个报告的差异
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct ReportDiff {
+    pub id: String, // 报告内部ID
+    pub kind: ChangeKind,
+    pub report_id: String, // 隐患编号
+    pub report_name: String,
+    pub changes: Vec<FieldDiff>, // 修改的字段，新增和删除的报告为空
+}
+
+/// 两个项目快照的差异
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct ProjectDiff {
+    pub old_name: String,
+    pub new_name: String,
+    pub reports: Vec<ReportDiff>, // 按新版本顺序排列，删除的报告排在最后
+    pub unchanged: usize,         // 未变化的报告数量
+}
+
+/// 变更记录的导出格式
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum DiffFormat {
+    #[default]
+    #[serde(rename = "markdown")]
+    Markdown,
+    #[serde(rename = "html")]
+    Html,
+}
+
+impl DiffFormat {
+    /// 导出文件扩展名
+    pub fn extension(&self) -> &'static str {
+        match self {
+            DiffFormat::Markdown => "md",
+            DiffFormat::Html => "html",
+        }
+    }
+}
+
+fn report_diff(report: &RiskReportData, kind: ChangeKind, changes: Vec<FieldDiff>) -> ReportDiff {
+    ReportDiff {
+        id: report.id.clone(),
+        kind,
+        report_id: report.report_id.clone(),
+        report_name: report.report_name.clone(),
+        changes,
+    }
+}
+
+/// 按报告内部ID对比两个项目快照
+pub fn diff_projects(old: &ReportProject, new: &ReportProject) -> Result<ProjectDiff, DbError> {
+    let old_reports: HashMap<&str, &RiskReportData> = old
+        .report_list
+        .iter()
+        .map(|report| (report.id.as_str(), report))
+        .collect();
+    let new_ids: Vec<&str> = new
+        .report_list
+        .iter()
+        .map(|report| report.id.as_str())
+        .collect();
+
+    let mut reports = Vec::new();
+    let mut unchanged = 0;
+    for report in &new.report_list {
+        let Some(previous) = old_reports.get(report.id.as_str()) else {
+            reports.push(report_diff(report, ChangeKind::Added, Vec::new()));
+            continue;
+        };
+        let mut changes: Vec<FieldDiff> = diff_fields(previous, report)?
+            .into_iter()
+            .flat_map(|(field, old_value, new_value)| {
+                if field == CUSTOM_FIELDS {
+                    custom_field_changes(&old_value, &new_value)
+                } else {
+                    vec![FieldDiff {
+                        field,
+                        old_value,
+                        new_value,
+                    }]
+                }
+            })
+            .collect();
+        if changes.is_empty() {
+            unchanged += 1;
+            continue;
+        }
+        changes.sort_by_key(|change| field_order(&change.field));
+        reports.push(report_diff(report, ChangeKind::Modified, changes));
+    }
+    reports.extend(
+        old.report_list
+            .iter()
+            .filter(|report| !new_ids.contains(&report.id.as_str()))
+            .map(|report| report_diff(report, ChangeKind::Removed, Vec::new())),
+    );
+
+    Ok(ProjectDiff {
+        old_name: old.project_name.clone(),
+        new_name: new.project_name.clone(),
+        reports,
+        unchanged,
+    })
+}
+
+/// 自定义字段按字段名拆分为单独的差异，字段名为 "custom_fields.<名称>"
+fn custom_field_changes(old: &Value, new: &Value) -> Vec<FieldDiff> {
+    let empty = serde_json::Map::new();
+    let old = old.as_object().unwrap_or(&empty);
+    let new = new.as_object().unwrap_or(&empty);
+    let mut names: Vec<&String> = old.keys().chain(new.keys()).collect();
+    names.sort();
+    names.dedup();
+    names
+        .into_iter()
+        .filter_map(|name| {
+            let old_value = old.get(name).cloned().unwrap_or(Value::Null);
+            let new_value = new.get(name).cloned().unwrap_or(Value::Null);
+            (old_value != new_value).then(|| FieldDiff {
+                field: format!("{}.{}", CUSTOM_FIELDS, name),
+                old_value,
+                new_value,
+            })
+        })
+        .collect()
+}
+
+fn field_order(field: &str) -> usize {
+    FIELD_LABELS
+        .iter()
+        .position(|(name, _)| *name == field)
+        .unwrap_or(FIELD_LABELS.len())
+}
+
+fn field_label(field: &str) -> &str {
+    if let Some(name) = field
+        .strip_prefix(CUSTOM_FIELDS)
+        .and_then(|name| name.strip_prefix('.'))
+    {
+        return name;
+    }
+    FIELD_LABELS
+        .iter()
+        .find(|(name, _)| *name == field)
+        .map_or(field, |(_, label)| label)
+}
+
+/// 截图列表中文本和代码显示的最大字符数
+const SCREENSHOT_PREVIEW_CHARS: usize = 60;
+/// 截图列表中图片内容哈希显示的位数
+const SCREENSHOT_HASH_CHARS: usize = 12;
+
+/// 截断过长的文本，只保留开头部分
+fn preview(text: &str) -> String {
+    match text.char_indices().nth(SCREENSHOT_PREVIEW_CHARS) {
+        Some((end, _)) => format!("{}…", &text[..end]),
+        None => text.to_string(),
+    }
+}
+
+/// 截图列表中的单项。图片显示内容哈希（截图摘要中 data 为内容哈希）和说明，
+/// 文本和代码显示开头部分
+fn display_screenshot(item: &Value) -> String {
+    let content = &item["content"];
+    match item["type"].as_str() {
+        Some("image") => {
+            let hash = content["data"].as_str().unwrap_or_default();
+            let hash = &hash[..hash.len().min(SCREENSHOT_HASH_CHARS)];
+            match content["caption"].as_str() {
+                Some(caption) if !caption.is_empty() => format!("图片 {}：{}", hash, caption),
+                _ => format!("图片 {}", hash),
+            }
+        }
+        Some("text") => format!("文本：{}", preview(content.as_str().unwrap_or_default())),
+        Some("code") => format!(
+            "代码：{}",
+            preview(content["code"].as_str().unwrap_or_default())
+        ),
+        _ => item.to_string(),
+    }
+}
+
+/// 字段值转换为可读文本，截图列表逐项显示
+fn display_value(field: &str, value: &Value) -> String {
+    if field == "remediation_status" {
+        if let Ok(status) = serde_json::from_value::<RemediationStatus>(value.clone()) {
+            return status.label().to_string();
+        }
+    }
+    match value {
+        Value::Null => "（空）".to_string(),
+        Value::String(text) if text.is_empty() => "（空）".to_string(),
+        Value::String(text) => text.clone(),
+        Value::Array(items) if items.is_empty() => "（空）".to_string(),
+        Value::Array(items) => items
+            .iter()
+            .enumerate()
+            .map(|(index, item)| format!("{}. {}", index + 1, display_screenshot(item)))
+            .collect::<Vec<_>>()
+            .join("\n"),
+        other => other.to_string(),
+    }
+}
+
+fn kind_label(kind: ChangeKind) -> &'static str {
+    match kind {
+        ChangeKind::Added => "新增",
+        ChangeKind::Removed => "删除",
+        ChangeKind::Modified => "修改",
+    }
+}
+
+fn escape_markdown(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace('|', "\\|")
+        .replace("\r\n", "<br>")
+        .replace('\n', "<br>")
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace("\r\n", "<br>")
+        .replace('\n', "<br>")
+}
+
+impl ProjectDiff {
+    /// 指定类型的报告数量
+    pub fn count(&self, kind: ChangeKind) -> usize {
+        self.reports
+            .iter()
+            .filter(|report| report.kind == kind)
+            .count()
+    }
+
+    fn title(&self) -> String {
+        if self.old_name == self.new_name {
+            format!("{} 变更记录", self.new_name)
+        } else {
+            format!("{} → {} 变更记录", self.old_name, self.new_name)
+        }
+    }
+
+    fn summary(&self) -> String {
+        format!(
+            "新增 {} 项，删除 {} 项，修改 {} 项，未变化 {} 项",
+            self.count(ChangeKind::Added),
+            self.count(ChangeKind::Removed),
+            self.count(ChangeKind::Modified),
+            self.unchanged
+        )
+    }
+
+    /// 渲染为 Markdown 变更记录
+    pub fn to_markdown(&self) -> String {
+        let mut output = format!("# {}\n\n{}\n", self.title(), self.summary());
+        for report in &self.reports {
+            output.push_str(&format!(
+                "\n## 【{}】{} {}\n",
+                kind_label(report.kind),
+                report.report_id,
+                report.report_name
+            ));
+            if report.changes.is_empty() {
+                continue;
+            }
+            output.push_str("\n| 字段 | 修改前 | 修改后 |\n| --- | --- | --- |\n");
+            for change in &report.changes {
+                output.push_str(&format!(
+                    "| {} | {} | {} |\n",
+                    field_label(&change.field),
+                    escape_markdown(&display_value(&change.field, &change.old_value)),
+                    escape_markdown(&display_value(&change.field, &change.new_value))
+                ));
+            }
+        }
+        output
+    }
+
+    /// 渲染为 HTML 变更记录
+    pub fn to_html(&self) -> String {
+        let title = escape_html(&self.title());
+        let mut body = format!("<h1>{}</h1>\n<p>{}</p>\n", title, self.summary());
+        for report in &self.reports {
+            body.push_str(&format!(
+                "<h2 class=\"{}\">【{}】{} {}</h2>\n",
+                match report.kind {
+                    ChangeKind::Added => "added",
+                    ChangeKind::Removed => "removed",
+                    ChangeKind::Modified => "modified",
+                },
+                kind_label(report.kind),
+                escape_html(&report.report_id),
+                escape_html(&report.report_name)
+            ));
+            if report.changes.is_empty() {
+                continue;
+            }
+            body.push_str("<table>\n<tr><th>字段</th><th>修改前</th><th>修改后</th></tr>\n");
+            for change in &report.changes {
+                body.push_str(&format!(
+                    "<tr><td>{}</td><td class=\"old\">{}</td><td class=\"new\">{}</td></tr>\n",
+                    escape_html(field_label(&change.field)),
+                    escape_html(&display_value(&change.field, &change.old_value)),
+                    escape_html(&display_value(&change.field, &change.new_value))
+                ));
+            }
+            body.push_str("</table>\n");
+        }
+        format!(
+            "<!DOCTYPE html>\n<html lang=\"zh-CN\">\n<head>\n<meta charset=\"utf-8\">\n<title>{}</title>\n<style>\n{}\n</style>\n</head>\n<body>\n{}</body>\n</html>\n",
+            title, HTML_STYLE, body
+        )
+    }
+
+    /// 按指定格式渲染变更记录
+    pub fn render(&self, format: DiffFormat) -> String {
+        match format {
+            DiffFormat::Markdown => self.to_markdown(),
+            DiffFormat::Html => self.to_html(),
+        }
+    }
+}
+
+const HTML_STYLE: &str = "body { font-family: sans-serif; margin: 2em; }
+table { border-collapse: collapse; width: 100%; }
+th, td { border: 1px solid #ccc; padding: 4px 8px; text-align: left; vertical-align: top; }
+th { background: #f3f3f3; }
+h2.added { color: #1a7f37; }
+h2.removed { color: #cf222e; }
+h2.modified { color: #9a6700; }
+td.old { color: #cf222e; }
+td.new { color: #1a7f37; }";
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::report::{content_hash, ScreenshotContent, ScreenshotImage};
+
+    fn report(id: &str, level: &str) -> RiskReportData {
+        RiskReportData {
+            id: id.to_string(),
+            report_id: format!("R-{}", id),
+            report_name: "SQL注入".to_string(),
+            hazard_level: level.to_string(),
+            ..Default::default()
+        }
+    }
+
+    fn project(reports: Vec<RiskReportData>) -> ReportProject {
+        ReportProject {
+            project_name: "项目A".to_string(),
+            report_list: reports,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_diff_projects() {
+        let mut old = project(vec![
+            report("a", "高危"),
+            report("b", "中危"),
+            report("c", "低危"),
+        ]);
+        let mut changed = report("a", "中危");
+        changed.problem_description = "存在 | 注入\n第二行".to_string();
+        changed
+            .custom_fields
+            .insert("端口".to_string(), "8080".to_string());
+        let old_image = ScreenshotContent::Image(ScreenshotImage {
+            data: vec![1, 2, 3],
+            ..Default::default()
+        });
+        let new_image = ScreenshotContent::Image(ScreenshotImage {
+            data: vec![4, 5, 6],
+            caption: Some("登录页面".to_string()),
+            ..Default::default()
+        });
+        old.report_list[0].evidence_screenshots =
+            vec![ScreenshotContent::Text("访问首页".to_string()), old_image];
+        changed.evidence_screenshots =
+            vec![ScreenshotContent::Text("访问首页".to_string()), new_image];
+        let new = project(vec![changed, report("c", "低危"), report("d", "严重")]);
+
+        let diff = diff_projects(&old, &new).unwrap();
+        let kinds: Vec<(&str, ChangeKind)> = diff
+            .reports
+            .iter()
+            .map(|report| (report.id.as_str(), report.kind))
+            .collect();
+        assert_eq!(
+            kinds,
+            vec![
+                ("a", ChangeKind::Modified),
+                ("d", ChangeKind::Added),
+                ("b", ChangeKind::Removed)
+            ]
+        );
+        assert_eq!(diff.unchanged, 1);
+        // 字段按报告表格顺序排列
+        let fields: Vec<&str> = diff.reports[0]
+            .changes
+            .iter()
+            .map(|change| change.field.as_str())
+            .collect();
+        assert_eq!(
+            fields,
+            vec![
+                "hazard_level",
+                "problem_description",
+                "evidence_screenshots",
+                "custom_fields.端口"
+            ]
+        );
+
+        let markdown = diff.to_markdown();
+        assert!(markdown.contains("新增 1 项，删除 1 项，修改 1 项，未变化 1 项"));
+        assert!(markdown.contains("| 隐患级别 | 高危 | 中危 |"));
+        assert!(markdown.contains("| 问题描述 | （空） | 存在 \\| 注入<br>第二行 |"));
+        assert!(markdown.contains("## 【删除】R-b SQL注入"));
+        assert!(markdown.contains("| 端口 | （空） | 8080 |"));
+        // 截图逐项显示，图片以内容哈希区分
+        let old_hash = &content_hash(&[1, 2, 3])[..SCREENSHOT_HASH_CHARS];
+        let new_hash = &content_hash(&[4, 5, 6])[..SCREENSHOT_HASH_CHARS];
+        assert!(markdown.contains(&format!(
+            "| 证据截图 | 1. 文本：访问首页<br>2. 图片 {} | 1. 文本：访问首页<br>2. 图片 {}：登录页面 |",
+            old_hash, new_hash
+        )));
+
+        let html = diff.to_html();
+        assert!(html.contains("<h2 class=\"added\">【新增】R-d SQL注入</h2>"));
+        assert!(html.contains("<td class=\"new\">中危</td>"));
+    }
+}
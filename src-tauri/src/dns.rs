@@ -0,0 +1,335 @@
+use crate::report::RiskReportData;
+use hickory_resolver::config::{LookupIpStrategy, NameServerConfigGroup, ResolverOpts};
+use hickory_resolver::error::{ResolveError, ResolveErrorKind};
+use hickory_resolver::{system_conf, Resolver};
+use serde::{Deserialize, Serialize};
+use std::net::{IpAddr, SocketAddr};
+use std::time::Duration;
+
+/// 域名解析相关的错误类型
+#[derive(Debug, thiserror::Error)]
+pub enum DnsError {
+    #[error("无法从目标中解析出主机: {0}")]
+    InvalidTarget(String),
+    #[error("DNS服务器地址无效: {0}")]
+    InvalidNameserver(String),
+    #[error("DNS解析失败: {0}")]
+    Resolve(String),
+    #[error("IO错误: {0}")]
+    Io(#[from] std::io::Error),
+}
+
+/// 目标中的主机部分
+#[derive(Debug, Clone, PartialEq)]
+pub enum Host {
+    Domain(String),
+    Ip(IpAddr),
+}
+
+/// 域名解析接口
+pub trait DnsResolver: Send + Sync {
+    /// 解析域名的 A/AAAA 记录
+    fn lookup_ip(&self, domain: &str) -> Result<Vec<IpAddr>, DnsError>;
+    /// 反向解析 IP 的 PTR 记录，不支持时返回空列表
+    fn reverse(&self, ip: IpAddr) -> Result<Vec<String>, DnsError>;
+}
+
+/// 解析器配置，未指定 DNS 服务器时使用系统配置的 DNS 服务器
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct ResolverConfig {
+    pub nameserver: Option<String>, // DNS服务器地址，如 "223.5.5.5" 或 "127.0.0.1:5353"
+    pub timeout_ms: u64,
+}
+
+impl Default for ResolverConfig {
+    fn default() -> Self {
+        Self {
+            nameserver: None,
+            timeout_ms: 3000,
+        }
+    }
+}
+
+impl ResolverConfig {
+    /// 根据配置创建解析器
+    pub fn build(&self) -> Result<Box<dyn DnsResolver>, DnsError> {
+        let (config, mut options) = match self.nameserver.as_deref().map(str::trim) {
+            Some(server) if !server.is_empty() => {
+                let address = server
+                    .parse::<SocketAddr>()
+                    .or_else(|_| server.parse::<IpAddr>().map(|ip| SocketAddr::new(ip, 53)))
+                    .map_err(|_| DnsError::InvalidNameserver(server.to_string()))?;
+                let servers =
+                    NameServerConfigGroup::from_ips_clear(&[address.ip()], address.port(), true);
+                let mut options = ResolverOpts::default();
+                // 指定服务器时只采用该服务器的结果
+                options.use_hosts_file = false;
+                (
+                    hickory_resolver::config::ResolverConfig::from_parts(None, vec![], servers),
+                    options,
+                )
+            }
+            _ => system_conf::read_system_conf().map_err(|e| DnsError::Resolve(e.to_string()))?,
+        };
+        options.timeout = Duration::from_millis(self.timeout_ms);
+        options.ip_strategy = LookupIpStrategy::Ipv4AndIpv6;
+        Ok(Box::new(HickoryResolver {
+            resolver: Resolver::new(config, options)?,
+        }))
+    }
+}
+
+/// 基于 hickory-resolver 的解析器，支持 TCP 回退（应答截断时）和国际化域名
+pub struct HickoryResolver {
+    resolver: Resolver,
+}
+
+/// 域名不存在或没有对应记录时视为空结果
+fn empty_if_not_found<T>(error: ResolveError) -> Result<Vec<T>, DnsError> {
+    match error.kind() {
+        ResolveErrorKind::NoRecordsFound { .. } => Ok(Vec::new()),
+        _ => Err(DnsError::Resolve(error.to_string())),
+    }
+}
+
+impl DnsResolver for HickoryResolver {
+    fn lookup_ip(&self, domain: &str) -> Result<Vec<IpAddr>, DnsError> {
+        match self.resolver.lookup_ip(domain) {
+            Ok(lookup) => {
+                let mut addresses = Vec::new();
+                for ip in lookup.iter() {
+                    if !addresses.contains(&ip) {
+                        addresses.push(ip);
+                    }
+                }
+                Ok(addresses)
+            }
+            Err(e) => empty_if_not_found(e),
+        }
+    }
+
+    fn reverse(&self, ip: IpAddr) -> Result<Vec<String>, DnsError> {
+        match self.resolver.reverse_lookup(ip) {
+            Ok(lookup) => Ok(lookup
+                .iter()
+                .map(|name| name.to_utf8().trim_end_matches('.').to_string())
+                .collect()),
+            Err(e) => empty_if_not_found(e),
+        }
+    }
+}
+
+/// 从目标中提取主机，支持 URL、带端口的地址及 IPv6 方括号形式
+pub fn extract_host(target: &str) -> Result<Host, DnsError> {
+    let invalid = || DnsError::InvalidTarget(target.to_string());
+    let trimmed = target.trim();
+    let without_scheme = trimmed.split_once("://").map_or(trimmed, |(_, rest)| rest);
+    let authority = without_scheme
+        .split(['/', '?', '#'])
+        .next()
+        .unwrap_or_default();
+    let authority = authority.rsplit('@').next().unwrap_or_default();
+
+    let host = if let Some(rest) = authority.strip_prefix('[') {
+        rest.split_once(']')
+            .map(|(host, _)| host)
+            .ok_or_else(invalid)?
+    } else if authority.matches(':').count() > 1 {
+        authority // 不带方括号的 IPv6 地址
+    } else {
+        authority.split(':').next().unwrap_or_default()
+    };
+
+    if let Ok(ip) = host.parse::<IpAddr>() {
+        return Ok(Host::Ip(ip));
+    }
+
+    let domain = host.trim_end_matches('.').to_ascii_lowercase();
+    let valid = domain.contains('.')
+        && domain.split('.').all(|label| {
+            !label.is_empty() && label.chars().all(|c| c.is_alphanumeric() || c == '-')
+        });
+    if valid {
+        Ok(Host::Domain(domain))
+    } else {
+        Err(invalid())
+    }
+}
+
+/// 拆分手工填写的 IP 字段，支持逗号、顿号、分号和空白分隔
+fn parse_entered_ips(value: &str) -> Vec<IpAddr> {
+    value
+        .split([',', '，', '、', ';', '；', ' ', '\n', '\t'])
+        .filter_map(|part| part.trim().parse().ok())
+        .collect()
+}
+
+/// 单个报告的补全结果
+#[derive(Serialize, Debug, Clone, Default)]
+pub struct EnrichmentResult {
+    pub id: String,            // 报告内部ID
+    pub host: Option<String>,  // 从目标中提取的主机
+    pub resolved: Vec<String>, // 域名解析结果
+    pub reverse: Vec<String>,  // IP 反向解析结果
+    pub mismatch: bool,        // 填写的 IP 与解析结果不一致
+    pub error: Option<String>, // 解析失败原因
+}
+
+/// 解析报告目标，补全域名与 IP 并检查二者是否一致
+pub fn enrich_report(report: &mut RiskReportData, resolver: &dyn DnsResolver) -> EnrichmentResult {
+    let mut result = EnrichmentResult {
+        id: report.id.clone(),
+        ..Default::default()
+    };
+
+    let source = if report.target.trim().is_empty() {
+        &report.domain
+    } else {
+        &report.target
+    };
+    let host = match extract_host(source) {
+        Ok(host) => host,
+        Err(e) => {
+            result.error = Some(e.to_string());
+            return result;
+        }
+    };
+
+    let entered = parse_entered_ips(&report.ip_address);
+    let resolved = match &host {
+        Host::Domain(domain) => {
+            result.host = Some(domain.clone());
+            report.domain = domain.clone();
+            resolver.lookup_ip(domain)
+        }
+        Host::Ip(ip) => {
+            result.host = Some(ip.to_string());
+            Ok(vec![*ip])
+        }
+    };
+
+    match resolved {
+        Ok(addresses) => {
+            result.resolved = addresses.iter().map(IpAddr::to_string).collect();
+            if entered.is_empty() {
+                if let Some(first) = addresses.first() {
+                    report.ip_address = first.to_string();
+                }
+            } else if !addresses.is_empty() {
+                result.mismatch = entered.iter().any(|ip| !addresses.contains(ip));
+            }
+        }
+        Err(e) => result.error = Some(e.to_string()),
+    }
+
+    // 对填写或解析得到的第一个 IP 做反向解析
+    if let Some(ip) = parse_entered_ips(&report.ip_address).first() {
+        match resolver.reverse(*ip) {
+            Ok(names) => result.reverse = names,
+            Err(e) => log::debug!("反向解析失败: {}: {}", ip, e),
+        }
+    }
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hickory_resolver::proto::op::{Message, MessageType};
+    use hickory_resolver::proto::rr::rdata::{A, PTR};
+    use hickory_resolver::proto::rr::{Name, RData, Record, RecordType};
+    use hickory_resolver::proto::serialize::binary::{BinDecodable, BinEncodable};
+    use std::net::{Ipv4Addr, UdpSocket};
+
+    /// 本地 DNS 桩的应答：A 查询返回 10.0.0.8，PTR 查询返回 host.example.com，其余为空
+    fn stub_response(query: &Message) -> Message {
+        let mut response = Message::new();
+        response
+            .set_id(query.id())
+            .set_message_type(MessageType::Response)
+            .set_op_code(query.op_code())
+            .set_recursion_desired(true)
+            .set_recursion_available(true)
+            .add_queries(query.queries().to_vec());
+        if let Some(question) = query.queries().first() {
+            let rdata = match question.query_type() {
+                RecordType::A => Some(RData::A(A(Ipv4Addr::new(10, 0, 0, 8)))),
+                RecordType::PTR => Some(RData::PTR(PTR(
+                    Name::from_ascii("host.example.com.").unwrap()
+                ))),
+                _ => None,
+            };
+            if let Some(rdata) = rdata {
+                response.add_answer(Record::from_rdata(question.name().clone(), 60, rdata));
+            }
+        }
+        response
+    }
+
+    #[test]
+    fn test_extract_host() {
+        assert_eq!(
+            extract_host("https://admin:pw@WWW.Example.com:8443/login").unwrap(),
+            Host::Domain("www.example.com".to_string())
+        );
+        assert_eq!(
+            extract_host("http://[2001:db8::1]:8080/").unwrap(),
+            Host::Ip("2001:db8::1".parse().unwrap())
+        );
+        assert_eq!(
+            extract_host("10.0.0.1:3389").unwrap(),
+            Host::Ip("10.0.0.1".parse().unwrap())
+        );
+        assert_eq!(
+            extract_host("http://例子.中国/").unwrap(),
+            Host::Domain("例子.中国".to_string())
+        );
+        assert!(extract_host("").is_err());
+        assert_eq!(
+            parse_entered_ips("1.1.1.1、2.2.2.2, x"),
+            vec![
+                "1.1.1.1".parse::<IpAddr>().unwrap(),
+                "2.2.2.2".parse().unwrap()
+            ]
+        );
+    }
+
+    #[test]
+    fn test_enrich_with_local_stub() {
+        let stub = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let address = stub.local_addr().unwrap();
+        std::thread::spawn(move || {
+            let mut buffer = [0u8; 512];
+            while let Ok((length, from)) = stub.recv_from(&mut buffer) {
+                let Ok(query) = Message::from_bytes(&buffer[..length]) else {
+                    continue;
+                };
+                let response = stub_response(&query).to_bytes().unwrap();
+                let _ = stub.send_to(&response, from);
+            }
+        });
+
+        let resolver = ResolverConfig {
+            nameserver: Some(address.to_string()),
+            timeout_ms: 2000,
+        }
+        .build()
+        .unwrap();
+
+        let mut report = RiskReportData {
+            target: "https://Portal.Example.com/login".to_string(),
+            ..Default::default()
+        };
+        let result = enrich_report(&mut report, resolver.as_ref());
+        assert_eq!(result.error, None);
+        assert_eq!(report.domain, "portal.example.com");
+        assert_eq!(report.ip_address, "10.0.0.8");
+        assert!(!result.mismatch);
+        assert_eq!(result.reverse, vec!["host.example.com".to_string()]);
+
+        report.ip_address = "10.0.0.9".to_string();
+        assert!(enrich_report(&mut report, resolver.as_ref()).mismatch);
+    }
+}
@@ -0,0 +1,314 @@
+use crate::report::RiskReportData;
+use serde::{Deserialize, Serialize};
+use std::net::{IpAddr, Ipv4Addr};
+
+/// IP 归属地查询相关的错误类型
+#[derive(Debug, thiserror::Error)]
+pub enum GeoIpError {
+    #[error("IP归属地数据库格式无效: {0}")]
+    InvalidDatabase(String),
+    #[error("IO错误: {0}")]
+    Io(#[from] std::io::Error),
+}
+
+/// IP 归属地信息
+#[derive(Serialize, Debug, Clone, Default, PartialEq)]
+pub struct Region {
+    pub country: String,
+    pub province: String,
+    pub city: String,
+    pub isp: String,
+}
+
+/// IP 归属地数据库接口，未收录的 IP 返回 None
+pub trait GeoIpDatabase: Send + Sync {
+    fn lookup(&self, ip: IpAddr) -> Option<Region>;
+}
+
+/// xdb 文件头长度
+const HEADER_LENGTH: usize = 256;
+/// 向量索引的行列数（按 IP 前两个字节索引）
+const VECTOR_INDEX_ROWS: usize = 256;
+const VECTOR_INDEX_COLS: usize = 256;
+/// 向量索引单项长度：起始指针 + 结束指针
+const VECTOR_INDEX_SIZE: usize = 8;
+/// 段索引单项长度：起始IP + 结束IP + 数据长度 + 数据指针
+const SEGMENT_INDEX_SIZE: usize = 14;
+
+/// ip2region xdb 格式的离线数据库，整体加载到内存中查询，仅支持 IPv4
+pub struct Ip2Region {
+    data: Vec<u8>,
+}
+
+impl Ip2Region {
+    /// 从文件加载数据库
+    pub fn load(path: &str) -> Result<Self, GeoIpError> {
+        Self::from_bytes(std::fs::read(path)?)
+    }
+
+    /// 从内存数据创建数据库
+    pub fn from_bytes(data: Vec<u8>) -> Result<Self, GeoIpError> {
+        let min_length = HEADER_LENGTH + VECTOR_INDEX_ROWS * VECTOR_INDEX_COLS * VECTOR_INDEX_SIZE;
+        if data.len() < min_length {
+            return Err(GeoIpError::InvalidDatabase(format!(
+                "文件长度{}字节，小于索引长度",
+                data.len()
+            )));
+        }
+        Ok(Self { data })
+    }
+
+    fn read_u32(&self, offset: usize) -> Option<u32> {
+        let bytes = self.data.get(offset..offset + 4)?;
+        Some(u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+    }
+
+    fn read_u16(&self, offset: usize) -> Option<u16> {
+        let bytes = self.data.get(offset..offset + 2)?;
+        Some(u16::from_le_bytes([bytes[0], bytes[1]]))
+    }
+
+    /// 查询 IPv4 对应的原始区域字符串
+    fn search(&self, ip: Ipv4Addr) -> Option<String> {
+        let ip = u32::from(ip);
+        let row = (ip >> 24) as usize;
+        let col = ((ip >> 16) & 0xFF) as usize;
+        let index = HEADER_LENGTH + (row * VECTOR_INDEX_COLS + col) * VECTOR_INDEX_SIZE;
+        let start = self.read_u32(index)? as usize;
+        let end = self.read_u32(index + 4)? as usize;
+        if end < start {
+            return None;
+        }
+
+        // 在段索引中二分查找
+        let (mut low, mut high) = (0usize, (end - start) / SEGMENT_INDEX_SIZE + 1);
+        while low < high {
+            let middle = (low + high) / 2;
+            let offset = start + middle * SEGMENT_INDEX_SIZE;
+            let start_ip = self.read_u32(offset)?;
+            let end_ip = self.read_u32(offset + 4)?;
+            if ip < start_ip {
+                high = middle;
+            } else if ip > end_ip {
+                low = middle + 1;
+            } else {
+                let length = self.read_u16(offset + 8)? as usize;
+                let pointer = self.read_u32(offset + 10)? as usize;
+                let region = self.data.get(pointer..pointer + length)?;
+                return Some(String::from_utf8_lossy(region).into_owned());
+            }
+        }
+        None
+    }
+}
+
+impl GeoIpDatabase for Ip2Region {
+    fn lookup(&self, ip: IpAddr) -> Option<Region> {
+        match ip {
+            IpAddr::V4(ip) => self.search(ip).map(|region| parse_region(&region)),
+            IpAddr::V6(ip) => ip
+                .to_ipv4_mapped()
+                .and_then(|ip| self.search(ip))
+                .map(|region| parse_region(&region)),
+        }
+    }
+}
+
+/// 解析区域字符串，兼容 "国家|区域|省份|城市|ISP" 与 "国家|省份|城市|ISP" 两种格式，"0" 表示未知
+pub fn parse_region(region: &str) -> Region {
+    let parts: Vec<&str> = region
+        .split('|')
+        .map(|part| if part == "0" { "" } else { part.trim() })
+        .collect();
+    let part = |index: usize| parts.get(index).copied().unwrap_or_default().to_string();
+
+    if parts.len() >= 5 {
+        Region {
+            country: part(0),
+            province: part(2),
+            city: part(3),
+            isp: part(4),
+        }
+    } else {
+        Region {
+            country: part(0),
+            province: part(1),
+            city: part(2),
+            isp: part(3),
+        }
+    }
+}
+
+/// 归属地市补全配置
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(default)]
+pub struct CityFillOptions {
+    pub database_path: String, // ip2region xdb 数据库路径
+    pub overwrite: bool,       // 是否覆盖已填写的归属地市
+    pub trim_suffix: bool,     // 是否去除末尾的 "市"，如 "北京市" -> "北京"
+}
+
+/// 单个报告的归属地建议
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct CityProposal {
+    pub id: String,         // 报告内部ID
+    pub ip_address: String, // 用于查询的IP
+    pub region: Region,
+    pub city: String,  // 建议的归属地市，城市未知时使用省份
+    pub applied: bool, // 是否已写入报告
+}
+
+/// 根据 IP 为所有报告生成归属地建议，并按配置写入归属地市
+pub fn fill_cities(
+    reports: &mut [RiskReportData],
+    database: &dyn GeoIpDatabase,
+    options: &CityFillOptions,
+) -> Vec<CityProposal> {
+    let mut proposals = Vec::new();
+
+    for report in reports.iter_mut() {
+        // IP 字段可能包含多个地址，取第一个可解析的
+        let Some(ip) = report
+            .ip_address
+            .split([',', '，', '、', ';', '；', ' '])
+            .find_map(|part| part.trim().parse::<IpAddr>().ok())
+        else {
+            continue;
+        };
+        let Some(region) = database.lookup(ip) else {
+            continue;
+        };
+
+        let mut city = if region.city.is_empty() {
+            region.province.clone()
+        } else {
+            region.city.clone()
+        };
+        if options.trim_suffix && city.chars().count() > 2 {
+            if let Some(trimmed) = city.strip_suffix('市') {
+                city = trimmed.to_string();
+            }
+        }
+        if city.is_empty() {
+            continue;
+        }
+
+        let applied = options.overwrite || report.city.trim().is_empty();
+        if applied {
+            report.city = city.clone();
+        }
+        proposals.push(CityProposal {
+            id: report.id.clone(),
+            ip_address: ip.to_string(),
+            region,
+            city,
+            applied,
+        });
+    }
+
+    proposals
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 构造只包含给定网段的 xdb 数据
+    fn build_xdb(segments: &[(Ipv4Addr, Ipv4Addr, &str)]) -> Vec<u8> {
+        let index_end = HEADER_LENGTH + VECTOR_INDEX_ROWS * VECTOR_INDEX_COLS * VECTOR_INDEX_SIZE;
+        let mut data = vec![0u8; index_end];
+
+        // 区域字符串紧跟在索引之后，段索引放在最后
+        let mut pointers = Vec::new();
+        for (_, _, region) in segments {
+            pointers.push(data.len());
+            data.extend_from_slice(region.as_bytes());
+        }
+        let segment_start = data.len();
+        for ((start, end, region), pointer) in segments.iter().zip(&pointers) {
+            data.extend_from_slice(&u32::from(*start).to_le_bytes());
+            data.extend_from_slice(&u32::from(*end).to_le_bytes());
+            data.extend_from_slice(&(region.len() as u16).to_le_bytes());
+            data.extend_from_slice(&(*pointer as u32).to_le_bytes());
+        }
+
+        // 测试网段不跨越前两个字节，向量索引直接指向对应的段
+        for (index, (start, _, _)) in segments.iter().enumerate() {
+            let [a, b, _, _] = start.octets();
+            let offset =
+                HEADER_LENGTH + (a as usize * VECTOR_INDEX_COLS + b as usize) * VECTOR_INDEX_SIZE;
+            let pointer = (segment_start + index * SEGMENT_INDEX_SIZE) as u32;
+            data[offset..offset + 4].copy_from_slice(&pointer.to_le_bytes());
+            data[offset + 4..offset + 8].copy_from_slice(&pointer.to_le_bytes());
+        }
+        data
+    }
+
+    #[test]
+    fn test_parse_region() {
+        assert_eq!(
+            parse_region("中国|0|广东省|深圳市|电信"),
+            Region {
+                country: "中国".to_string(),
+                province: "广东省".to_string(),
+                city: "深圳市".to_string(),
+                isp: "电信".to_string(),
+            }
+        );
+        assert_eq!(parse_region("中国|北京|北京市|联通").city, "北京市");
+        assert_eq!(parse_region("0|0|0|内网IP|内网IP").country, "");
+    }
+
+    #[test]
+    fn test_fill_cities() {
+        let database = Ip2Region::from_bytes(build_xdb(&[
+            (
+                Ipv4Addr::new(1, 2, 0, 0),
+                Ipv4Addr::new(1, 2, 255, 255),
+                "中国|0|北京|北京市|联通",
+            ),
+            (
+                Ipv4Addr::new(8, 8, 0, 0),
+                Ipv4Addr::new(8, 8, 255, 255),
+                "美国|0|0|0|Level3",
+            ),
+        ]))
+        .unwrap();
+        assert!(database.lookup("9.9.9.9".parse().unwrap()).is_none());
+
+        let mut reports = vec![
+            RiskReportData {
+                id: "a".to_string(),
+                ip_address: "1.2.3.4, 5.6.7.8".to_string(),
+                ..Default::default()
+            },
+            RiskReportData {
+                id: "b".to_string(),
+                ip_address: "1.2.9.9".to_string(),
+                city: "上海".to_string(),
+                ..Default::default()
+            },
+            RiskReportData {
+                id: "c".to_string(),
+                ip_address: "8.8.8.8".to_string(),
+                ..Default::default()
+            },
+        ];
+        let options = CityFillOptions {
+            trim_suffix: true,
+            ..Default::default()
+        };
+        let proposals = fill_cities(&mut reports, &database, &options);
+
+        assert_eq!(proposals.len(), 2);
+        assert_eq!(reports[0].city, "北京");
+        assert!(proposals[0].applied);
+        // 已填写的归属地市默认不覆盖
+        assert_eq!(reports[1].city, "上海");
+        assert!(!proposals[1].applied);
+        // 城市与省份均未知时不生成建议
+        assert_eq!(reports[2].city, "");
+
+        assert!(Ip2Region::from_bytes(vec![0; 16]).is_err());
+    }
+}
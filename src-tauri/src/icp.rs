@@ -0,0 +1,364 @@
+use crate::report::RiskReportData;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::time::Duration;
+
+/// 备案查询相关的错误类型
+#[derive(Debug, thiserror::Error)]
+pub enum IcpError {
+    #[error("域名无效: {0}")]
+    InvalidDomain(String),
+    #[error("备案查询请求失败: {0}")]
+    Http(String),
+    #[error("备案查询结果解析失败: {0}")]
+    Parse(String),
+    #[error("IO错误: {0}")]
+    Io(#[from] std::io::Error),
+}
+
+/// 备案信息
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(default)]
+pub struct IcpRecord {
+    pub domain: String,      // 备案域名
+    pub case_number: String, // 备案号
+    pub entity_name: String, // 主办单位名称
+    pub entity_type: String, // 主办单位性质，如 "企业"、"事业单位"
+    pub site_name: String,   // 网站名称
+}
+
+impl IcpRecord {
+    /// 将主办单位性质映射为报告中的单位类型
+    pub fn unit_type(&self) -> &'static str {
+        match self.entity_type.trim() {
+            "企业" => "民营企业",
+            "国有企业" => "国有企业",
+            "事业单位" => "事业单位",
+            "社会团体" => "社会团体",
+            "民办非企业单位" | "民办非企业" => "民办非企业",
+            "政府机关" => "政府机关",
+            "党委机关" => "党委机关",
+            "人大政协" => "人大政协",
+            "个人" => "个人",
+            _ => "其他组织",
+        }
+    }
+
+    /// 将备案信息填入报告，空字段不会覆盖已有内容
+    pub fn apply_to(&self, report: &mut RiskReportData) {
+        let fields = [
+            (&mut report.case_number, self.case_number.as_str()),
+            (&mut report.customer_company_name, self.entity_name.as_str()),
+            (&mut report.website_name, self.site_name.as_str()),
+        ];
+        for (field, value) in fields {
+            if !value.is_empty() {
+                *field = value.to_string();
+            }
+        }
+        if !self.entity_type.is_empty() {
+            report.unit_type = self.unit_type().to_string();
+        }
+    }
+}
+
+/// 备案信息查询接口，查询不到备案时返回 None
+pub trait IcpProvider: Send + Sync {
+    /// 查询单个已规范化域名的备案信息，不做上级域名回退
+    fn lookup_exact(&self, domain: &str) -> Result<Option<IcpRecord>, IcpError>;
+
+    /// 查询域名的备案信息，依次回退到上级域名直至主域名
+    fn lookup(&self, domain: &str) -> Result<Option<IcpRecord>, IcpError> {
+        let domain = normalize_domain(domain)?;
+        for candidate in candidate_domains(&domain) {
+            if let Some(record) = self.lookup_exact(candidate)? {
+                return Ok(Some(record));
+            }
+        }
+        Ok(None)
+    }
+}
+
+/// HTTP 接口返回结果中各字段的路径，使用 "." 分隔，数字表示数组下标
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct IcpFieldMapping {
+    pub case_number: String,
+    pub entity_name: String,
+    pub entity_type: String,
+    pub site_name: String,
+}
+
+impl Default for IcpFieldMapping {
+    fn default() -> Self {
+        Self {
+            case_number: "data.0.serviceLicence".to_string(),
+            entity_name: "data.0.unitName".to_string(),
+            entity_type: "data.0.natureName".to_string(),
+            site_name: "data.0.serviceName".to_string(),
+        }
+    }
+}
+
+/// 备案查询数据源配置
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(tag = "type")]
+pub enum IcpProviderConfig {
+    /// 第三方 HTTP 查询接口，endpoint 中的 {domain} 会被替换为查询域名
+    #[serde(rename = "http")]
+    Http {
+        endpoint: String,
+        #[serde(default)]
+        headers: HashMap<String, String>,
+        #[serde(default)]
+        fields: IcpFieldMapping,
+        #[serde(default = "default_timeout_secs")]
+        timeout_secs: u64,
+    },
+    /// 本地 JSON 文件，内容为备案信息数组，用于离线查询和测试
+    #[serde(rename = "file")]
+    File { path: String },
+}
+
+fn default_timeout_secs() -> u64 {
+    15
+}
+
+impl IcpProviderConfig {
+    /// 根据配置创建查询接口
+    pub fn build(&self) -> Result<Box<dyn IcpProvider>, IcpError> {
+        match self {
+            IcpProviderConfig::Http {
+                endpoint,
+                headers,
+                fields,
+                timeout_secs,
+            } => Ok(Box::new(HttpIcpProvider::new(
+                endpoint.clone(),
+                headers.clone(),
+                fields.clone(),
+                Duration::from_secs(*timeout_secs),
+            )?)),
+            IcpProviderConfig::File { path } => Ok(Box::new(FileIcpProvider::load(path)?)),
+        }
+    }
+}
+
+/// 规范化域名：去除协议、路径、端口及末尾的点，并转为小写
+pub fn normalize_domain(input: &str) -> Result<String, IcpError> {
+    let trimmed = input.trim();
+    let without_scheme = trimmed.split_once("://").map_or(trimmed, |(_, rest)| rest);
+    let host = without_scheme
+        .split(['/', '?', '#'])
+        .next()
+        .unwrap_or_default()
+        .rsplit('@')
+        .next()
+        .unwrap_or_default();
+    let host = host
+        .split_once(':')
+        .map_or(host, |(host, _)| host)
+        .trim_end_matches('.')
+        .to_ascii_lowercase();
+
+    let valid = !host.is_empty()
+        && host.contains('.')
+        && host.split('.').all(|label| {
+            !label.is_empty() && label.chars().all(|c| c.is_alphanumeric() || c == '-')
+        });
+    if valid {
+        Ok(host)
+    } else {
+        Err(IcpError::InvalidDomain(input.to_string()))
+    }
+}
+
+/// 常见的二级公共后缀，其下一级才是可注册的主域名
+const SECOND_LEVEL_SUFFIXES: &[&str] = &[
+    "com.cn", "net.cn", "org.cn", "gov.cn", "edu.cn", "ac.cn", "mil.cn", "com.hk", "com.tw",
+    "co.uk", "org.uk", "co.jp", "com.au",
+];
+
+/// 依次返回域名及其上级域名，直至主域名为止，备案通常登记在主域名下
+fn candidate_domains(domain: &str) -> Vec<&str> {
+    let mut candidates = vec![domain];
+    let mut rest = domain;
+    while let Some((_, parent)) = rest.split_once('.') {
+        if !parent.contains('.') || SECOND_LEVEL_SUFFIXES.contains(&parent) {
+            break;
+        }
+        candidates.push(parent);
+        rest = parent;
+    }
+    candidates
+}
+
+/// 基于 HTTP 接口的备案查询
+pub struct HttpIcpProvider {
+    client: reqwest::blocking::Client,
+    endpoint: String,
+    headers: HashMap<String, String>,
+    fields: IcpFieldMapping,
+}
+
+impl HttpIcpProvider {
+    pub fn new(
+        endpoint: String,
+        headers: HashMap<String, String>,
+        fields: IcpFieldMapping,
+        timeout: Duration,
+    ) -> Result<Self, IcpError> {
+        let client = reqwest::blocking::Client::builder()
+            .timeout(timeout)
+            .build()
+            .map_err(|e| IcpError::Http(e.to_string()))?;
+        Ok(Self {
+            client,
+            endpoint,
+            headers,
+            fields,
+        })
+    }
+}
+
+impl IcpProvider for HttpIcpProvider {
+    fn lookup_exact(&self, domain: &str) -> Result<Option<IcpRecord>, IcpError> {
+        let url = self.endpoint.replace("{domain}", domain);
+
+        let mut request = self.client.get(&url);
+        for (name, value) in &self.headers {
+            request = request.header(name, value);
+        }
+        let response = request
+            .send()
+            .and_then(|response| response.error_for_status())
+            .map_err(|e| IcpError::Http(e.to_string()))?;
+        let body = response.text().map_err(|e| IcpError::Http(e.to_string()))?;
+        let json: serde_json::Value =
+            serde_json::from_str(&body).map_err(|e| IcpError::Parse(e.to_string()))?;
+
+        Ok(extract_record(&json, &self.fields, domain))
+    }
+}
+
+/// 按字段路径读取 JSON 中的值
+fn json_path<'a>(json: &'a serde_json::Value, path: &str) -> Option<&'a serde_json::Value> {
+    path.split('.')
+        .filter(|key| !key.is_empty())
+        .try_fold(json, |value, key| match key.parse::<usize>() {
+            Ok(index) if value.is_array() => value.get(index),
+            _ => value.get(key),
+        })
+}
+
+/// 从接口返回结果中提取备案信息，备案号为空时视为未备案
+pub fn extract_record(
+    json: &serde_json::Value,
+    fields: &IcpFieldMapping,
+    domain: &str,
+) -> Option<IcpRecord> {
+    let read = |path: &str| match json_path(json, path) {
+        Some(serde_json::Value::String(text)) => text.trim().to_string(),
+        Some(serde_json::Value::Number(number)) => number.to_string(),
+        _ => String::new(),
+    };
+
+    let record = IcpRecord {
+        domain: domain.to_string(),
+        case_number: read(&fields.case_number),
+        entity_name: read(&fields.entity_name),
+        entity_type: read(&fields.entity_type),
+        site_name: read(&fields.site_name),
+    };
+    (!record.case_number.is_empty()).then_some(record)
+}
+
+/// 基于本地数据的备案查询
+pub struct FileIcpProvider {
+    records: HashMap<String, IcpRecord>,
+}
+
+impl FileIcpProvider {
+    /// 从 JSON 文件加载备案信息
+    pub fn load(path: &str) -> Result<Self, IcpError> {
+        let content = std::fs::read_to_string(path)?;
+        let records: Vec<IcpRecord> =
+            serde_json::from_str(&content).map_err(|e| IcpError::Parse(e.to_string()))?;
+        Ok(Self::from_records(records))
+    }
+
+    /// 从内存中的备案信息创建，无效域名的记录会被忽略
+    pub fn from_records(records: Vec<IcpRecord>) -> Self {
+        let records = records
+            .into_iter()
+            .filter_map(|record| Some((normalize_domain(&record.domain).ok()?, record)))
+            .collect();
+        Self { records }
+    }
+}
+
+impl IcpProvider for FileIcpProvider {
+    fn lookup_exact(&self, domain: &str) -> Result<Option<IcpRecord>, IcpError> {
+        Ok(self.records.get(domain).cloned())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_domain() {
+        assert_eq!(
+            normalize_domain("https://User@WWW.Example.com:8443/login?a=1").unwrap(),
+            "www.example.com"
+        );
+        assert_eq!(normalize_domain("example.com.").unwrap(), "example.com");
+        assert!(normalize_domain("localhost").is_err());
+        assert!(normalize_domain("exa mple.com").is_err());
+        assert_eq!(
+            candidate_domains("a.b.example.com.cn"),
+            vec!["a.b.example.com.cn", "b.example.com.cn", "example.com.cn"]
+        );
+        assert_eq!(
+            candidate_domains("www.example.com"),
+            vec!["www.example.com", "example.com"]
+        );
+    }
+
+    #[test]
+    fn test_extract_record() {
+        let json = serde_json::json!({
+            "code": 200,
+            "data": [{
+                "serviceLicence": "京ICP备12345678号-1",
+                "unitName": "示例科技有限公司",
+                "natureName": "企业",
+                "serviceName": "示例网站"
+            }]
+        });
+        let record = extract_record(&json, &IcpFieldMapping::default(), "example.com").unwrap();
+        assert_eq!(record.case_number, "京ICP备12345678号-1");
+        assert_eq!(record.entity_name, "示例科技有限公司");
+        assert_eq!(record.site_name, "示例网站");
+        assert_eq!(record.unit_type(), "民营企业");
+
+        let empty = serde_json::json!({ "code": 200, "data": [] });
+        assert!(extract_record(&empty, &IcpFieldMapping::default(), "example.com").is_none());
+    }
+
+    #[test]
+    fn test_file_provider_lookup() {
+        let provider = FileIcpProvider::from_records(vec![IcpRecord {
+            domain: "example.com".to_string(),
+            case_number: "京ICP备12345678号".to_string(),
+            entity_type: "事业单位".to_string(),
+            ..Default::default()
+        }]);
+
+        let record = provider.lookup("http://www.Example.com/").unwrap().unwrap();
+        assert_eq!(record.case_number, "京ICP备12345678号");
+        assert_eq!(record.unit_type(), "事业单位");
+        assert!(provider.lookup("other.com").unwrap().is_none());
+    }
+}
@@ -1,8 +1,34 @@
-use crate::report::RiskReportData;
+use crate::annotate::Shape;
+use crate::archive::{ArchiveFile, ImportConflict, ImportSummary};
+use crate::backup::{BackupConfig, BackupInfo, BackupManager, BackupReason};
+use crate::capture::CaptureOptions;
+use crate::db::{Database, FieldChange, ReportProject, VaultStatus};
+use crate::diff::{DiffFormat, ProjectDiff, SnapshotSource};
+use crate::dns::{EnrichmentResult, ResolverConfig};
+use crate::geoip::{CityFillOptions, CityProposal};
+use crate::icp::{IcpProviderConfig, IcpRecord};
+use crate::ocr::{OcrOptions, SensitiveRegion};
+use crate::redact::{RedactionPolicy, RedactionSummary};
+use crate::report::{
+    CustomFieldDef, ImageOptimizeOptions, LevelColorConfig, ReportVariant, RiskReportData,
+    ScreenshotContent,
+};
 use tauri::webview::WebviewWindowBuilder;
-use tauri::Manager;
+use tauri::{Emitter, Manager};
 use tauri_plugin_dialog::DialogExt;
 
+pub mod annotate;
+pub mod archive;
+pub mod backup;
+pub mod capture;
+pub mod crypto;
+pub mod db;
+pub mod diff;
+pub mod dns;
+pub mod geoip;
+pub mod icp;
+pub mod ocr;
+pub mod redact;
 pub mod report;
 
 #[derive(Debug, thiserror::Error)]
@@ -25,12 +51,31 @@ impl From<AppError> for String {
     }
 }
 
+/// 解析前端传入的报告，旧版本的数据（如未升级的草稿）按版本升级为当前结构
+fn parse_reports(values: Vec<serde_json::Value>) -> Result<Vec<RiskReportData>, String> {
+    values
+        .into_iter()
+        .map(report::schema::from_value)
+        .collect::<Result<_, _>>()
+        .map_err(|e| format!("报告数据解析失败: {}", e))
+}
+
 #[tauri::command]
 async fn generate(
     project_name: &str,
-    report_list: Vec<RiskReportData>,
+    report_list: Vec<serde_json::Value>,
+    level_colors: Option<LevelColorConfig>,
+    image_options: Option<ImageOptimizeOptions>,
+    redaction: Option<RedactionPolicy>,
+    ocr: Option<OcrOptions>,
+    variant: Option<ReportVariant>,
+    custom_fields: Option<Vec<CustomFieldDef>>,
     app: tauri::AppHandle,
+    database: tauri::State<'_, Database>,
 ) -> Result<String, String> {
+    let mut report_list = parse_reports(report_list)?;
+    let variant = variant.unwrap_or_default();
+    let custom_fields = custom_fields.unwrap_or_default();
     log::info!(
         "开始生成报告，项目名称: {}, 报告数量: {}",
         project_name,
@@ -46,27 +91,78 @@ async fn generate(
         return Err(AppError::EmptyReportList.to_string());
     }
 
+    // 前端只持有已保存图片的引用，生成前读取图片数据
+    database
+        .fill_images(&mut report_list)
+        .map_err(|e| e.to_string())?;
+
     // 验证报告数据完整性
     for (index, report) in report_list.iter().enumerate() {
         if report.report_id.trim().is_empty() {
             return Err(format!("第{}个报告的隐患编号不能为空", index + 1));
         }
+        if let Err(e) = report.check_images() {
+            return Err(format!("第{}个报告的{}", index + 1, e));
+        }
+        if let Err(e) = report.check_custom_fields(&custom_fields) {
+            return Err(format!("第{}个报告的{}", index + 1, e));
+        }
     }
 
     log::info!("报告数据验证通过，开始构建文档");
 
+    // 按脱敏策略处理文本中的敏感信息，指定 OCR 配置时同时模糊截图中的敏感信息
+    // 仅指定 OCR 配置时使用默认脱敏策略，避免 OCR 配置被静默忽略
+    let redaction = redaction.or_else(|| ocr.as_ref().map(|_| RedactionPolicy::default()));
+    let redaction_summary = match redaction {
+        Some(policy) => {
+            let mut summary = redact::redact_reports(&mut report_list, &policy);
+            log::info!("敏感信息脱敏完成，共处理{}处", summary.hits.len());
+            if let Some(options) = ocr {
+                let (reports, result) = tauri::async_runtime::spawn_blocking(move || {
+                    let result = ocr::redact_report_images(&mut report_list, &policy, &options);
+                    (report_list, result)
+                })
+                .await
+                .map_err(|e| e.to_string())?;
+                report_list = reports;
+                summary.image_regions = result.map_err(|e| {
+                    log::error!("截图敏感信息识别失败: {}", e);
+                    e.to_string()
+                })?;
+                log::info!("截图敏感信息模糊完成，共处理{}处", summary.image_regions);
+            }
+            Some(summary)
+        }
+        None => None,
+    };
+
     // 创建DocxBuilder并设置基本配置
-    let mut builder = report::DocxBuilder::new().with_title_font_size(24);
+    let mut builder = report::DocxBuilder::new()
+        .with_title_font_size(24)
+        .with_level_colors(level_colors.unwrap_or_default())
+        .with_image_options(image_options.unwrap_or_default())
+        .with_variant(variant)
+        .with_custom_fields(custom_fields);
 
     // 遍历报告列表，为每个报告添加内容
     for (index, report) in report_list.iter().enumerate() {
         log::debug!("处理第{}个报告: {}", index + 1, report.report_name);
 
-        // 添加报告标题：【隐患类型】隐患名称 【隐患级别】
-        let title = format!(
-            "【{}】{} 【{}】",
-            report.hazard_type, report.report_name, report.hazard_level
-        );
+        // 添加报告标题：【隐患类型】隐患名称 【隐患级别】，复测报告同时显示修复状态 【隐患级别｜修复状态】
+        let title = match variant {
+            ReportVariant::Original => format!(
+                "【{}】{} 【{}】",
+                report.hazard_type, report.report_name, report.hazard_level
+            ),
+            ReportVariant::Retest => format!(
+                "【{}】{} 【{}｜{}】",
+                report.hazard_type,
+                report.report_name,
+                report.hazard_level,
+                report.remediation_status.label()
+            ),
+        };
         builder = builder.add_title(title);
 
         // 添加风险表格
@@ -80,9 +176,12 @@ async fn generate(
 
     log::info!("文档构建完成，开始保存文件");
 
-    // 生成安全的文件名：项目名称_风险隐患报告.docx
+    // 生成安全的文件名：项目名称_风险隐患报告.docx 或 项目名称_风险隐患复测报告.docx
     let safe_project_name = sanitize_filename(project_name);
-    let file_name = format!("{}_风险隐患报告.docx", safe_project_name);
+    let file_name = match variant {
+        ReportVariant::Original => format!("{}_风险隐患报告.docx", safe_project_name),
+        ReportVariant::Retest => format!("{}_风险隐患复测报告.docx", safe_project_name),
+    };
 
     // 显示保存对话框
     let file_path = app
@@ -97,9 +196,17 @@ async fn generate(
             log::info!("用户选择保存路径: {}", path_str);
 
             match builder.save_to_file(&path_str) {
-                Ok(_) => {
+                Ok(image_stats) => {
                     log::info!("报告生成成功: {}", path_str);
-                    Ok(format!("成功生成报告：{}", path_str))
+                    log::info!("图片预处理: {}", image_stats.summary());
+                    let mut message = format!("成功生成报告：{}", path_str);
+                    if image_stats.optimized > 0 {
+                        message = format!("{}\n{}", message, image_stats.summary());
+                    }
+                    if let Some(summary) = redaction_summary.filter(|s| !s.is_empty()) {
+                        message = format!("{}\n{}", message, summary.summary());
+                    }
+                    Ok(message)
                 }
                 Err(e) => {
                     log::error!("保存文件失败: {}", e);
@@ -135,6 +242,716 @@ async fn open_icp_query_window(handle: tauri::AppHandle) -> Result<(), String> {
     Ok(())
 }
 
+/// 截取备案查询窗口，追加到指定报告（按内部ID查找）的备案截图中，返回更新后的报告
+/// selector 为 CSS 选择器，指定时裁剪到对应元素（如查询结果表格）
+#[tauri::command]
+async fn capture_icp_screenshot(
+    project_name: String,
+    id: String,
+    selector: Option<String>,
+    author: Option<String>,
+    app: tauri::AppHandle,
+    database: tauri::State<'_, Database>,
+) -> Result<RiskReportData, String> {
+    let window = app
+        .get_webview_window("beian")
+        .ok_or_else(|| "备案查询窗口未打开".to_string())?;
+
+    let data = capture::capture_window(&window, selector.as_deref(), 30)
+        .await
+        .map_err(|e| {
+            log::error!("备案截图失败: {}", e);
+            e.to_string()
+        })?;
+
+    let mut report = database
+        .load_report(&project_name, &id)
+        .map_err(|e| e.to_string())?;
+    report
+        .filing_screenshots
+        .push(ScreenshotContent::Image(report::ScreenshotImage {
+            data,
+            ..Default::default()
+        }));
+
+    database
+        .save_report(
+            &project_name,
+            &report,
+            author.as_deref().unwrap_or_default(),
+        )
+        .map_err(|e| AppError::FileSave(e.to_string()).to_string())?;
+    log::info!("备案截图已追加到报告: {}", report.report_id);
+    Ok(report)
+}
+
+/// 在后台打开目标网页并截取整页图片，返回可直接追加到证明截图的内容
+#[tauri::command]
+async fn capture_webpage(
+    target: String,
+    options: Option<CaptureOptions>,
+    app: tauri::AppHandle,
+) -> Result<ScreenshotContent, String> {
+    log::info!("开始截取网页: {}", target);
+    capture::capture_webpage(&app, &target, &options.unwrap_or_default())
+        .await
+        .map_err(|e| {
+            log::error!("网页截图失败: {}", e);
+            e.to_string()
+        })
+}
+
+/// 当前平台是否支持网页截图，前端据此隐藏报告表单中的网页截图按钮，macOS 暂不支持
+#[tauri::command]
+fn capture_supported() -> bool {
+    capture::SUPPORTED
+}
+
+/// 查询域名的备案信息，未备案时返回 None
+#[tauri::command]
+async fn lookup_icp(
+    domain: String,
+    provider: IcpProviderConfig,
+) -> Result<Option<IcpRecord>, String> {
+    tauri::async_runtime::spawn_blocking(move || provider.build()?.lookup(&domain))
+        .await
+        .map_err(|e| e.to_string())?
+        .map_err(|e| {
+            log::error!("备案查询失败: {}", e);
+            e.to_string()
+        })
+}
+
+/// 按报告中的域名批量查询备案信息，填充备案号、单位名称、单位类型和网站名称
+#[tauri::command]
+async fn fill_icp_info(
+    report_list: Vec<serde_json::Value>,
+    provider: IcpProviderConfig,
+) -> Result<Vec<RiskReportData>, String> {
+    let mut report_list = parse_reports(report_list)?;
+    tauri::async_runtime::spawn_blocking(move || {
+        let provider = provider.build().map_err(|e| e.to_string())?;
+        for report in report_list.iter_mut() {
+            if report.domain.trim().is_empty() {
+                continue;
+            }
+            match provider.lookup(&report.domain) {
+                Ok(Some(record)) => record.apply_to(report),
+                Ok(None) => log::info!("域名未查询到备案信息: {}", report.domain),
+                Err(e) => log::warn!("域名备案查询失败: {}: {}", report.domain, e),
+            }
+        }
+        Ok(report_list)
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// 解析报告目标补全域名与IP，返回更新后的报告列表及每个报告的解析结果
+#[tauri::command]
+async fn enrich_reports(
+    report_list: Vec<serde_json::Value>,
+    resolver: Option<ResolverConfig>,
+) -> Result<(Vec<RiskReportData>, Vec<EnrichmentResult>), String> {
+    let mut report_list = parse_reports(report_list)?;
+    tauri::async_runtime::spawn_blocking(move || {
+        let resolver = resolver
+            .unwrap_or_default()
+            .build()
+            .map_err(|e| e.to_string())?;
+        let results: Vec<EnrichmentResult> = report_list
+            .iter_mut()
+            .map(|report| dns::enrich_report(report, resolver.as_ref()))
+            .collect();
+        let mismatches = results.iter().filter(|r| r.mismatch).count();
+        log::info!(
+            "域名解析补全完成，报告数量: {}, IP不一致: {}",
+            results.len(),
+            mismatches
+        );
+        Ok((report_list, results))
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// 使用离线 IP 归属地数据库批量补全归属地市，返回更新后的报告列表及归属地建议
+#[tauri::command]
+async fn fill_cities(
+    report_list: Vec<serde_json::Value>,
+    options: CityFillOptions,
+) -> Result<(Vec<RiskReportData>, Vec<CityProposal>), String> {
+    let mut report_list = parse_reports(report_list)?;
+    tauri::async_runtime::spawn_blocking(move || {
+        let database = geoip::Ip2Region::load(&options.database_path).map_err(|e| {
+            log::error!("加载IP归属地数据库失败: {}", e);
+            e.to_string()
+        })?;
+        let proposals = geoip::fill_cities(&mut report_list, &database, &options);
+        log::info!(
+            "归属地市补全完成，建议: {}, 已写入: {}",
+            proposals.len(),
+            proposals.iter().filter(|p| p.applied).count()
+        );
+        Ok((report_list, proposals))
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// 在截图上绘制标注（矩形框、箭头、文字、遮挡、模糊），返回PNG图片数据
+#[tauri::command]
+async fn annotate_image(image: Vec<u8>, shapes: Vec<Shape>) -> Result<Vec<u8>, String> {
+    log::info!("开始标注图片，标注数量: {}", shapes.len());
+    // 解码、绘制与PNG编码较耗时，在阻塞线程池中执行
+    tauri::async_runtime::spawn_blocking(move || annotate::annotate_image(&image, &shapes))
+        .await
+        .map_err(|e| e.to_string())?
+        .map_err(|e| {
+            log::error!("图片标注失败: {}", e);
+            e.to_string()
+        })
+}
+
+/// 离线识别截图中的敏感信息，返回建议的模糊区域
+#[tauri::command]
+async fn detect_sensitive_regions(
+    image: Vec<u8>,
+    policy: Option<RedactionPolicy>,
+    options: Option<OcrOptions>,
+) -> Result<Vec<SensitiveRegion>, String> {
+    // Tesseract 子进程会阻塞线程，在阻塞线程池中执行
+    let regions = tauri::async_runtime::spawn_blocking(move || {
+        ocr::detect_sensitive_regions(
+            &image,
+            &policy.unwrap_or_default(),
+            &options.unwrap_or_default(),
+        )
+    })
+    .await
+    .map_err(|e| e.to_string())?
+    .map_err(|e| {
+        log::error!("截图敏感信息识别失败: {}", e);
+        e.to_string()
+    })?;
+    log::info!("截图敏感信息识别完成，发现{}处", regions.len());
+    Ok(regions)
+}
+
+/// 预览脱敏结果，返回脱敏后的报告列表及脱敏记录
+#[tauri::command]
+async fn redact_reports(
+    report_list: Vec<serde_json::Value>,
+    policy: Option<RedactionPolicy>,
+) -> Result<(Vec<RiskReportData>, RedactionSummary), String> {
+    let mut report_list = parse_reports(report_list)?;
+    let summary = redact::redact_reports(&mut report_list, &policy.unwrap_or_default());
+    log::info!("敏感信息脱敏预览，共处理{}处", summary.hits.len());
+    Ok((report_list, summary))
+}
+
+/// 加载所有项目，图片只包含引用，通过 load_image 按需读取
+#[tauri::command]
+async fn load_projects(database: tauri::State<'_, Database>) -> Result<Vec<ReportProject>, String> {
+    database.load_projects().map_err(|e| e.to_string())
+}
+
+/// 读取单张已保存的图片，以二进制形式返回，避免序列化为数字数组
+#[tauri::command]
+async fn load_image(
+    hash: String,
+    database: tauri::State<'_, Database>,
+) -> Result<tauri::ipc::Response, String> {
+    database
+        .load_image(&hash)
+        .map(tauri::ipc::Response::new)
+        .map_err(|e| e.to_string())
+}
+
+/// 保存整个项目，项目不存在时创建，author 为修改人（应用设置中的用户名）
+#[tauri::command]
+async fn save_project(
+    project: serde_json::Value,
+    author: Option<String>,
+    database: tauri::State<'_, Database>,
+) -> Result<(), String> {
+    let project =
+        ReportProject::from_json(project).map_err(|e| format!("项目数据解析失败: {}", e))?;
+    let author = author.unwrap_or_default();
+    database.save_project(&project, &author).map_err(|e| {
+        log::error!("保存项目失败: {}", e);
+        e.to_string()
+    })
+}
+
+/// 删除项目及其全部报告
+#[tauri::command]
+async fn delete_project(
+    project_name: String,
+    database: tauri::State<'_, Database>,
+    backups: tauri::State<'_, BackupManager>,
+) -> Result<(), String> {
+    backup_before(&backups, &database, BackupReason::BeforeDelete)?;
+    database
+        .delete_project(&project_name)
+        .map_err(|e| e.to_string())
+}
+
+/// 重命名项目
+#[tauri::command]
+async fn rename_project(
+    old_name: String,
+    new_name: String,
+    database: tauri::State<'_, Database>,
+) -> Result<(), String> {
+    database
+        .rename_project(&old_name, &new_name)
+        .map_err(|e| e.to_string())
+}
+
+/// 新增或更新单个报告，author 为修改人（应用设置中的用户名）
+#[tauri::command]
+async fn save_report(
+    project_name: String,
+    report: serde_json::Value,
+    author: Option<String>,
+    database: tauri::State<'_, Database>,
+) -> Result<(), String> {
+    let report =
+        report::schema::from_value(report).map_err(|e| format!("报告数据解析失败: {}", e))?;
+    database
+        .save_report(
+            &project_name,
+            &report,
+            author.as_deref().unwrap_or_default(),
+        )
+        .map_err(|e| {
+            log::error!("保存报告失败: {}", e);
+            e.to_string()
+        })
+}
+
+/// 报告的字段修改记录，最新的在前
+#[tauri::command]
+async fn report_history(
+    project_name: String,
+    id: String,
+    database: tauri::State<'_, Database>,
+) -> Result<Vec<FieldChange>, String> {
+    database
+        .report_history(&project_name, &id)
+        .map_err(|e| e.to_string())
+}
+
+/// 将报告字段回退到某次修改前的值，返回回退后的报告
+#[tauri::command]
+async fn revert_report_field(
+    project_name: String,
+    id: String,
+    change_id: i64,
+    author: Option<String>,
+    database: tauri::State<'_, Database>,
+) -> Result<RiskReportData, String> {
+    database
+        .revert_field(
+            &project_name,
+            &id,
+            change_id,
+            author.as_deref().unwrap_or_default(),
+        )
+        .map_err(|e| {
+            log::error!("回退报告字段失败: {}", e);
+            e.to_string()
+        })
+}
+
+/// 删除单个报告
+#[tauri::command]
+async fn delete_report(
+    project_name: String,
+    id: String,
+    database: tauri::State<'_, Database>,
+) -> Result<(), String> {
+    database
+        .delete_report(&project_name, &id)
+        .map_err(|e| e.to_string())
+}
+
+/// 设置项目声明的自定义字段
+#[tauri::command]
+async fn set_custom_fields(
+    project_name: String,
+    fields: Vec<CustomFieldDef>,
+    database: tauri::State<'_, Database>,
+) -> Result<(), String> {
+    database
+        .set_custom_fields(&project_name, &fields)
+        .map_err(|e| e.to_string())
+}
+
+/// 导出项目归档（.rgproj），包含全部报告及截图，用于分享和备份
+#[tauri::command]
+async fn export_project(
+    project_name: String,
+    password: Option<String>,
+    app: tauri::AppHandle,
+    database: tauri::State<'_, Database>,
+) -> Result<String, String> {
+    let project = database
+        .load_project(&project_name)
+        .map_err(|e| e.to_string())?;
+
+    let file_name = format!(
+        "{}.{}",
+        sanitize_filename(&project_name),
+        archive::ARCHIVE_EXTENSION
+    );
+    let path = app
+        .dialog()
+        .file()
+        .set_file_name(file_name)
+        .add_filter("项目归档", &[archive::ARCHIVE_EXTENSION])
+        .blocking_save_file()
+        .ok_or_else(|| AppError::UserCancelled.to_string())?
+        .to_string();
+
+    let data = archive::export_to_bytes(&project, password.as_deref()).map_err(|e| {
+        log::error!("导出项目归档失败: {}", e);
+        AppError::FileSave(e.to_string()).to_string()
+    })?;
+    std::fs::write(&path, data).map_err(|e| AppError::FileSave(e.to_string()).to_string())?;
+    log::info!("项目归档导出成功: {}", path);
+    Ok(format!("成功导出项目：{}", path))
+}
+
+/// 选择要导入的项目归档，返回路径及是否需要密码，用户取消时返回 None
+#[tauri::command]
+async fn select_project_archive(app: tauri::AppHandle) -> Result<Option<ArchiveFile>, String> {
+    let Some(path) = app
+        .dialog()
+        .file()
+        .add_filter("项目归档", &[archive::ARCHIVE_EXTENSION])
+        .blocking_pick_file()
+    else {
+        return Ok(None);
+    };
+    let path = path.to_string();
+
+    let mut header = [0u8; 16];
+    let length = std::fs::File::open(&path)
+        .and_then(|mut file| std::io::Read::read(&mut file, &mut header))
+        .map_err(|e| AppError::Io(e).to_string())?;
+    Ok(Some(ArchiveFile {
+        encrypted: crypto::is_sealed(&header[..length]),
+        path,
+    }))
+}
+
+/// 导入项目归档，同名项目按 conflict 处理（默认以新名称导入），
+/// 覆盖或合并已有报告时以 author 记录修改历史
+#[tauri::command]
+async fn import_project(
+    path: String,
+    password: Option<String>,
+    conflict: Option<ImportConflict>,
+    author: Option<String>,
+    database: tauri::State<'_, Database>,
+    backups: tauri::State<'_, BackupManager>,
+) -> Result<ImportSummary, String> {
+    let data = std::fs::read(&path).map_err(|e| AppError::Io(e).to_string())?;
+    let (manifest, project) =
+        archive::import_from_bytes(data, password.as_deref()).map_err(|e| {
+            log::error!("读取项目归档失败: {}", e);
+            e.to_string()
+        })?;
+    log::info!(
+        "读取项目归档: {}, 版本: {}, 报告数量: {}",
+        manifest.project_name,
+        manifest.version,
+        manifest.report_count
+    );
+
+    let names = database.project_names().map_err(|e| e.to_string())?;
+    let current = if names.contains(&project.project_name) {
+        Some(
+            database
+                .load_project(&project.project_name)
+                .map_err(|e| e.to_string())?,
+        )
+    } else {
+        None
+    };
+    let (project, summary) =
+        archive::resolve_conflict(project, &names, current, conflict.unwrap_or_default());
+    backup_before(&backups, &database, BackupReason::BeforeImport)?;
+    let author = author.unwrap_or_default();
+    database.save_project(&project, &author).map_err(|e| {
+        log::error!("保存导入的项目失败: {}", e);
+        e.to_string()
+    })?;
+    log::info!(
+        "项目归档导入成功: {}, 导入报告: {}, 跳过: {}",
+        summary.project_name,
+        summary.imported,
+        summary.skipped
+    );
+    Ok(summary)
+}
+
+/// 对比两个项目快照，返回新增、删除和修改的报告及字段差异
+#[tauri::command]
+async fn diff_projects(
+    old: SnapshotSource,
+    new: SnapshotSource,
+    database: tauri::State<'_, Database>,
+) -> Result<ProjectDiff, String> {
+    let old = old.load(&database).map_err(|e| e.to_string())?;
+    let new = new.load(&database).map_err(|e| e.to_string())?;
+    diff::diff_projects(&old, &new).map_err(|e| e.to_string())
+}
+
+/// 导出两个项目快照的变更记录（Markdown 或 HTML）
+#[tauri::command]
+async fn export_project_diff(
+    old: SnapshotSource,
+    new: SnapshotSource,
+    format: Option<DiffFormat>,
+    app: tauri::AppHandle,
+    database: tauri::State<'_, Database>,
+) -> Result<String, String> {
+    let old = old.load(&database).map_err(|e| e.to_string())?;
+    let new = new.load(&database).map_err(|e| e.to_string())?;
+    let diff = diff::diff_projects(&old, &new).map_err(|e| e.to_string())?;
+
+    let format = format.unwrap_or_default();
+    let file_name = format!(
+        "{}_变更记录.{}",
+        sanitize_filename(&new.project_name),
+        format.extension()
+    );
+    let path = app
+        .dialog()
+        .file()
+        .set_file_name(file_name)
+        .add_filter("变更记录", &[format.extension()])
+        .blocking_save_file()
+        .ok_or_else(|| AppError::UserCancelled.to_string())?
+        .to_string();
+    std::fs::write(&path, diff.render(format))
+        .map_err(|e| AppError::FileSave(e.to_string()).to_string())?;
+    log::info!("变更记录导出成功: {}", path);
+    Ok(format!("成功导出变更记录：{}", path))
+}
+
+/// 项目数据加密状态
+#[tauri::command]
+fn vault_status(database: tauri::State<'_, Database>) -> VaultStatus {
+    database.vault_status()
+}
+
+/// 在后台线程中执行需要派生密钥的数据库操作，避免阻塞界面
+async fn run_vault_task<F>(app: tauri::AppHandle, task: F) -> Result<(), String>
+where
+    F: FnOnce(&Database) -> Result<(), db::DbError> + Send + 'static,
+{
+    tauri::async_runtime::spawn_blocking(move || task(&app.state::<Database>()))
+        .await
+        .map_err(|e| e.to_string())?
+        .map_err(|e| e.to_string())
+}
+
+/// 使用密码解锁项目数据
+#[tauri::command]
+async fn unlock_vault(password: String, app: tauri::AppHandle) -> Result<(), String> {
+    run_vault_task(app, move |database| database.unlock(&password)).await?;
+    log::info!("项目数据已解锁");
+    Ok(())
+}
+
+/// 立即锁定项目数据
+#[tauri::command]
+fn lock_vault(database: tauri::State<'_, Database>) {
+    database.lock();
+    log::info!("项目数据已锁定");
+}
+
+/// 启用加密或修改密码后删除旧备份，旧备份中的明文或旧密码加密的数据不再保留
+fn remove_stale_backups(app: &tauri::AppHandle) -> Result<(), String> {
+    app.state::<BackupManager>()
+        .remove_all()
+        .map(|_| ())
+        .map_err(|e| {
+            log::error!("删除旧备份失败: {}", e);
+            format!("删除旧备份失败，请在备份目录中手动删除: {}", e)
+        })
+}
+
+/// 启用项目数据加密，完成后删除旧备份，并按保留策略删除旧版明文存储文件
+#[tauri::command]
+async fn enable_encryption(
+    password: String,
+    auto_lock_minutes: u32,
+    app: tauri::AppHandle,
+) -> Result<(), String> {
+    run_vault_task(app.clone(), move |database| {
+        database.enable_encryption(&password, auto_lock_minutes)
+    })
+    .await?;
+    log::info!("项目数据加密已启用");
+
+    let data_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    if let Err(e) = db::remove_legacy_store(&data_dir) {
+        log::warn!("删除旧版明文存储失败: {}", e);
+    }
+    remove_stale_backups(&app)
+}
+
+/// 关闭项目数据加密
+#[tauri::command]
+async fn disable_encryption(password: String, app: tauri::AppHandle) -> Result<(), String> {
+    run_vault_task(app, move |database| database.disable_encryption(&password)).await?;
+    log::info!("项目数据加密已关闭");
+    Ok(())
+}
+
+/// 修改项目数据加密密码，完成后删除使用旧密码加密的备份
+#[tauri::command]
+async fn change_password(
+    old_password: String,
+    new_password: String,
+    app: tauri::AppHandle,
+) -> Result<(), String> {
+    run_vault_task(app.clone(), move |database| {
+        database.change_password(&old_password, &new_password)
+    })
+    .await?;
+    log::info!("项目数据加密密码已修改");
+    remove_stale_backups(&app)
+}
+
+/// 设置无操作自动锁定的分钟数，0 表示不自动锁定
+#[tauri::command]
+fn set_auto_lock(minutes: u32, database: tauri::State<'_, Database>) -> Result<(), String> {
+    database.set_auto_lock(minutes).map_err(|e| e.to_string())
+}
+
+/// 定期检查是否需要自动锁定，锁定后通知前端
+fn spawn_auto_lock(app: tauri::AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        let mut interval = tokio::time::interval(std::time::Duration::from_secs(30));
+        loop {
+            interval.tick().await;
+            if app.state::<Database>().lock_if_idle() {
+                log::info!("长时间无操作，项目数据已自动锁定");
+                if let Err(e) = app.emit("vault-locked", ()) {
+                    log::warn!("发送锁定通知失败: {}", e);
+                }
+            }
+        }
+    });
+}
+
+/// 在修改数据前创建备份，备份失败时取消操作
+fn backup_before(
+    backups: &BackupManager,
+    database: &Database,
+    reason: BackupReason,
+) -> Result<(), String> {
+    backups.create(database, reason).map(|_| ()).map_err(|e| {
+        log::error!("创建备份失败: {}", e);
+        format!("创建备份失败，操作已取消: {}", e)
+    })
+}
+
+/// 列出所有备份，最新的在前
+#[tauri::command]
+fn list_backups(backups: tauri::State<'_, BackupManager>) -> Result<Vec<BackupInfo>, String> {
+    backups.list().map_err(|e| e.to_string())
+}
+
+/// 立即备份项目数据
+#[tauri::command]
+async fn create_backup(
+    database: tauri::State<'_, Database>,
+    backups: tauri::State<'_, BackupManager>,
+) -> Result<BackupInfo, String> {
+    backups
+        .create(&database, BackupReason::Manual)
+        .map_err(|e| e.to_string())
+}
+
+/// 从备份恢复项目数据，恢复前的数据会自动备份。
+/// 备份的加密设置与当前不同时，需要用户确认后传入 allow_encryption_change
+#[tauri::command]
+async fn restore_backup(
+    id: String,
+    allow_encryption_change: Option<bool>,
+    database: tauri::State<'_, Database>,
+    backups: tauri::State<'_, BackupManager>,
+) -> Result<BackupInfo, String> {
+    let allow = allow_encryption_change.unwrap_or(false);
+    backups.restore(&database, &id, allow).map_err(|e| {
+        log::error!("恢复备份失败: {}", e);
+        e.to_string()
+    })
+}
+
+/// 当前备份配置
+#[tauri::command]
+fn backup_config(backups: tauri::State<'_, BackupManager>) -> BackupConfig {
+    backups.config()
+}
+
+/// 保存备份配置
+#[tauri::command]
+fn set_backup_config(
+    config: BackupConfig,
+    backups: tauri::State<'_, BackupManager>,
+) -> Result<(), String> {
+    backups.set_config(config).map_err(|e| e.to_string())
+}
+
+/// 定期检查是否需要定时备份
+fn spawn_scheduled_backup(app: tauri::AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        let mut interval = tokio::time::interval(std::time::Duration::from_secs(60));
+        loop {
+            interval.tick().await;
+            let app = app.clone();
+            let result = tauri::async_runtime::spawn_blocking(move || {
+                app.state::<BackupManager>()
+                    .run_scheduled(&app.state::<Database>())
+            })
+            .await;
+            match result {
+                Ok(Err(e)) => log::warn!("定时备份失败: {}", e),
+                Err(e) => log::warn!("定时备份任务异常: {}", e),
+                Ok(Ok(_)) => {}
+            }
+        }
+    });
+}
+
+/// 打开应用数据目录下的数据库，并导入旧版存储中的项目
+fn open_database(app: &tauri::App) -> Result<Database, Box<dyn std::error::Error>> {
+    let data_dir = app.path().app_data_dir()?;
+    std::fs::create_dir_all(&data_dir)?;
+    let database = Database::open(&data_dir.join(db::DATABASE_FILE))?;
+
+    match database.migrate_from_store(&data_dir) {
+        Ok(Some(summary)) => log::info!(
+            "已从旧版存储导入项目: {}, 报告: {}, 跳过: {:?}",
+            summary.projects,
+            summary.reports,
+            summary.skipped
+        ),
+        Ok(None) => {}
+        // 导入失败不影响启动，下次启动时重试
+        Err(e) => log::error!("导入旧版项目存储失败: {}", e),
+    }
+    Ok(database)
+}
+
 /// 清理文件名中的非法字符
 fn sanitize_filename(filename: &str) -> String {
     // 移除或替换文件名中的非法字符
@@ -173,8 +990,54 @@ pub fn run() {
         .plugin(tauri_plugin_fs::init())
         .plugin(tauri_plugin_store::Builder::new().build())
         .plugin(tauri_plugin_persisted_scope::init())
-        .invoke_handler(tauri::generate_handler![generate, open_icp_query_window])
-        .setup(|_app| {
+        .invoke_handler(tauri::generate_handler![
+            generate,
+            open_icp_query_window,
+            capture_supported,
+            capture_webpage,
+            capture_icp_screenshot,
+            lookup_icp,
+            fill_icp_info,
+            enrich_reports,
+            fill_cities,
+            annotate_image,
+            redact_reports,
+            detect_sensitive_regions,
+            load_projects,
+            load_image,
+            save_project,
+            delete_project,
+            rename_project,
+            save_report,
+            report_history,
+            revert_report_field,
+            delete_report,
+            set_custom_fields,
+            export_project,
+            select_project_archive,
+            import_project,
+            diff_projects,
+            export_project_diff,
+            vault_status,
+            unlock_vault,
+            lock_vault,
+            enable_encryption,
+            disable_encryption,
+            change_password,
+            set_auto_lock,
+            list_backups,
+            create_backup,
+            restore_backup,
+            backup_config,
+            set_backup_config
+        ])
+        .setup(|app| {
+            app.manage(open_database(app)?);
+            app.manage(BackupManager::open(
+                app.path().app_data_dir()?.join(backup::BACKUP_DIR),
+            )?);
+            spawn_scheduled_backup(app.handle().clone());
+            spawn_auto_lock(app.handle().clone());
             log::info!("应用程序设置完成");
             Ok(())
         })
@@ -0,0 +1,332 @@
+use crate::annotate::{self, AnnotateError, Shape};
+use crate::redact::{self, RedactionPolicy, SensitiveKind};
+use crate::report::{content_hash, RiskReportData, ScreenshotContent};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+/// OCR 识别相关的错误类型
+#[derive(Debug, thiserror::Error)]
+pub enum OcrError {
+    #[error("未找到 OCR 引擎 {0}，请安装 Tesseract 或在设置中指定其路径")]
+    EngineNotFound(String),
+    #[error("OCR 识别失败: {0}")]
+    Recognition(String),
+    #[error("截图模糊处理失败: {0}")]
+    Annotate(#[from] AnnotateError),
+    #[error("IO错误: {0}")]
+    Io(#[from] std::io::Error),
+}
+
+/// OCR 配置，使用本地 Tesseract 引擎离线识别
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct OcrOptions {
+    pub tesseract_path: Option<String>, // Tesseract 可执行文件路径，为空时从 PATH 查找
+    pub languages: String,              // 识别语言，如 "chi_sim+eng"
+    pub padding: u32,                   // 模糊区域向外扩展的像素数
+}
+
+impl Default for OcrOptions {
+    fn default() -> Self {
+        Self {
+            tesseract_path: None,
+            languages: "chi_sim+eng".to_string(),
+            padding: 4,
+        }
+    }
+}
+
+/// OCR 识别出的单词及其位置
+#[derive(Debug, Clone, PartialEq)]
+pub struct OcrWord {
+    pub text: String,
+    pub line: (u32, u32, u32), // (块, 段落, 行) 编号，用于按行拼接
+    pub left: i32,
+    pub top: i32,
+    pub width: u32,
+    pub height: u32,
+}
+
+/// 截图中检测到的敏感区域，坐标与尺寸以原图像素为单位，可作为 blur 标注传给 annotate_image
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct SensitiveRegion {
+    pub kind: SensitiveKind,
+    pub text: String, // 脱敏后的识别文本，便于用户确认
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
+}
+
+/// 调用 Tesseract 识别图片中的文字，返回单词级别的结果
+pub fn recognize(image_data: &[u8], options: &OcrOptions) -> Result<Vec<OcrWord>, OcrError> {
+    let program = options.tesseract_path.as_deref().unwrap_or("tesseract");
+    let mut child = Command::new(program)
+        .args(["stdin", "stdout", "-l", &options.languages, "tsv"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| match e.kind() {
+            std::io::ErrorKind::NotFound => OcrError::EngineNotFound(program.to_string()),
+            _ => OcrError::Io(e),
+        })?;
+
+    // 在独立线程中写入图片，避免输出缓冲区写满导致死锁
+    let mut stdin = child.stdin.take().expect("stdin is piped");
+    let data = image_data.to_vec();
+    let writer = std::thread::spawn(move || stdin.write_all(&data));
+
+    let output = child.wait_with_output()?;
+    writer
+        .join()
+        .map_err(|_| OcrError::Recognition("写入图片数据失败".to_string()))??;
+
+    if !output.status.success() {
+        return Err(OcrError::Recognition(
+            String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        ));
+    }
+
+    Ok(parse_tsv(&String::from_utf8_lossy(&output.stdout)))
+}
+
+/// 解析 Tesseract TSV 输出，只保留单词级别（level 5）且非空的结果
+pub fn parse_tsv(tsv: &str) -> Vec<OcrWord> {
+    tsv.lines()
+        .skip(1)
+        .filter_map(|line| {
+            let columns: Vec<&str> = line.split('\t').collect();
+            if columns.len() < 12 || columns[0] != "5" {
+                return None;
+            }
+            let text = columns[11].trim();
+            if text.is_empty() {
+                return None;
+            }
+            let number = |index: usize| columns[index].parse::<u32>().ok();
+            Some(OcrWord {
+                text: text.to_string(),
+                line: (number(2)?, number(3)?, number(4)?),
+                left: columns[6].parse().ok()?,
+                top: columns[7].parse().ok()?,
+                width: number(8)?,
+                height: number(9)?,
+            })
+        })
+        .collect()
+}
+
+/// 按行拼接识别结果并匹配敏感内容，返回需要模糊的区域
+pub fn find_sensitive_regions(
+    words: &[OcrWord],
+    policy: &RedactionPolicy,
+    padding: u32,
+) -> Vec<SensitiveRegion> {
+    let mut regions = Vec::new();
+    let mut start = 0;
+
+    while start < words.len() {
+        let end = words[start..]
+            .iter()
+            .position(|word| word.line != words[start].line)
+            .map_or(words.len(), |offset| start + offset);
+        let line = &words[start..end];
+        start = end;
+
+        // 拼接整行文本并记录每个单词的字节范围
+        let mut text = String::new();
+        let mut spans = Vec::with_capacity(line.len());
+        for word in line {
+            if !text.is_empty() {
+                text.push(' ');
+            }
+            spans.push((text.len(), text.len() + word.text.len()));
+            text.push_str(&word.text);
+        }
+
+        for found in redact::find_sensitive(&text, policy) {
+            let covered: Vec<&OcrWord> = line
+                .iter()
+                .zip(&spans)
+                .filter(|(_, (s, e))| *s < found.end && found.start < *e)
+                .map(|(word, _)| word)
+                .collect();
+            if covered.is_empty() {
+                continue;
+            }
+
+            let pad = padding as i32;
+            let left = covered.iter().map(|w| w.left).min().unwrap_or(0) - pad;
+            let top = covered.iter().map(|w| w.top).min().unwrap_or(0) - pad;
+            let right = covered
+                .iter()
+                .map(|w| w.left + w.width as i32)
+                .max()
+                .unwrap_or(0)
+                + pad;
+            let bottom = covered
+                .iter()
+                .map(|w| w.top + w.height as i32)
+                .max()
+                .unwrap_or(0)
+                + pad;
+
+            regions.push(SensitiveRegion {
+                kind: found.kind,
+                text: redact::mask(found.kind, &text[found.start..found.end]),
+                x: left.max(0),
+                y: top.max(0),
+                width: (right - left.max(0)).max(0) as u32,
+                height: (bottom - top.max(0)).max(0) as u32,
+            });
+        }
+    }
+
+    regions
+}
+
+/// 识别截图中的敏感信息，返回建议的模糊区域
+pub fn detect_sensitive_regions(
+    image_data: &[u8],
+    policy: &RedactionPolicy,
+    options: &OcrOptions,
+) -> Result<Vec<SensitiveRegion>, OcrError> {
+    let words = recognize(image_data, options)?;
+    Ok(find_sensitive_regions(&words, policy, options.padding))
+}
+
+/// 识别报告截图中的敏感信息并模糊处理，相同内容的图片只识别一次，返回模糊的区域数。
+/// 调用 Tesseract 子进程会阻塞当前线程，异步命令中需在阻塞线程池执行
+pub fn redact_report_images(
+    reports: &mut [RiskReportData],
+    policy: &RedactionPolicy,
+    options: &OcrOptions,
+) -> Result<usize, OcrError> {
+    let mut redacted: HashMap<String, Option<Vec<u8>>> = HashMap::new();
+    let mut regions_count = 0;
+
+    for report in reports.iter_mut() {
+        for (_, contents) in report.screenshot_groups_mut() {
+            for content in contents.iter_mut() {
+                let ScreenshotContent::Image(image) = content else {
+                    continue;
+                };
+                let hash = content_hash(&image.data);
+                if !redacted.contains_key(&hash) {
+                    let regions = detect_sensitive_regions(&image.data, policy, options)?;
+                    regions_count += regions.len();
+                    let shapes: Vec<Shape> = regions
+                        .iter()
+                        .map(|region| Shape::Blur {
+                            x: region.x,
+                            y: region.y,
+                            width: region.width,
+                            height: region.height,
+                            sigma: 8.0,
+                        })
+                        .collect();
+                    let data = if shapes.is_empty() {
+                        None
+                    } else {
+                        Some(annotate::annotate_image(&image.data, &shapes)?)
+                    };
+                    redacted.insert(hash.clone(), data);
+                }
+                if let Some(Some(data)) = redacted.get(&hash) {
+                    image.data = data.clone();
+                }
+            }
+        }
+    }
+
+    Ok(regions_count)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TSV: &str = "level\tpage_num\tblock_num\tpar_num\tline_num\tword_num\tleft\ttop\twidth\theight\tconf\ttext
+1\t1\t0\t0\t0\t0\t0\t0\t800\t600\t-1\t
+4\t1\t1\t1\t1\t0\t10\t20\t400\t30\t-1\t
+5\t1\t1\t1\t1\t1\t10\t20\t80\t30\t96.1\t手机号:
+5\t1\t1\t1\t1\t2\t100\t22\t150\t28\t95.3\t13812345678
+5\t1\t1\t1\t2\t1\t10\t60\t90\t30\t91.0\tCookie:
+5\t1\t1\t1\t2\t2\t110\t60\t120\t30\t90.2\tsid=abc;
+5\t1\t1\t1\t2\t3\t240\t60\t100\t30\t90.2\tuid=42
+5\t1\t1\t1\t3\t1\t10\t100\t60\t30\t90.2\t
+";
+
+    #[test]
+    fn test_redact_report_images_requires_engine() {
+        let options = OcrOptions {
+            tesseract_path: Some("/nonexistent/tesseract".to_string()),
+            ..Default::default()
+        };
+        let mut reports = vec![RiskReportData {
+            evidence_screenshots: vec![ScreenshotContent::Text("文本".to_string())],
+            ..Default::default()
+        }];
+        // 没有图片时不调用 OCR 引擎
+        let policy = RedactionPolicy::default();
+        assert_eq!(
+            redact_report_images(&mut reports, &policy, &options).unwrap(),
+            0
+        );
+
+        reports[0]
+            .retest_screenshots
+            .push(ScreenshotContent::Image(Default::default()));
+        assert!(matches!(
+            redact_report_images(&mut reports, &policy, &options),
+            Err(OcrError::EngineNotFound(_))
+        ));
+    }
+
+    #[test]
+    fn test_parse_tsv() {
+        let words = parse_tsv(TSV);
+        assert_eq!(words.len(), 5);
+        assert_eq!(
+            words[1],
+            OcrWord {
+                text: "13812345678".to_string(),
+                line: (1, 1, 1),
+                left: 100,
+                top: 22,
+                width: 150,
+                height: 28,
+            }
+        );
+    }
+
+    #[test]
+    fn test_find_sensitive_regions() {
+        let words = parse_tsv(TSV);
+        let regions = find_sensitive_regions(&words, &RedactionPolicy::default(), 4);
+        assert_eq!(
+            regions,
+            vec![
+                SensitiveRegion {
+                    kind: SensitiveKind::Mobile,
+                    text: "138****5678".to_string(),
+                    x: 96,
+                    y: 18,
+                    width: 158,
+                    height: 36,
+                },
+                SensitiveRegion {
+                    kind: SensitiveKind::Cookie,
+                    text: "sid=***; uid=***".to_string(),
+                    x: 106,
+                    y: 56,
+                    width: 238,
+                    height: 38,
+                },
+            ]
+        );
+    }
+}
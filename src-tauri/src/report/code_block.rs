@@ -0,0 +1,161 @@
+use super::CodeBlock;
+use docx_rs::*;
+
+/// 代码块字体
+const CODE_FONT: &str = "Consolas";
+/// 代码块字号（半磅，18 即 9 磅）
+const CODE_FONT_SIZE: usize = 18;
+/// 制表符展开宽度
+const TAB_WIDTH: usize = 4;
+
+/// 代码片段样式
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum SpanStyle {
+    Plain,      // 普通文本
+    StartLine,  // HTTP 请求行/状态行
+    HeaderName, // HTTP 头部名称
+    LineNumber, // 行号
+    Payload,    // 标记的 payload
+}
+
+/// 将代码块渲染为左对齐、等宽字体的段落列表（每行一个段落）
+pub fn render_paragraphs(block: &CodeBlock) -> Vec<Paragraph> {
+    let lines: Vec<String> = block
+        .code
+        .split('\n')
+        .map(|line| {
+            line.trim_end_matches('\r')
+                .replace('\t', &" ".repeat(TAB_WIDTH))
+        })
+        .collect();
+    let number_width = lines.len().to_string().len();
+    let is_http = block
+        .language
+        .as_deref()
+        .is_some_and(|language| language.eq_ignore_ascii_case("http"));
+    let payload = block.highlight.as_deref().filter(|p| !p.is_empty());
+
+    let mut in_http_headers = is_http;
+    let mut paragraphs = Vec::with_capacity(lines.len());
+
+    for (index, line) in lines.iter().enumerate() {
+        let mut paragraph = Paragraph::new()
+            .align(AlignmentType::Left)
+            .line_spacing(LineSpacing::new().before(0).after(0));
+
+        if block.line_numbers {
+            paragraph = paragraph.add_run(create_run(
+                &format!("{:>width$} ", index + 1, width = number_width),
+                SpanStyle::LineNumber,
+            ));
+        }
+
+        let spans = if is_http && index == 0 {
+            vec![(line.as_str(), SpanStyle::StartLine)]
+        } else if in_http_headers {
+            if line.trim().is_empty() {
+                // 空行之后为报文正文
+                in_http_headers = false;
+            }
+            http_header_spans(line)
+        } else {
+            vec![(line.as_str(), SpanStyle::Plain)]
+        };
+
+        for (text, style) in spans {
+            for (segment, is_payload) in split_highlight(text, payload) {
+                let style = if is_payload {
+                    SpanStyle::Payload
+                } else {
+                    style
+                };
+                paragraph = paragraph.add_run(create_run(segment, style));
+            }
+        }
+
+        paragraphs.push(paragraph);
+    }
+
+    paragraphs
+}
+
+/// 拆分 HTTP 头部行为名称与值两部分
+fn http_header_spans(line: &str) -> Vec<(&str, SpanStyle)> {
+    match line.find(':') {
+        Some(pos) if pos > 0 && !line[..pos].contains(char::is_whitespace) => vec![
+            (&line[..=pos], SpanStyle::HeaderName),
+            (&line[pos + 1..], SpanStyle::Plain),
+        ],
+        _ => vec![(line, SpanStyle::Plain)],
+    }
+}
+
+/// 按 payload 拆分文本，返回 (片段, 是否为 payload)
+fn split_highlight<'a>(text: &'a str, payload: Option<&str>) -> Vec<(&'a str, bool)> {
+    let Some(payload) = payload else {
+        return vec![(text, false)];
+    };
+
+    let mut segments = Vec::new();
+    let mut last = 0;
+    for (start, matched) in text.match_indices(payload) {
+        if start > last {
+            segments.push((&text[last..start], false));
+        }
+        segments.push((matched, true));
+        last = start + matched.len();
+    }
+    if last < text.len() || segments.is_empty() {
+        segments.push((&text[last..], false));
+    }
+    segments
+}
+
+/// 按样式创建文本块
+fn create_run(text: &str, style: SpanStyle) -> Run {
+    let run = Run::new().add_text(text).size(CODE_FONT_SIZE).fonts(
+        RunFonts::new()
+            .ascii(CODE_FONT)
+            .hi_ansi(CODE_FONT)
+            .east_asia("仿宋"),
+    );
+
+    match style {
+        SpanStyle::Plain => run,
+        SpanStyle::StartLine => run.bold(),
+        SpanStyle::HeaderName => run.color("2E74B5"),
+        SpanStyle::LineNumber => run.color("808080"),
+        SpanStyle::Payload => run.bold().color("C00000").highlight("yellow"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_highlight() {
+        assert_eq!(
+            split_highlight("id=1' or '1'='1", Some("' or '")),
+            vec![("id=1", false), ("' or '", true), ("1'='1", false)]
+        );
+        assert_eq!(split_highlight("abc", None), vec![("abc", false)]);
+        assert_eq!(split_highlight("", Some("x")), vec![("", false)]);
+    }
+
+    #[test]
+    fn test_http_header_spans() {
+        assert_eq!(
+            http_header_spans("Host: example.com"),
+            vec![
+                ("Host:", SpanStyle::HeaderName),
+                (" example.com", SpanStyle::Plain)
+            ]
+        );
+        assert_eq!(
+            http_header_spans("not a header"),
+            vec![("not a header", SpanStyle::Plain)]
+        );
+        assert_eq!(render_paragraphs(&CodeBlock::default()).len(), 1);
+    }
+}
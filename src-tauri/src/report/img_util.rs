@@ -1,3 +1,13 @@
+use super::ImageSizing;
+use image::{codecs::jpeg::JpegEncoder, imageops::FilterType, DynamicImage};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+/// 1像素对应的EMU数
+pub const EMU_PER_PIXEL: u32 = 9525;
+/// 1英寸对应的EMU数
+const EMU_PER_INCH: u64 = 914_400;
+
 /// 图片处理相关的错误类型
 #[derive(Debug, thiserror::Error)]
 pub enum ImageError {
@@ -7,6 +17,10 @@ pub enum ImageError {
     CorruptedData,
     #[error("图片尺寸无效")]
     InvalidDimensions,
+    #[error("图片解码失败: {0}")]
+    Decode(String),
+    #[error("图片编码失败: {0}")]
+    Encode(String),
 }
 
 /// 图片格式枚举
@@ -56,6 +70,12 @@ impl ImageFormat {
 
         None
     }
+
+    /// 是否可直接嵌入文档。JPEG 媒体文件在打包时改用 .jpeg 扩展名（见 media::pack_docx），
+    /// 其他格式需转换为PNG
+    pub fn is_embeddable(self) -> bool {
+        matches!(self, ImageFormat::Png | ImageFormat::Jpeg)
+    }
 }
 
 /// 获取图片尺寸信息
@@ -295,26 +315,293 @@ pub fn calculate_proportional_size(
     original_height: u32,
 ) -> (u32, u32) {
     if original_width == 0 || original_height == 0 {
-        // 如果无法获取原始尺寸，使用默认比例(16:9)
-        return (target_width_emu, target_width_emu * 9 / 16);
+        return scale_to_width(target_width_emu, original_width, original_height);
     }
-
-    // 计算比例
-    let aspect_ratio = original_height as f64 / original_width as f64;
-    let target_height_emu = (target_width_emu as f64 * aspect_ratio) as u32;
+    let (_, target_height_emu) = scale_to_width(target_width_emu, original_width, original_height);
 
     // 防止图片过高
     let max_height = target_width_emu * 2; // 最大高度为宽度的2倍
     if target_height_emu > max_height {
-        (
-            target_width_emu * original_width / original_height,
-            max_height,
-        )
+        // 按最大高度等比缩小宽度，使用u64避免溢出
+        let width = max_height as u64 * original_width as u64 / original_height as u64;
+        (width as u32, max_height)
     } else {
         (target_width_emu, target_height_emu)
     }
 }
 
+/// 按目标宽度等比计算高度，不限制高度
+fn scale_to_width(target_width_emu: u32, original_width: u32, original_height: u32) -> (u32, u32) {
+    if original_width == 0 || original_height == 0 {
+        // 如果无法获取原始尺寸，使用默认比例(16:9)
+        return (target_width_emu, target_width_emu * 9 / 16);
+    }
+
+    let aspect_ratio = original_height as f64 / original_width as f64;
+    (
+        target_width_emu,
+        (target_width_emu as f64 * aspect_ratio) as u32,
+    )
+}
+
+/// 按尺寸策略计算图片显示尺寸
+/// 仅未指定最大高度的适应宽度图片沿用默认的高度限制（宽度的2倍），
+/// 原始尺寸或指定了最大高度时按用户设置显示
+/// max_width_px: 可用的最大宽度（像素），max_height_px: 可选的最大高度（像素）
+/// 返回: (width_emu, height_emu)
+pub fn calculate_display_size(
+    sizing: ImageSizing,
+    max_width_px: u32,
+    max_height_px: Option<u32>,
+    original_width: u32,
+    original_height: u32,
+) -> (u32, u32) {
+    let target_width_px = match sizing {
+        ImageSizing::FitWidth => max_width_px,
+        // 原始尺寸，超出可用宽度时缩小
+        ImageSizing::Original if original_width > 0 => original_width.min(max_width_px),
+        ImageSizing::Original => max_width_px,
+    };
+
+    let target_width_emu = target_width_px * EMU_PER_PIXEL;
+    let (width_emu, height_emu) = match (sizing, max_height_px) {
+        (ImageSizing::FitWidth, None) => {
+            calculate_proportional_size(target_width_emu, original_width, original_height)
+        }
+        _ => scale_to_width(target_width_emu, original_width, original_height),
+    };
+
+    match max_height_px.map(|h| h.max(1) * EMU_PER_PIXEL) {
+        Some(max_height_emu) if height_emu > max_height_emu => {
+            let width = width_emu as u64 * max_height_emu as u64 / height_emu as u64;
+            (width as u32, max_height_emu)
+        }
+        _ => (width_emu, height_emu),
+    }
+}
+
+/// 图片预处理配置
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct ImageOptimizeOptions {
+    pub enabled: bool,            // 是否启用预处理
+    pub dpi: u32,                 // 按显示尺寸换算像素时使用的分辨率
+    pub jpeg_quality: Option<u8>, // JPEG质量（1-100），设置后不透明的照片类图片编码为JPEG
+}
+
+impl Default for ImageOptimizeOptions {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            dpi: 150,
+            jpeg_quality: None,
+        }
+    }
+}
+
+/// 图片预处理统计
+#[derive(Serialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ImageOptimizeStats {
+    pub images: usize,       // 处理的图片数量
+    pub optimized: usize,    // 实际被重新编码的图片数量
+    pub transcoded: usize,   // 由 WebP/BMP/GIF 转换的图片数量
+    pub jpeg_encoded: usize, // 按 jpeg_quality 编码为JPEG的图片数量
+    pub original_bytes: u64, // 原始总大小
+    pub output_bytes: u64,   // 处理后总大小
+    pub deduplicated: usize, // 重复出现、引用已嵌入媒体文件的图片数量
+}
+
+impl ImageOptimizeStats {
+    /// 记录一张图片的处理结果
+    pub fn record(&mut self, original_bytes: usize, prepared: &PreparedImage) {
+        self.images += 1;
+        if prepared.optimized {
+            self.optimized += 1;
+        }
+        if prepared.transcoded {
+            self.transcoded += 1;
+        }
+        if prepared.jpeg_encoded {
+            self.jpeg_encoded += 1;
+        }
+        self.original_bytes += original_bytes as u64;
+        self.output_bytes += prepared.data.len() as u64;
+    }
+
+    /// 合并另一份统计
+    pub fn merge(&mut self, other: &ImageOptimizeStats) {
+        self.images += other.images;
+        self.optimized += other.optimized;
+        self.transcoded += other.transcoded;
+        self.jpeg_encoded += other.jpeg_encoded;
+        self.original_bytes += other.original_bytes;
+        self.output_bytes += other.output_bytes;
+        self.deduplicated += other.deduplicated;
+    }
+
+    /// 节省的字节数
+    pub fn saved_bytes(&self) -> u64 {
+        self.original_bytes.saturating_sub(self.output_bytes)
+    }
+
+    /// 生成统计摘要
+    pub fn summary(&self) -> String {
+        let percent = if self.original_bytes > 0 {
+            self.saved_bytes() as f64 * 100.0 / self.original_bytes as f64
+        } else {
+            0.0
+        };
+        let summary = format!(
+            "图片 {} 张，压缩 {} 张，转换格式 {} 张，{:.1} MB → {:.1} MB（节省 {:.1}%）",
+            self.images,
+            self.optimized,
+            self.transcoded,
+            self.original_bytes as f64 / 1_048_576.0,
+            self.output_bytes as f64 / 1_048_576.0,
+            percent
+        );
+        let summary = if self.jpeg_encoded > 0 {
+            format!("{}，JPEG压缩 {} 张", summary, self.jpeg_encoded)
+        } else {
+            summary
+        };
+        if self.deduplicated > 0 {
+            format!("{}，重复引用 {} 张", summary, self.deduplicated)
+        } else {
+            summary
+        }
+    }
+}
+
+/// 预处理后的图片
+#[derive(Debug, Clone)]
+pub struct PreparedImage {
+    pub data: Vec<u8>,      // 最终嵌入文档的图片数据
+    pub optimized: bool,    // 是否经过重新编码
+    pub transcoded: bool,   // 是否由 WebP/BMP/GIF 转换而来
+    pub jpeg_encoded: bool, // 是否按 jpeg_quality 编码为JPEG
+}
+
+impl PreparedImage {
+    /// 保留原始图片数据
+    fn unchanged(data: &[u8]) -> Self {
+        Self {
+            data: data.to_vec(),
+            optimized: false,
+            transcoded: false,
+            jpeg_encoded: false,
+        }
+    }
+}
+
+/// 按显示尺寸预处理图片：降采样到目标分辨率，WebP/BMP/GIF 转换为PNG；
+/// 设置了 jpeg_quality 时，不透明的照片类图片编码为JPEG。
+/// PNG/JPEG 图片处理结果不小于原图时保留原始数据
+pub fn optimize_image(
+    data: &[u8],
+    display_width_emu: u32,
+    display_height_emu: u32,
+    options: &ImageOptimizeOptions,
+) -> Result<PreparedImage, ImageError> {
+    let format = ImageFormat::from_signature(data).ok_or(ImageError::UnsupportedFormat)?;
+
+    // WebP、BMP、GIF 无法直接嵌入，始终转换
+    let needs_transcode = !format.is_embeddable();
+    if !options.enabled && !needs_transcode {
+        return Ok(PreparedImage::unchanged(data));
+    }
+
+    // GIF 动图仅解码第一帧
+    let image = image::load_from_memory(data)
+        .map_err(|e| ImageError::Decode(format!("{:?}: {}", format, e)))?;
+
+    // 计算目标像素尺寸
+    let dpi = options.dpi.max(1) as u64;
+    let target_width = (display_width_emu as u64 * dpi)
+        .div_ceil(EMU_PER_INCH)
+        .max(1) as u32;
+    let target_height = (display_height_emu as u64 * dpi)
+        .div_ceil(EMU_PER_INCH)
+        .max(1) as u32;
+
+    let needs_resize =
+        options.enabled && image.width() > target_width && image.height() > target_height;
+    // 截图以PNG保存更清晰，仅照片类图片使用有损压缩；透明图片无法以JPEG保存
+    let jpeg_quality = options.jpeg_quality.filter(|_| {
+        options.enabled
+            && is_opaque(&image)
+            && (matches!(format, ImageFormat::Jpeg) || is_photographic(&image))
+    });
+    if !needs_resize && !needs_transcode && jpeg_quality.is_none() {
+        return Ok(PreparedImage::unchanged(data));
+    }
+    let image = if needs_resize {
+        image.resize(target_width, target_height, FilterType::Lanczos3)
+    } else {
+        image
+    };
+
+    let encoded = match jpeg_quality {
+        Some(quality) => encode_jpeg(&image, quality)?,
+        None => encode_png(&image)?,
+    };
+
+    // 转码的图片即使变大也必须使用转换结果
+    if !needs_transcode && encoded.len() >= data.len() {
+        return Ok(PreparedImage::unchanged(data));
+    }
+
+    Ok(PreparedImage {
+        data: encoded,
+        optimized: true,
+        transcoded: needs_transcode,
+        jpeg_encoded: jpeg_quality.is_some(),
+    })
+}
+
+/// 采样像素中不同颜色的占比达到该值时视为照片类图片
+const PHOTO_COLOR_RATIO: f64 = 0.25;
+/// 判断照片类图片时最多采样的像素数
+const PHOTO_SAMPLE_PIXELS: u32 = 4096;
+
+/// 是否为照片类图片。截图以大面积纯色为主，采样像素中的颜色种类远少于照片
+fn is_photographic(image: &DynamicImage) -> bool {
+    let rgb = image.to_rgb8();
+    let total = rgb.width() as u64 * rgb.height() as u64;
+    let step = (total / PHOTO_SAMPLE_PIXELS as u64).max(1) as usize;
+
+    let mut colors = HashSet::new();
+    let mut samples = 0usize;
+    for pixel in rgb.pixels().step_by(step) {
+        colors.insert(pixel.0);
+        samples += 1;
+    }
+    samples > 0 && colors.len() as f64 / samples as f64 >= PHOTO_COLOR_RATIO
+}
+
+/// 是否不含透明像素
+fn is_opaque(image: &DynamicImage) -> bool {
+    !image.color().has_alpha() || image.to_rgba8().pixels().all(|pixel| pixel.0[3] == u8::MAX)
+}
+
+/// 按指定质量编码为JPEG
+fn encode_jpeg(image: &DynamicImage, quality: u8) -> Result<Vec<u8>, ImageError> {
+    let mut buffer = Vec::new();
+    JpegEncoder::new_with_quality(&mut buffer, quality.clamp(1, 100))
+        .encode_image(&image.to_rgb8())
+        .map_err(|e| ImageError::Encode(e.to_string()))?;
+    Ok(buffer)
+}
+
+/// 编码为PNG
+fn encode_png(image: &DynamicImage) -> Result<Vec<u8>, ImageError> {
+    let mut buffer = std::io::Cursor::new(Vec::new());
+    image
+        .write_to(&mut buffer, image::ImageFormat::Png)
+        .map_err(|e| ImageError::Encode(e.to_string()))?;
+    Ok(buffer.into_inner())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -378,5 +665,164 @@ mod tests {
         let (width, height) = calculate_proportional_size(1920 * 9525, 0, 0);
         assert_eq!(width, 1920 * 9525);
         assert_eq!(height, 1920 * 9525 * 9 / 16);
+
+        // 过高的图片按最大高度等比缩小
+        let (width, height) = calculate_proportional_size(520 * 9525, 1080, 4320);
+        assert_eq!(width, 260 * 9525);
+        assert_eq!(height, 1040 * 9525);
+    }
+
+    /// 生成带噪点的测试PNG，避免被过度压缩
+    fn create_test_png(width: u32, height: u32) -> Vec<u8> {
+        let image = image::RgbImage::from_fn(width, height, |x, y| {
+            let v = (x.wrapping_mul(7919) ^ y.wrapping_mul(104729)) as u8;
+            image::Rgb([v, v.wrapping_mul(3), v.wrapping_add(x as u8)])
+        });
+        encode_png(&DynamicImage::ImageRgb8(image)).unwrap()
+    }
+
+    #[test]
+    fn test_optimize_image() {
+        let png = create_test_png(1040, 520);
+        // 显示为 520x260 像素，按96 DPI 换算后应降采样
+        let options = ImageOptimizeOptions {
+            dpi: 96,
+            ..Default::default()
+        };
+        let prepared = optimize_image(&png, 520 * 9525, 260 * 9525, &options).unwrap();
+        assert!(prepared.optimized);
+        assert!(prepared.data.len() < png.len());
+        assert_eq!(get_image_dimensions(&prepared.data).unwrap(), (520, 260));
+
+        // 小图无需降采样时保留原始数据
+        let small = create_test_png(100, 50);
+        let prepared = optimize_image(&small, 520 * 9525, 260 * 9525, &options).unwrap();
+        assert!(!prepared.optimized);
+        assert_eq!(prepared.data, small);
+
+        // 未启用时保留原始数据
+        let disabled = ImageOptimizeOptions {
+            enabled: false,
+            ..Default::default()
+        };
+        let prepared = optimize_image(&png, 520 * 9525, 260 * 9525, &disabled).unwrap();
+        assert_eq!(prepared.data, png);
+    }
+
+    #[test]
+    fn test_transcode_to_png() {
+        let image =
+            DynamicImage::ImageRgb8(image::RgbImage::from_pixel(40, 20, image::Rgb([255, 0, 0])));
+        let mut bmp = std::io::Cursor::new(Vec::new());
+        image.write_to(&mut bmp, image::ImageFormat::Bmp).unwrap();
+        let bmp = bmp.into_inner();
+
+        // 即使未启用预处理，BMP 也会被转换为PNG
+        let disabled = ImageOptimizeOptions {
+            enabled: false,
+            ..Default::default()
+        };
+        let prepared = optimize_image(&bmp, 520 * 9525, 260 * 9525, &disabled).unwrap();
+        assert!(prepared.transcoded);
+        assert!(matches!(
+            ImageFormat::from_signature(&prepared.data),
+            Some(ImageFormat::Png)
+        ));
+        assert_eq!(get_image_dimensions(&prepared.data).unwrap(), (40, 20));
+
+        // JPEG 以 .jpeg 媒体文件嵌入，无需转换
+        let mut jpeg = std::io::Cursor::new(Vec::new());
+        image.write_to(&mut jpeg, image::ImageFormat::Jpeg).unwrap();
+        let jpeg = jpeg.into_inner();
+        let prepared = optimize_image(&jpeg, 520 * 9525, 260 * 9525, &disabled).unwrap();
+        assert!(!prepared.transcoded);
+        assert_eq!(prepared.data, jpeg);
+
+        // 损坏的数据返回解码错误
+        let mut corrupted = bmp.clone();
+        corrupted.truncate(30);
+        assert!(matches!(
+            optimize_image(&corrupted, 520 * 9525, 260 * 9525, &disabled),
+            Err(ImageError::Decode(_))
+        ));
+    }
+
+    #[test]
+    fn test_jpeg_quality() {
+        let options = ImageOptimizeOptions {
+            jpeg_quality: Some(80),
+            ..Default::default()
+        };
+
+        // 不透明的照片类图片编码为JPEG
+        let photo = create_test_png(400, 200);
+        let prepared = optimize_image(&photo, 400 * 9525, 200 * 9525, &options).unwrap();
+        assert!(prepared.jpeg_encoded);
+        assert!(prepared.data.len() < photo.len());
+        assert!(matches!(
+            ImageFormat::from_signature(&prepared.data),
+            Some(ImageFormat::Jpeg)
+        ));
+        let mut stats = ImageOptimizeStats::default();
+        stats.record(photo.len(), &prepared);
+        assert_eq!(stats.jpeg_encoded, 1);
+        assert!(stats.summary().contains("JPEG压缩 1 张"));
+
+        // 纯色为主的截图保留PNG
+        let screenshot = encode_png(&DynamicImage::ImageRgb8(image::RgbImage::from_pixel(
+            400,
+            200,
+            image::Rgb([240, 240, 240]),
+        )))
+        .unwrap();
+        let prepared = optimize_image(&screenshot, 400 * 9525, 200 * 9525, &options).unwrap();
+        assert!(!prepared.jpeg_encoded);
+        assert_eq!(prepared.data, screenshot);
+
+        // 含透明像素的图片不编码为JPEG
+        let transparent = image::RgbaImage::from_fn(400, 200, |x, y| {
+            let v = (x.wrapping_mul(7919) ^ y.wrapping_mul(104729)) as u8;
+            image::Rgba([v, v.wrapping_mul(3), x as u8, if x < 10 { 0 } else { 255 }])
+        });
+        let transparent = encode_png(&DynamicImage::ImageRgba8(transparent)).unwrap();
+        let prepared = optimize_image(&transparent, 400 * 9525, 200 * 9525, &options).unwrap();
+        assert!(!prepared.jpeg_encoded);
+
+        // 未设置质量时照片类图片保持PNG
+        let prepared = optimize_image(
+            &photo,
+            400 * 9525,
+            200 * 9525,
+            &ImageOptimizeOptions::default(),
+        )
+        .unwrap();
+        assert!(!prepared.jpeg_encoded);
+    }
+
+    #[test]
+    fn test_calculate_display_size() {
+        // 适应宽度：小图放大到可用宽度
+        let (width, height) = calculate_display_size(ImageSizing::FitWidth, 520, None, 52, 26);
+        assert_eq!((width, height), (520 * 9525, 260 * 9525));
+
+        // 原始尺寸：小图保持原尺寸，大图缩小到可用宽度
+        let (width, height) = calculate_display_size(ImageSizing::Original, 520, None, 52, 26);
+        assert_eq!((width, height), (52 * 9525, 26 * 9525));
+        let (width, _) = calculate_display_size(ImageSizing::Original, 520, None, 1920, 1080);
+        assert_eq!(width, 520 * 9525);
+
+        // 最大高度限制
+        let (width, height) =
+            calculate_display_size(ImageSizing::FitWidth, 520, Some(400), 1000, 2000);
+        assert_eq!((width, height), (200 * 9525, 400 * 9525));
+
+        // 长截图：适应宽度时限制为宽度的2倍，原始尺寸或指定最大高度时不受该限制
+        let (width, height) = calculate_display_size(ImageSizing::FitWidth, 520, None, 500, 3000);
+        assert_eq!((width, height), (1040 * 9525 / 6, 1040 * 9525));
+        let (width, height) = calculate_display_size(ImageSizing::Original, 520, None, 500, 3000);
+        assert_eq!((width, height), (500 * 9525, 3000 * 9525));
+        let (width, height) =
+            calculate_display_size(ImageSizing::FitWidth, 520, Some(1500), 500, 3000);
+        assert_eq!((width, height), (250 * 9525, 1500 * 9525));
     }
 }
@@ -0,0 +1,231 @@
+use docx_rs::*;
+
+/// 行内文本片段
+#[derive(Debug, Clone, PartialEq)]
+pub enum Inline {
+    Text(String),                       // 普通文本
+    Bold(String),                       // **加粗**
+    Code(String),                       // `行内代码`
+    Link { text: String, url: String }, // [链接文本](地址)
+}
+
+/// 文本块，每个文本块渲染为一个段落
+#[derive(Debug, Clone, PartialEq)]
+pub enum Block {
+    Paragraph(Vec<Inline>), // 普通段落
+    Bullet(Vec<Inline>),    // 以 "- " 或 "* " 开头的列表项
+}
+
+/// 长文本正文字体
+const BODY_FONT: &str = "仿宋";
+/// 行内代码字体
+const CODE_FONT: &str = "Consolas";
+/// 列表项左缩进（约两个字符）
+const BULLET_INDENT: i32 = 420;
+
+/// 将文本按行解析为文本块，空行会被忽略
+pub fn parse(text: &str) -> Vec<Block> {
+    text.lines()
+        .map(|line| line.trim_end_matches('\r'))
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| {
+            let trimmed = line.trim_start();
+            match ["- ", "* ", "• "]
+                .iter()
+                .find_map(|marker| trimmed.strip_prefix(marker))
+            {
+                Some(item) => Block::Bullet(parse_inline(item.trim_start())),
+                None => Block::Paragraph(parse_inline(line)),
+            }
+        })
+        .collect()
+}
+
+/// 解析行内标记：**加粗**、`代码`、[文本](链接)，未闭合的标记按普通文本处理
+pub fn parse_inline(line: &str) -> Vec<Inline> {
+    let mut inlines = Vec::new();
+    let mut plain = String::new();
+    let mut rest = line;
+
+    while let Some(ch) = rest.chars().next() {
+        let parsed = if let Some(after) = rest.strip_prefix("**") {
+            after
+                .find("**")
+                .filter(|&end| end > 0)
+                .map(|end| (Inline::Bold(after[..end].to_string()), &after[end + 2..]))
+        } else if let Some(after) = rest.strip_prefix('`') {
+            after
+                .find('`')
+                .filter(|&end| end > 0)
+                .map(|end| (Inline::Code(after[..end].to_string()), &after[end + 1..]))
+        } else if let Some(after) = rest.strip_prefix('[') {
+            parse_link(after)
+        } else {
+            None
+        };
+
+        match parsed {
+            Some((inline, remaining)) => {
+                if !plain.is_empty() {
+                    inlines.push(Inline::Text(std::mem::take(&mut plain)));
+                }
+                inlines.push(inline);
+                rest = remaining;
+            }
+            None => {
+                plain.push(ch);
+                rest = &rest[ch.len_utf8()..];
+            }
+        }
+    }
+
+    if !plain.is_empty() {
+        inlines.push(Inline::Text(plain));
+    }
+
+    inlines
+}
+
+/// 解析 "文本](链接)" 部分，返回链接及剩余文本
+fn parse_link(after_bracket: &str) -> Option<(Inline, &str)> {
+    let text_end = after_bracket.find("](")?;
+    let text = &after_bracket[..text_end];
+    let after_text = &after_bracket[text_end + 2..];
+    let url_end = after_text.find(')')?;
+    let url = after_text[..url_end].trim();
+
+    if text.is_empty() || url.is_empty() || text.contains('\n') {
+        return None;
+    }
+
+    Some((
+        Inline::Link {
+            text: text.to_string(),
+            url: url.to_string(),
+        },
+        &after_text[url_end + 1..],
+    ))
+}
+
+/// 将长文本渲染为左对齐的段落列表，至少返回一个段落
+pub fn render_paragraphs(text: &str) -> Vec<Paragraph> {
+    let paragraphs: Vec<Paragraph> = parse(text).into_iter().map(render_block).collect();
+
+    if paragraphs.is_empty() {
+        vec![Paragraph::new()
+            .add_run(
+                Run::new()
+                    .add_text("")
+                    .fonts(RunFonts::new().ascii(BODY_FONT)),
+            )
+            .align(AlignmentType::Left)]
+    } else {
+        paragraphs
+    }
+}
+
+/// 渲染单个文本块
+fn render_block(block: Block) -> Paragraph {
+    match block {
+        Block::Paragraph(inlines) => {
+            add_inlines(Paragraph::new(), inlines).align(AlignmentType::Left)
+        }
+        Block::Bullet(inlines) => {
+            let paragraph = Paragraph::new().add_run(
+                Run::new()
+                    .add_text("• ")
+                    .fonts(RunFonts::new().ascii(BODY_FONT)),
+            );
+            add_inlines(paragraph, inlines)
+                .align(AlignmentType::Left)
+                .indent(
+                    Some(BULLET_INDENT),
+                    Some(SpecialIndentType::Hanging(BULLET_INDENT)),
+                    None,
+                    None,
+                )
+        }
+    }
+}
+
+/// 将行内片段追加到段落
+fn add_inlines(mut paragraph: Paragraph, inlines: Vec<Inline>) -> Paragraph {
+    for inline in inlines {
+        paragraph = match inline {
+            Inline::Text(text) => paragraph.add_run(
+                Run::new()
+                    .add_text(text)
+                    .fonts(RunFonts::new().ascii(BODY_FONT)),
+            ),
+            Inline::Bold(text) => paragraph.add_run(
+                Run::new()
+                    .add_text(text)
+                    .bold()
+                    .fonts(RunFonts::new().ascii(BODY_FONT)),
+            ),
+            Inline::Code(text) => paragraph.add_run(
+                Run::new()
+                    .add_text(text)
+                    .highlight("lightGray")
+                    .fonts(RunFonts::new().ascii(CODE_FONT).hi_ansi(CODE_FONT)),
+            ),
+            Inline::Link { text, url } => paragraph.add_hyperlink(
+                Hyperlink::new(url, HyperlinkType::External).add_run(
+                    Run::new()
+                        .add_text(text)
+                        .color("0563C1")
+                        .underline("single")
+                        .fonts(RunFonts::new().ascii(BODY_FONT)),
+                ),
+            ),
+        };
+    }
+    paragraph
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_lines_and_bullets() {
+        let blocks = parse("1、第一步\r\n\n- 列表项\n  * 缩进列表项\n");
+        assert_eq!(
+            blocks,
+            vec![
+                Block::Paragraph(vec![Inline::Text("1、第一步".to_string())]),
+                Block::Bullet(vec![Inline::Text("列表项".to_string())]),
+                Block::Bullet(vec![Inline::Text("缩进列表项".to_string())]),
+            ]
+        );
+
+        assert!(parse("").is_empty());
+        assert_eq!(render_paragraphs("").len(), 1);
+    }
+
+    #[test]
+    fn test_parse_inline_markup() {
+        let inlines =
+            parse_inline("将 `expose_php = On` 改为 **Off**，参考[文档](https://php.net)");
+        assert_eq!(
+            inlines,
+            vec![
+                Inline::Text("将 ".to_string()),
+                Inline::Code("expose_php = On".to_string()),
+                Inline::Text(" 改为 ".to_string()),
+                Inline::Bold("Off".to_string()),
+                Inline::Text("，参考".to_string()),
+                Inline::Link {
+                    text: "文档".to_string(),
+                    url: "https://php.net".to_string(),
+                },
+            ]
+        );
+
+        // 未闭合的标记按普通文本处理
+        assert_eq!(
+            parse_inline("a ** b ` c [d]"),
+            vec![Inline::Text("a ** b ` c [d]".to_string())]
+        );
+    }
+}
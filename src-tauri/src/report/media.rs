@@ -0,0 +1,119 @@
+use super::img_util::ImageFormat;
+use docx_rs::{
+    DrawingPosition, DrawingPositionType, Pic, RelativeFromHType, RelativeFromVType, XMLDocx,
+};
+use sha2::{Digest, Sha256};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::io::{Cursor, Seek, Write};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// 图片关系ID序号，与 docx-rs 的 "rIdImage{n}" 命名保持一致
+static NEXT_PIC_ID: AtomicUsize = AtomicUsize::new(1);
+
+/// 计算图片内容的哈希（SHA-256 十六进制），作为内容寻址的键
+pub fn content_hash(data: &[u8]) -> String {
+    format!("{:x}", Sha256::digest(data))
+}
+
+/// 以PNG或JPEG数据创建内联图片。不使用 Pic::new，它会再次解码并重新编码为PNG
+fn new_pic(image: Vec<u8>, width_emu: u32, height_emu: u32) -> Pic {
+    Pic {
+        id: format!("rIdImage{}", NEXT_PIC_ID.fetch_add(1, Ordering::Relaxed)),
+        image,
+        size: (width_emu, height_emu),
+        position_type: DrawingPositionType::Inline,
+        simple_pos: false,
+        simple_pos_x: 0,
+        simple_pos_y: 0,
+        layout_in_cell: false,
+        relative_height: 190500,
+        allow_overlap: false,
+        position_h: DrawingPosition::Offset(0),
+        position_v: DrawingPosition::Offset(0),
+        relative_from_h: RelativeFromHType::default(),
+        relative_from_v: RelativeFromVType::default(),
+        dist_t: 0,
+        dist_b: 0,
+        dist_l: 0,
+        dist_r: 0,
+        rot: 0,
+    }
+}
+
+/// 文档内已嵌入的图片，按内容哈希和显示尺寸索引。
+/// 图片按显示尺寸预处理，相同内容、相同尺寸的图片共享同一个媒体文件
+#[derive(Default)]
+pub struct MediaCache {
+    images: RefCell<HashMap<(String, u32, u32), Pic>>,
+}
+
+impl MediaCache {
+    /// 查找以相同尺寸嵌入过的图片，返回引用同一媒体文件的图片
+    pub fn get(&self, hash: &str, width_emu: u32, height_emu: u32) -> Option<Pic> {
+        let images = self.images.borrow();
+        images
+            .get(&(hash.to_string(), width_emu, height_emu))
+            .cloned()
+    }
+
+    /// 嵌入预处理后的图片并记录，数据必须为PNG或JPEG格式（JPEG 需经 pack_docx 打包）；
+    /// 媒体数据只随第一次出现的图片写入，打包时按ID合并，后续引用无需携带图片数据
+    pub fn insert(&self, hash: String, image: Vec<u8>, width_emu: u32, height_emu: u32) -> Pic {
+        let pic = new_pic(image, width_emu, height_emu);
+        let template = Pic {
+            image: Vec::new(),
+            ..pic.clone()
+        };
+        self.images
+            .borrow_mut()
+            .insert((hash, width_emu, height_emu), template);
+        pic
+    }
+}
+
+/// 打包文档。docx-rs 将媒体文件一律写为 `word/media/{id}.png`，
+/// 内容为JPEG的媒体文件改用 .jpeg 扩展名，并同步更新关系文件中的引用
+pub fn pack_docx<W: Write + Seek>(mut xml: XMLDocx, writer: W) -> zip::result::ZipResult<()> {
+    let jpeg_ids: Vec<String> = xml
+        .media
+        .iter()
+        .filter(|(_, data)| matches!(ImageFormat::from_signature(data), Some(ImageFormat::Jpeg)))
+        .map(|(id, _)| id.clone())
+        .collect();
+    if jpeg_ids.is_empty() {
+        return xml.pack(writer);
+    }
+
+    let rename = |rels: &mut Vec<u8>| {
+        let mut text = String::from_utf8_lossy(rels).into_owned();
+        for id in &jpeg_ids {
+            text = text.replace(
+                &format!("\"media/{}.png\"", id),
+                &format!("\"media/{}.jpeg\"", id),
+            );
+        }
+        *rels = text.into_bytes();
+    };
+    rename(&mut xml.document_rels);
+    xml.header_rels.iter_mut().for_each(&rename);
+    xml.footer_rels.iter_mut().for_each(&rename);
+
+    // docx-rs 不支持指定媒体文件名，打包后逐项复制并重命名JPEG媒体文件
+    let mut packed = Cursor::new(Vec::new());
+    xml.pack(&mut packed)?;
+    let mut source = zip::ZipArchive::new(packed)?;
+    let mut output = zip::ZipWriter::new(writer);
+    for index in 0..source.len() {
+        let file = source.by_index_raw(index)?;
+        let name = match file.name().strip_prefix("word/media/") {
+            Some(media) if jpeg_ids.iter().any(|id| media == format!("{}.png", id)) => {
+                format!("{}.jpeg", file.name().trim_end_matches(".png"))
+            }
+            _ => file.name().to_string(),
+        };
+        output.raw_copy_file_rename(file, name)?;
+    }
+    output.finish()?;
+    Ok(())
+}
@@ -1,6 +1,16 @@
 use docx_rs::*;
 use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
+use std::collections::{BTreeMap, HashMap};
+use std::rc::Rc;
+mod code_block;
 mod img_util;
+mod markup;
+mod media;
+pub mod schema;
+
+pub use img_util::{ImageOptimizeOptions, ImageOptimizeStats};
+pub use media::{content_hash, MediaCache};
 /// 截图内容类型，支持文本说明和图片数据
 #[derive(Serialize, Deserialize, Debug, Clone)]
 #[serde(tag = "type", content = "content")]
@@ -8,11 +18,94 @@ pub enum ScreenshotContent {
     #[serde(rename = "text")]
     Text(String), // 文本说明
     #[serde(rename = "image")]
-    Image(Vec<u8>), // 图片数据
+    Image(ScreenshotImage), // 图片数据
+    #[serde(rename = "code")]
+    Code(CodeBlock), // 代码/HTTP报文
 }
 
-/// 风险隐患报告数据结构
-#[derive(Serialize, Deserialize, Debug, Clone)]
+/// 截图图片，兼容仅包含图片字节数组的旧格式
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(from = "ScreenshotImageRepr")]
+pub struct ScreenshotImage {
+    pub data: Vec<u8>, // 图片数据
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub caption: Option<String>, // 图片说明，渲染为 "图 N-M：说明"
+    #[serde(default)]
+    pub sizing: ImageSizing, // 尺寸策略
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_height: Option<u32>, // 最大显示高度（像素）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub blob: Option<String>, // 已保存图片的引用，加载项目时 data 为空，按需读取图片数据
+}
+
+/// 图片尺寸策略
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ImageSizing {
+    #[default]
+    #[serde(rename = "fit_width")]
+    FitWidth, // 缩放至可用宽度
+    #[serde(rename = "original")]
+    Original, // 保持原始尺寸，超出可用宽度时缩小
+}
+
+/// 截图图片的反序列化表示
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum ScreenshotImageRepr {
+    Legacy(Vec<u8>),
+    Full {
+        #[serde(default)]
+        data: Vec<u8>,
+        #[serde(default)]
+        caption: Option<String>,
+        #[serde(default)]
+        sizing: ImageSizing,
+        #[serde(default)]
+        max_height: Option<u32>,
+        #[serde(default)]
+        blob: Option<String>,
+    },
+}
+
+impl From<ScreenshotImageRepr> for ScreenshotImage {
+    fn from(repr: ScreenshotImageRepr) -> Self {
+        match repr {
+            ScreenshotImageRepr::Legacy(data) => Self {
+                data,
+                ..Default::default()
+            },
+            ScreenshotImageRepr::Full {
+                data,
+                caption,
+                sizing,
+                max_height,
+                blob,
+            } => Self {
+                data,
+                caption,
+                sizing,
+                max_height,
+                blob,
+            },
+        }
+    }
+}
+
+/// 代码块内容，以等宽字体左对齐渲染并保留空白
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct CodeBlock {
+    pub code: String,
+    #[serde(default)]
+    pub language: Option<String>, // 为 "http" 时高亮请求行/状态行与头部名称
+    #[serde(default)]
+    pub line_numbers: bool, // 是否显示行号
+    #[serde(default)]
+    pub highlight: Option<String>, // 需要突出显示的 payload 子串
+}
+
+/// 风险隐患报告数据结构，缺失的字段使用默认值，旧版本数据经 schema::upgrade 升级
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(default)]
 pub struct RiskReportData {
     pub id: String,
     pub hazard_type: String,
@@ -36,8 +129,292 @@ pub struct RiskReportData {
     pub evidence_screenshots: Vec<ScreenshotContent>, // 支持多张证据截图（文本或图片）
     pub filing_screenshots: Vec<ScreenshotContent>,   // 支持多张备案截图（文本或图片）
     pub remark: String,
+    pub evidence_columns: usize, // 证据截图每行并排的图片数（0或1为单列，最多3列）
+    pub remediation_status: RemediationStatus, // 修复状态
+    pub retest_date: String,     // 复测日期
+    pub retest_screenshots: Vec<ScreenshotContent>, // 复测证据截图（文本或图片）
+    pub custom_fields: BTreeMap<String, String>, // 项目自定义字段的值（字段名 -> 值）
+    pub schema_version: u32,     // 数据结构版本，保存时写入 schema::SCHEMA_VERSION，0 表示未标记
+}
+
+/// 隐患修复状态，复测时填写
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum RemediationStatus {
+    #[default]
+    #[serde(rename = "unfixed")]
+    Unfixed, // 未修复
+    #[serde(rename = "fixed")]
+    Fixed, // 已修复
+    #[serde(rename = "partially_fixed")]
+    PartiallyFixed, // 部分修复
+    #[serde(rename = "risk_accepted")]
+    RiskAccepted, // 风险接受
+}
+
+impl RemediationStatus {
+    /// 报告中显示的名称
+    pub fn label(&self) -> &'static str {
+        match self {
+            RemediationStatus::Unfixed => "未修复",
+            RemediationStatus::Fixed => "已修复",
+            RemediationStatus::PartiallyFixed => "部分修复",
+            RemediationStatus::RiskAccepted => "风险接受",
+        }
+    }
+}
+
+/// 自定义字段类型
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum CustomFieldType {
+    #[default]
+    #[serde(rename = "text")]
+    Text, // 单行文本
+    #[serde(rename = "multiline")]
+    Multiline, // 多行文本，在表格中独占一行
+    #[serde(rename = "number")]
+    Number, // 数字
+    #[serde(rename = "date")]
+    Date, // 日期
+}
+
+/// 项目声明的自定义字段
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct CustomFieldDef {
+    pub name: String, // 字段名，同时作为表格中的标签
+    #[serde(rename = "type", default)]
+    pub field_type: CustomFieldType,
+    #[serde(default)]
+    pub required: bool, // 生成报告时必须填写
+}
+
+/// 报告类型
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ReportVariant {
+    #[default]
+    #[serde(rename = "original")]
+    Original, // 原始报告
+    #[serde(rename = "retest")]
+    Retest, // 复测报告，并排显示原始证据与复测证据
+}
+
+/// 隐患级别着色位置
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum LevelShadingTarget {
+    #[serde(rename = "none")]
+    None, // 不着色
+    #[serde(rename = "header")]
+    Header, // 表格标题行
+    #[default]
+    #[serde(rename = "cell")]
+    Cell, // 隐患级别单元格
+}
+
+/// 隐患级别颜色配置（级别 -> 十六进制颜色，如 "FF0000"）
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct LevelColorConfig {
+    #[serde(default)]
+    pub target: LevelShadingTarget,
+    #[serde(default)]
+    pub colors: HashMap<String, String>,
+}
+
+impl Default for LevelColorConfig {
+    fn default() -> Self {
+        let colors = [
+            ("严重", "FF0000"),
+            ("高危", "FFA500"),
+            ("中危", "FFFF00"),
+            ("低危", "9DC3E6"),
+        ]
+        .into_iter()
+        .map(|(level, color)| (level.to_string(), color.to_string()))
+        .collect();
+
+        Self {
+            target: LevelShadingTarget::default(),
+            colors,
+        }
+    }
+}
+
+impl LevelColorConfig {
+    /// 获取隐患级别对应的颜色，返回去掉 `#` 前缀的大写十六进制值
+    pub fn color_for(&self, level: &str) -> Option<String> {
+        let color = self.colors.get(level.trim())?;
+        let color = color.trim().trim_start_matches('#');
+        if color.len() == 6 && color.chars().all(|c| c.is_ascii_hexdigit()) {
+            Some(color.to_ascii_uppercase())
+        } else {
+            log::warn!("隐患级别 {} 的颜色值无效: {}", level, color);
+            None
+        }
+    }
+
+    /// 获取指定位置的底纹，未配置或位置不匹配时返回 None
+    fn shading_for(&self, target: LevelShadingTarget, level: &str) -> Option<Shading> {
+        if self.target != target {
+            return None;
+        }
+        self.color_for(level)
+            .map(|color| Shading::new().fill(color))
+    }
+}
+
+/// 截图字段（字段名, 文档中的标题），顺序与 screenshot_groups 一致
+pub const SCREENSHOT_FIELDS: [(&str, &str); 3] = [
+    ("evidence_screenshots", "证据截图"),
+    ("filing_screenshots", "工信域名备案截图"),
+    ("retest_screenshots", "复测证据截图"),
+];
+
+impl RiskReportData {
+    /// 获取所有截图字段的可变引用（字段名, 截图列表）。
+    /// 存储、归档、脱敏等需要遍历截图的地方统一使用，新增截图字段时在 SCREENSHOT_FIELDS 和这两个方法中登记
+    pub fn screenshot_groups_mut(&mut self) -> Vec<(&'static str, &mut Vec<ScreenshotContent>)> {
+        let [evidence, filing, retest] = SCREENSHOT_FIELDS.map(|(field, _)| field);
+        vec![
+            (evidence, &mut self.evidence_screenshots),
+            (filing, &mut self.filing_screenshots),
+            (retest, &mut self.retest_screenshots),
+        ]
+    }
+
+    /// 获取所有截图字段（字段名, 截图列表），与 screenshot_groups_mut 顺序一致
+    pub fn screenshot_groups(&self) -> Vec<(&'static str, &Vec<ScreenshotContent>)> {
+        let [evidence, filing, retest] = SCREENSHOT_FIELDS.map(|(field, _)| field);
+        vec![
+            (evidence, &self.evidence_screenshots),
+            (filing, &self.filing_screenshots),
+            (retest, &self.retest_screenshots),
+        ]
+    }
+
+    /// 获取所有文本字段的可变引用（字段名, 值），不包含内部主键 id
+    pub fn text_fields_mut(&mut self) -> Vec<(&'static str, &mut String)> {
+        vec![
+            ("hazard_type", &mut self.hazard_type),
+            ("report_name", &mut self.report_name),
+            ("hazard_level", &mut self.hazard_level),
+            ("report_id", &mut self.report_id),
+            ("target", &mut self.target),
+            ("vul_name", &mut self.vul_name),
+            ("warning_level", &mut self.warning_level),
+            ("city", &mut self.city),
+            ("unit_type", &mut self.unit_type),
+            ("industry", &mut self.industry),
+            ("customer_company_name", &mut self.customer_company_name),
+            ("website_name", &mut self.website_name),
+            ("domain", &mut self.domain),
+            ("ip_address", &mut self.ip_address),
+            ("case_number", &mut self.case_number),
+            ("report_time", &mut self.report_time),
+            ("retest_date", &mut self.retest_date),
+            ("problem_description", &mut self.problem_description),
+            ("vul_modify_repair", &mut self.vul_modify_repair),
+            ("remark", &mut self.remark),
+        ]
+    }
+
+    /// 按字段声明排列的自定义字段（字段名, 类型, 值）。
+    /// 未声明但有值的字段排在最后，按单行文本处理
+    pub fn custom_field_values<'a>(
+        &'a self,
+        defs: &'a [CustomFieldDef],
+    ) -> Vec<(&'a str, CustomFieldType, &'a str)> {
+        let declared = defs.iter().map(|def| {
+            let value = self.custom_fields.get(&def.name).map_or("", String::as_str);
+            (def.name.as_str(), def.field_type, value)
+        });
+        let undeclared = self
+            .custom_fields
+            .iter()
+            .filter(|(name, value)| {
+                !value.trim().is_empty() && !defs.iter().any(|def| &def.name == *name)
+            })
+            .map(|(name, value)| (name.as_str(), CustomFieldType::Text, value.as_str()));
+        declared.chain(undeclared).collect()
+    }
+
+    /// 检查必填的自定义字段是否已填写，数字字段是否有效
+    pub fn check_custom_fields(&self, defs: &[CustomFieldDef]) -> Result<(), String> {
+        for def in defs {
+            let value = self
+                .custom_fields
+                .get(&def.name)
+                .map_or("", |value| value.trim());
+            if value.is_empty() {
+                if def.required {
+                    return Err(format!("自定义字段 {} 不能为空", def.name));
+                }
+                continue;
+            }
+            match def.field_type {
+                CustomFieldType::Number if value.parse::<f64>().is_err() => {
+                    return Err(format!("自定义字段 {} 不是有效的数字: {}", def.name, value));
+                }
+                CustomFieldType::Date if !is_valid_date(value) => {
+                    return Err(format!(
+                        "自定义字段 {} 不是有效的日期（格式为 YYYY-MM-DD）: {}",
+                        def.name, value
+                    ));
+                }
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    /// 检查截图中的图片格式能否识别
+    pub fn check_images(&self) -> Result<(), String> {
+        for ((_, title), (_, contents)) in
+            SCREENSHOT_FIELDS.into_iter().zip(self.screenshot_groups())
+        {
+            for (index, content) in contents.iter().enumerate() {
+                if let ScreenshotContent::Image(image) = content {
+                    img_util::get_image_dimensions(&image.data)
+                        .map_err(|e| format!("{}第{}项图片无效: {}", title, index + 1, e))?;
+                }
+            }
+        }
+        Ok(())
+    }
 }
 
+/// 是否为有效的日期，格式为 YYYY-MM-DD（与前端日期输入框一致）
+fn is_valid_date(value: &str) -> bool {
+    let parts: Vec<&str> = value.split('-').collect();
+    let [year, month, day] = parts[..] else {
+        return false;
+    };
+    if [(year, 4), (month, 2), (day, 2)]
+        .iter()
+        .any(|(part, len)| part.len() != *len || !part.bytes().all(|b| b.is_ascii_digit()))
+    {
+        return false;
+    }
+    let (Ok(year), Ok(month), Ok(day)) = (
+        year.parse::<u32>(),
+        month.parse::<u32>(),
+        day.parse::<u32>(),
+    ) else {
+        return false;
+    };
+    let leap = year % 4 == 0 && (year % 100 != 0 || year % 400 == 0);
+    let days = match month {
+        1 | 3 | 5 | 7 | 8 | 10 | 12 => 31,
+        4 | 6 | 9 | 11 => 30,
+        2 if leap => 29,
+        2 => 28,
+        _ => return false,
+    };
+    (1..=days).contains(&day)
+}
+
+/// 截图单元格可用宽度（像素）
+const SCREENSHOT_WIDTH_PX: u32 = 520;
+/// 并排图片的最大列数
+const MAX_IMAGE_COLUMNS: usize = 3;
+
 /// 文档内容项，可以是标题或表格
 #[derive(Debug, Clone)]
 pub enum DocumentItem {
@@ -50,6 +427,13 @@ pub enum DocumentItem {
 pub struct RiskTableBuilder {
     data: RiskReportData,
     base_row_height: f32,
+    level_colors: LevelColorConfig,
+    finding_number: usize,
+    image_options: ImageOptimizeOptions,
+    image_stats: RefCell<ImageOptimizeStats>,
+    media: Rc<MediaCache>,
+    variant: ReportVariant,
+    custom_fields: Vec<CustomFieldDef>,
 }
 
 impl RiskTableBuilder {
@@ -58,6 +442,13 @@ impl RiskTableBuilder {
         Self {
             data,
             base_row_height: 500.0,
+            level_colors: LevelColorConfig::default(),
+            finding_number: 1,
+            image_options: ImageOptimizeOptions::default(),
+            image_stats: RefCell::new(ImageOptimizeStats::default()),
+            media: Rc::new(MediaCache::default()),
+            variant: ReportVariant::Original,
+            custom_fields: Vec::new(),
         }
     }
 
@@ -67,17 +458,90 @@ impl RiskTableBuilder {
         self
     }
 
+    /// 设置隐患级别颜色配置
+    pub fn with_level_colors(mut self, level_colors: LevelColorConfig) -> Self {
+        self.level_colors = level_colors;
+        self
+    }
+
+    /// 设置隐患序号，用于图片编号 "图 N-M"
+    pub fn with_finding_number(mut self, number: usize) -> Self {
+        self.finding_number = number;
+        self
+    }
+
+    /// 设置图片预处理配置
+    pub fn with_image_options(mut self, options: ImageOptimizeOptions) -> Self {
+        self.image_options = options;
+        self
+    }
+
+    /// 设置文档共享的图片缓存，相同内容的图片在文档中只嵌入一次
+    pub fn with_media_cache(mut self, media: Rc<MediaCache>) -> Self {
+        self.media = media;
+        self
+    }
+
+    /// 设置报告类型
+    pub fn with_variant(mut self, variant: ReportVariant) -> Self {
+        self.variant = variant;
+        self
+    }
+
+    /// 设置项目声明的自定义字段，按声明顺序显示在基本信息之后
+    pub fn with_custom_fields(mut self, fields: Vec<CustomFieldDef>) -> Self {
+        self.custom_fields = fields;
+        self
+    }
+
+    /// 获取已生成表格的图片预处理统计
+    pub fn image_stats(&self) -> ImageOptimizeStats {
+        *self.image_stats.borrow()
+    }
+
+    /// 创建图片说明段落
+    fn create_caption_paragraph(&self, figure_index: usize, caption: &str) -> Paragraph {
+        Paragraph::new()
+            .add_run(
+                Run::new()
+                    .add_text(format!(
+                        "图 {}-{}：{}",
+                        self.finding_number,
+                        figure_index,
+                        caption.trim()
+                    ))
+                    .size(18)
+                    .fonts(RunFonts::new().ascii("仿宋")),
+            )
+            .align(AlignmentType::Center)
+    }
+
     /// 创建表格标题行 -- 风险隐患
     fn create_table_header(&self) -> TableRow {
-        TableRow::new(vec![TableCell::new()
+        let mut cell = TableCell::new()
             .add_paragraph(
                 Paragraph::new()
-                    .add_run(Run::new().add_text("风险隐患").bold())
+                    .add_run(
+                        Run::new()
+                            .add_text(match self.variant {
+                                ReportVariant::Original => "风险隐患",
+                                ReportVariant::Retest => "风险隐患复测",
+                            })
+                            .bold(),
+                    )
                     .align(AlignmentType::Center),
             )
             .grid_span(4)
-            .vertical_align(VAlignType::Center)])
-        .row_height(self.base_row_height)
+            .vertical_align(VAlignType::Center);
+
+        if let Some(shading) = self
+            .level_colors
+            .shading_for(LevelShadingTarget::Header, &self.data.hazard_level)
+        {
+            cell = cell.shading(shading);
+        }
+
+        TableRow::new(vec![cell]).row_height(self.base_row_height)
     }
 
     /// 创建两列数据行
@@ -88,6 +552,34 @@ impl RiskTableBuilder {
         label2: &str,
         value2: &str,
     ) -> TableRow {
+        self.create_shaded_two_column_row(label1, value1, label2, value2, None)
+    }
+
+    /// 创建两列数据行，第二个值单元格可设置底纹
+    fn create_shaded_two_column_row(
+        &self,
+        label1: &str,
+        value1: &str,
+        label2: &str,
+        value2: &str,
+        value2_shading: Option<Shading>,
+    ) -> TableRow {
+        let mut value2_cell = TableCell::new()
+            .add_paragraph(
+                Paragraph::new()
+                    .add_run(
+                        Run::new()
+                            .add_text(value2)
+                            .fonts(RunFonts::new().ascii("仿宋")),
+                    )
+                    .align(AlignmentType::Center),
+            )
+            .width(2500, WidthType::Dxa)
+            .vertical_align(VAlignType::Center);
+        if let Some(shading) = value2_shading {
+            value2_cell = value2_cell.shading(shading);
+        }
+
         TableRow::new(vec![
             TableCell::new()
                 .add_paragraph(
@@ -117,18 +609,7 @@ impl RiskTableBuilder {
                 )
                 .width(1500, WidthType::Dxa)
                 .vertical_align(VAlignType::Center),
-            TableCell::new()
-                .add_paragraph(
-                    Paragraph::new()
-                        .add_run(
-                            Run::new()
-                                .add_text(value2)
-                                .fonts(RunFonts::new().ascii("仿宋")),
-                        )
-                        .align(AlignmentType::Center),
-                )
-                .width(2500, WidthType::Dxa)
-                .vertical_align(VAlignType::Center),
+            value2_cell,
         ])
         .row_height(self.base_row_height)
     }
@@ -159,6 +640,48 @@ impl RiskTableBuilder {
         .row_height(self.base_row_height)
     }
 
+    /// 创建长文本行（跨4列），按行拆分段落并支持轻量标记，左对齐
+    fn create_rich_text_row(&self, label: &str, value: &str) -> TableRow {
+        let mut value_cell = TableCell::new();
+        for paragraph in markup::render_paragraphs(value) {
+            value_cell = value_cell.add_paragraph(paragraph);
+        }
+
+        TableRow::new(vec![
+            TableCell::new()
+                .add_paragraph(
+                    Paragraph::new()
+                        .add_run(Run::new().add_text(label).bold())
+                        .align(AlignmentType::Center),
+                )
+                .vertical_align(VAlignType::Center),
+            value_cell.grid_span(3).vertical_align(VAlignType::Center),
+        ])
+        .row_height(self.base_row_height)
+    }
+
+    /// 创建自定义字段行，单行字段两两并排，多行文本独占一行
+    fn create_custom_field_rows(&self) -> Vec<TableRow> {
+        let mut rows = Vec::new();
+        let mut pending: Option<(&str, &str)> = None;
+        for (name, field_type, value) in self.data.custom_field_values(&self.custom_fields) {
+            if field_type == CustomFieldType::Multiline {
+                rows.push(self.create_rich_text_row(name, value));
+                continue;
+            }
+            match pending.take() {
+                Some((first_name, first_value)) => {
+                    rows.push(self.create_two_column_row(first_name, first_value, name, value))
+                }
+                None => pending = Some((name, value)),
+            }
+        }
+        if let Some((name, value)) = pending {
+            rows.push(self.create_single_column_row(name, value));
+        }
+        rows
+    }
+
     /// 创建图片行（标题行）
     fn create_image_title_row(&self, title: &str) -> TableRow {
         TableRow::new(vec![TableCell::new()
@@ -172,62 +695,284 @@ impl RiskTableBuilder {
         .row_height(self.base_row_height)
     }
 
-    /// 创建截图内容行（支持文本和图片混合）
-    fn create_screenshot_content_row(&self, contents: &[ScreenshotContent]) -> TableRow {
+    /// 创建图片，按尺寸策略计算显示大小
+    fn create_image_run(&self, image: &ScreenshotImage, max_width_px: u32) -> Run {
+        let dimensions = img_util::get_image_dimensions(&image.data);
+        let (width_emu, height_emu) = match &dimensions {
+            Ok((orig_width, orig_height)) => img_util::calculate_display_size(
+                image.sizing,
+                max_width_px,
+                image.max_height,
+                *orig_width,
+                *orig_height,
+            ),
+            Err(e) => {
+                log::warn!("无法获取图片尺寸: {}, 使用默认比例", e);
+                // 如果无法获取图片尺寸，使用默认比例(16:9)
+                let target_width_emu = max_width_px * img_util::EMU_PER_PIXEL;
+                (target_width_emu, target_width_emu * 9 / 16)
+            }
+        };
+
+        // 相同内容、相同显示尺寸的图片引用已嵌入的媒体文件
+        let hash = content_hash(&image.data);
+        if let Some(pic) = self.media.get(&hash, width_emu, height_emu) {
+            self.image_stats.borrow_mut().deduplicated += 1;
+            return Run::new().add_image(pic);
+        }
+
+        // 按显示尺寸预处理，WebP/BMP/GIF 转换为PNG
+        let prepared =
+            match img_util::optimize_image(&image.data, width_emu, height_emu, &self.image_options)
+            {
+                Ok(prepared) => prepared,
+                Err(e) => {
+                    // 无法解码的图片以提示文本代替
+                    log::error!("图片处理失败: {}", e);
+                    return Run::new()
+                        .add_text(format!("【图片无法解析：{}】", e))
+                        .color("FF0000")
+                        .fonts(RunFonts::new().ascii("仿宋"));
+                }
+            };
+        self.image_stats
+            .borrow_mut()
+            .record(image.data.len(), &prepared);
+
+        Run::new().add_image(
+            self.media
+                .insert(hash, prepared.data, width_emu, height_emu),
+        )
+    }
+
+    /// 创建并排图片网格（无边框嵌套表格）
+    fn create_image_grid(
+        &self,
+        images: &[&ScreenshotImage],
+        columns: usize,
+        width_px: u32,
+        figure_index: &mut usize,
+    ) -> Table {
+        // 1像素约等于15 Dxa
+        let column_width_dxa = width_px as usize * 15 / columns;
+        let image_width_px = width_px / columns as u32 - 12;
+
+        let rows = images
+            .chunks(columns)
+            .map(|chunk| {
+                let mut cells: Vec<TableCell> = chunk
+                    .iter()
+                    .map(|image| {
+                        let mut cell = TableCell::new().add_paragraph(
+                            Paragraph::new()
+                                .add_run(self.create_image_run(image, image_width_px))
+                                .align(AlignmentType::Center),
+                        );
+                        if let Some(caption) =
+                            image.caption.as_deref().filter(|c| !c.trim().is_empty())
+                        {
+                            *figure_index += 1;
+                            cell = cell.add_paragraph(
+                                self.create_caption_paragraph(*figure_index, caption),
+                            );
+                        }
+                        cell.width(column_width_dxa, WidthType::Dxa)
+                            .vertical_align(VAlignType::Top)
+                    })
+                    .collect();
+
+                // 补齐最后一行的空单元格
+                while cells.len() < columns {
+                    cells.push(
+                        TableCell::new()
+                            .add_paragraph(Paragraph::new())
+                            .width(column_width_dxa, WidthType::Dxa),
+                    );
+                }
+                TableRow::new(cells)
+            })
+            .collect();
+
+        Table::without_borders(rows)
+            .set_grid(vec![column_width_dxa; columns])
+            .width(column_width_dxa * columns, WidthType::Dxa)
+            .align(TableAlignmentType::Center)
+    }
+
+    /// 创建截图内容行（跨4列）
+    fn create_screenshot_content_row(
+        &self,
+        contents: &[ScreenshotContent],
+        columns: usize,
+        figure_index: &mut usize,
+    ) -> TableRow {
+        let cell =
+            self.create_screenshot_cell(contents, columns, SCREENSHOT_WIDTH_PX, figure_index);
+        TableRow::new(vec![cell.grid_span(4)]).row_height(self.base_row_height * 2.0)
+    }
+
+    /// 创建截图内容单元格（支持文本、图片和代码块混合）
+    /// columns: 连续图片并排显示的列数
+    /// width_px: 单元格内图片的可用宽度
+    /// figure_index: 当前隐患内已使用的图片编号，带说明的图片会递增该编号
+    fn create_screenshot_cell(
+        &self,
+        contents: &[ScreenshotContent],
+        columns: usize,
+        width_px: u32,
+        figure_index: &mut usize,
+    ) -> TableCell {
+        let columns = columns.clamp(1, MAX_IMAGE_COLUMNS);
+        let mut blocks = Vec::new();
         let mut paragraph = Paragraph::new();
+        let mut has_runs = false;
 
         if contents.is_empty() {
             // 如果没有内容，显示占位符
             paragraph =
                 paragraph.add_run(Run::new().add_text("").fonts(RunFonts::new().ascii("仿宋")));
-        } else {
-            // 处理所有内容项
-            for (index, content) in contents.iter().enumerate() {
-                match content {
-                    ScreenshotContent::Text(text) => {
-                        // 添加文本内容
-                        paragraph = paragraph.add_run(
-                            Run::new()
-                                .add_text(text)
-                                .fonts(RunFonts::new().ascii("仿宋")),
-                        );
-                    }
-                    ScreenshotContent::Image(image_data) => {
-                        // 添加图片，保持宽度为520*9525并按原图比例计算高度
-                        let target_width_emu = 520 * 9525;
-                        let (width_emu, height_emu) =
-                            match img_util::get_image_dimensions(image_data) {
-                                Ok((orig_width, orig_height)) => {
-                                    img_util::calculate_proportional_size(
-                                        target_width_emu,
-                                        orig_width,
-                                        orig_height,
-                                    )
-                                }
-                                Err(e) => {
-                                    log::warn!("无法获取图片尺寸: {}, 使用默认比例", e);
-                                    // 如果无法获取图片尺寸，使用默认比例(16:9)
-                                    (target_width_emu, target_width_emu * 9 / 16)
-                                }
-                            };
-
-                        let pic = Pic::new(image_data).size(width_emu, height_emu);
-                        paragraph = paragraph.add_run(Run::new().add_image(pic));
+        }
+
+        // 处理所有内容项
+        let mut index = 0;
+        while index < contents.len() {
+            match &contents[index] {
+                ScreenshotContent::Text(text) => {
+                    // 在内容项之间添加换行
+                    if has_runs {
+                        paragraph =
+                            paragraph.add_run(Run::new().add_break(BreakType::TextWrapping));
                     }
+                    // 添加文本内容
+                    paragraph = paragraph.add_run(
+                        Run::new()
+                            .add_text(text)
+                            .fonts(RunFonts::new().ascii("仿宋")),
+                    );
+                    has_runs = true;
                 }
+                ScreenshotContent::Image(image) => {
+                    // 收集连续的图片，多列布局时并排显示
+                    let images: Vec<&ScreenshotImage> = contents[index..]
+                        .iter()
+                        .map_while(|content| match content {
+                            ScreenshotContent::Image(image) => Some(image),
+                            _ => None,
+                        })
+                        .collect();
 
-                // 在内容项之间添加换行（除了最后一项）
-                if index < contents.len() - 1 {
-                    paragraph = paragraph.add_run(Run::new().add_break(BreakType::TextWrapping));
+                    if columns > 1 && images.len() > 1 {
+                        if has_runs {
+                            blocks.push(TableCellContent::Paragraph(std::mem::replace(
+                                &mut paragraph,
+                                Paragraph::new(),
+                            )));
+                            has_runs = false;
+                        }
+                        blocks.push(TableCellContent::Table(self.create_image_grid(
+                            &images,
+                            columns,
+                            width_px,
+                            figure_index,
+                        )));
+                        index += images.len();
+                        continue;
+                    }
+
+                    if has_runs {
+                        paragraph =
+                            paragraph.add_run(Run::new().add_break(BreakType::TextWrapping));
+                    }
+                    paragraph = paragraph.add_run(self.create_image_run(image, width_px));
+                    has_runs = true;
+
+                    // 图片说明独占段落，显示在图片下方
+                    if let Some(caption) = image.caption.as_deref().filter(|c| !c.trim().is_empty())
+                    {
+                        *figure_index += 1;
+                        blocks.push(TableCellContent::Paragraph(std::mem::replace(
+                            &mut paragraph,
+                            Paragraph::new(),
+                        )));
+                        blocks.push(TableCellContent::Paragraph(
+                            self.create_caption_paragraph(*figure_index, caption),
+                        ));
+                        has_runs = false;
+                    }
+                }
+                ScreenshotContent::Code(block) => {
+                    // 代码块独占段落，先结束当前段落
+                    if has_runs {
+                        blocks.push(TableCellContent::Paragraph(std::mem::replace(
+                            &mut paragraph,
+                            Paragraph::new(),
+                        )));
+                        has_runs = false;
+                    }
+                    blocks.extend(
+                        code_block::render_paragraphs(block)
+                            .into_iter()
+                            .map(TableCellContent::Paragraph),
+                    );
                 }
             }
+            index += 1;
         }
 
-        TableRow::new(vec![TableCell::new()
-            .add_paragraph(paragraph)
-            .grid_span(4)
-            .vertical_align(VAlignType::Center)])
-        .row_height(self.base_row_height * 2.0)
+        // 单元格必须以段落结尾
+        if has_runs || !matches!(blocks.last(), Some(TableCellContent::Paragraph(_))) {
+            blocks.push(TableCellContent::Paragraph(paragraph));
+        }
+
+        let mut cell = TableCell::new();
+        for block in blocks {
+            cell = match block {
+                TableCellContent::Table(table) => cell.add_table(table),
+                TableCellContent::Paragraph(paragraph) => cell.add_paragraph(paragraph),
+                _ => cell,
+            };
+        }
+
+        cell.vertical_align(VAlignType::Center)
+    }
+
+    /// 创建并排的原始证据与复测证据行，左右各占2列
+    fn create_retest_evidence_rows(&self, figure_index: &mut usize) -> Vec<TableRow> {
+        let title_cell = |title: &str| {
+            TableCell::new()
+                .add_paragraph(
+                    Paragraph::new()
+                        .add_run(Run::new().add_text(title).bold())
+                        .align(AlignmentType::Center),
+                )
+                .grid_span(2)
+                .width(4000, WidthType::Dxa)
+                .vertical_align(VAlignType::Center)
+        };
+        let width_px = SCREENSHOT_WIDTH_PX / 2;
+        let columns = self.data.evidence_columns.clamp(1, 2);
+        let original = self.create_screenshot_cell(
+            &self.data.evidence_screenshots,
+            columns,
+            width_px,
+            figure_index,
+        );
+        let retest = self.create_screenshot_cell(
+            &self.data.retest_screenshots,
+            columns,
+            width_px,
+            figure_index,
+        );
+
+        vec![
+            TableRow::new(vec![title_cell("原始证据"), title_cell("复测证据")])
+                .row_height(self.base_row_height),
+            TableRow::new(vec![
+                original.grid_span(2).width(4000, WidthType::Dxa),
+                retest.grid_span(2).width(4000, WidthType::Dxa),
+            ])
+            .row_height(self.base_row_height * 2.0),
+        ]
     }
 
     /// 创建备注行
@@ -279,12 +1024,16 @@ impl RiskTableBuilder {
 
         table = table.add_row(self.create_single_column_row("隐患URL", &self.data.target));
 
-        table = table.add_row(self.create_two_column_row(
-            "隐患类型",
-            &self.data.vul_name,
-            "隐患级别",
-            &self.data.hazard_level,
-        ));
+        table = table.add_row(
+            self.create_shaded_two_column_row(
+                "隐患类型",
+                &self.data.vul_name,
+                "隐患级别",
+                &self.data.hazard_level,
+                self.level_colors
+                    .shading_for(LevelShadingTarget::Cell, &self.data.hazard_level),
+            ),
+        );
 
         table = table.add_row(self.create_two_column_row(
             "预警级别",
@@ -321,19 +1070,45 @@ impl RiskTableBuilder {
             &self.data.report_time,
         ));
 
-        table = table
-            .add_row(self.create_single_column_row("问题描述", &self.data.problem_description));
+        for row in self.create_custom_field_rows() {
+            table = table.add_row(row);
+        }
 
         table =
-            table.add_row(self.create_single_column_row("整改建议", &self.data.vul_modify_repair));
+            table.add_row(self.create_rich_text_row("问题描述", &self.data.problem_description));
+
+        table = table.add_row(self.create_rich_text_row("整改建议", &self.data.vul_modify_repair));
 
-        // 添加证据截图
-        table = table.add_row(self.create_image_title_row("证据截图"));
-        table = table.add_row(self.create_screenshot_content_row(&self.data.evidence_screenshots));
+        // 图片编号在同一隐患内连续递增
+        let mut figure_index = 0;
+
+        if self.variant == ReportVariant::Retest {
+            table = table.add_row(self.create_two_column_row(
+                "修复状态",
+                self.data.remediation_status.label(),
+                "复测日期",
+                &self.data.retest_date,
+            ));
+            for row in self.create_retest_evidence_rows(&mut figure_index) {
+                table = table.add_row(row);
+            }
+        } else {
+            // 添加证据截图
+            table = table.add_row(self.create_image_title_row("证据截图"));
+            table = table.add_row(self.create_screenshot_content_row(
+                &self.data.evidence_screenshots,
+                self.data.evidence_columns,
+                &mut figure_index,
+            ));
+        }
 
-        // 添加工信域名备案截图
+        // 添加工信域名备案截图，复测报告同样保留
         table = table.add_row(self.create_image_title_row("工信域名备案截图"));
-        table = table.add_row(self.create_screenshot_content_row(&self.data.filing_screenshots));
+        table = table.add_row(self.create_screenshot_content_row(
+            &self.data.filing_screenshots,
+            1,
+            &mut figure_index,
+        ));
 
         // 添加备注
         table = table.add_row(self.create_remark_row());
@@ -350,6 +1125,10 @@ impl RiskTableBuilder {
 pub struct DocxBuilder {
     items: Vec<DocumentItem>,
     title_font_size: usize,
+    level_colors: LevelColorConfig,
+    image_options: ImageOptimizeOptions,
+    variant: ReportVariant,
+    custom_fields: Vec<CustomFieldDef>,
 }
 
 impl Default for DocxBuilder {
@@ -364,6 +1143,10 @@ impl DocxBuilder {
         Self {
             items: Vec::new(),
             title_font_size: 24,
+            level_colors: LevelColorConfig::default(),
+            image_options: ImageOptimizeOptions::default(),
+            variant: ReportVariant::Original,
+            custom_fields: Vec::new(),
         }
     }
 
@@ -373,6 +1156,30 @@ impl DocxBuilder {
         self
     }
 
+    /// 设置隐患级别颜色配置
+    pub fn with_level_colors(mut self, level_colors: LevelColorConfig) -> Self {
+        self.level_colors = level_colors;
+        self
+    }
+
+    /// 设置图片预处理配置
+    pub fn with_image_options(mut self, options: ImageOptimizeOptions) -> Self {
+        self.image_options = options;
+        self
+    }
+
+    /// 设置报告类型，复测报告中并排显示原始证据与复测证据
+    pub fn with_variant(mut self, variant: ReportVariant) -> Self {
+        self.variant = variant;
+        self
+    }
+
+    /// 设置项目声明的自定义字段
+    pub fn with_custom_fields(mut self, fields: Vec<CustomFieldDef>) -> Self {
+        self.custom_fields = fields;
+        self
+    }
+
     /// 添加标题
     pub fn add_title(mut self, title: String) -> Self {
         self.items.push(DocumentItem::Title(title));
@@ -414,7 +1221,15 @@ impl DocxBuilder {
 
     /// 构建完整文档
     pub fn build(self) -> Docx {
+        self.build_with_stats().0
+    }
+
+    /// 构建完整文档，同时返回图片预处理统计
+    pub fn build_with_stats(self) -> (Docx, ImageOptimizeStats) {
         let mut docx = Docx::new();
+        let mut finding_number = 0;
+        let mut image_stats = ImageOptimizeStats::default();
+        let media = Rc::new(MediaCache::default());
 
         for (index, item) in self.items.iter().enumerate() {
             match item {
@@ -427,11 +1242,19 @@ impl DocxBuilder {
                 }
                 DocumentItem::RiskTable(data, row_height) => {
                     // 创建表格，如果有自定义行高则使用，否则使用默认值
-                    let mut table_builder = RiskTableBuilder::new((**data).clone());
+                    finding_number += 1;
+                    let mut table_builder = RiskTableBuilder::new((**data).clone())
+                        .with_level_colors(self.level_colors.clone())
+                        .with_finding_number(finding_number)
+                        .with_image_options(self.image_options.clone())
+                        .with_media_cache(media.clone())
+                        .with_variant(self.variant)
+                        .with_custom_fields(self.custom_fields.clone());
                     if let Some(height) = row_height {
                         table_builder = table_builder.with_base_row_height(*height);
                     }
                     let table = table_builder.build_table();
+                    image_stats.merge(&table_builder.image_stats());
                     docx = docx.add_table(table);
 
                     // 在表格后添加空行（除非是最后一项）
@@ -448,14 +1271,296 @@ impl DocxBuilder {
             }
         }
 
-        docx
+        (docx, image_stats)
     }
 
-    /// 保存文档到文件
-    pub fn save_to_file(self, path: &str) -> Result<(), Box<dyn std::error::Error>> {
+    /// 保存文档到文件，返回图片预处理统计
+    pub fn save_to_file(
+        self,
+        path: &str,
+    ) -> Result<ImageOptimizeStats, Box<dyn std::error::Error>> {
         let file = std::fs::File::create(path)?;
-        let docx = self.build();
-        docx.build().pack(file)?;
-        Ok(())
+        let (docx, image_stats) = self.build_with_stats();
+        media::pack_docx(docx.build(), file)?;
+        Ok(image_stats)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_screenshot_image_deserialization() {
+        // 旧格式：图片内容为字节数组
+        let legacy: ScreenshotContent =
+            serde_json::from_str(r#"{"type":"image","content":[137,80,78,71]}"#).unwrap();
+        assert!(matches!(
+            legacy,
+            ScreenshotContent::Image(ScreenshotImage { ref data, caption: None, .. }) if data == &[137, 80, 78, 71]
+        ));
+
+        // 新格式：包含图片说明
+        let captioned: ScreenshotContent = serde_json::from_str(
+            r#"{"type":"image","content":{"data":[1,2],"caption":"登录页面"}}"#,
+        )
+        .unwrap();
+        assert!(matches!(
+            captioned,
+            ScreenshotContent::Image(ScreenshotImage { caption: Some(ref c), .. }) if c == "登录页面"
+        ));
+    }
+
+    #[test]
+    fn test_level_shading() {
+        let document = |target, level: &str| {
+            let report = RiskReportData {
+                report_id: "1".to_string(),
+                hazard_level: level.to_string(),
+                ..Default::default()
+            };
+            let xml = DocxBuilder::new()
+                .with_level_colors(LevelColorConfig {
+                    target,
+                    ..Default::default()
+                })
+                .add_risk_table(report)
+                .build()
+                .build()
+                .document;
+            String::from_utf8(xml).unwrap()
+        };
+
+        for (level, color) in [
+            ("严重", "FF0000"),
+            ("高危", "FFA500"),
+            ("中危", "FFFF00"),
+            ("低危", "9DC3E6"),
+        ] {
+            let fill = format!(r#"w:fill="{}""#, color);
+            // 隐患级别单元格着色：底纹位于隐患级别标签之后
+            let xml = document(LevelShadingTarget::Cell, level);
+            assert_eq!(xml.matches(&fill).count(), 1, "{}", level);
+            assert!(xml.find(&fill) > xml.find("隐患级别"), "{}", level);
+
+            // 标题行着色：底纹位于第一个数据行之前
+            let xml = document(LevelShadingTarget::Header, level);
+            assert_eq!(xml.matches(&fill).count(), 1, "{}", level);
+            assert!(xml.find(&fill) < xml.find("隐患编号"), "{}", level);
+
+            assert!(!document(LevelShadingTarget::None, level).contains(&fill));
+        }
+
+        // 未配置颜色的级别不着色
+        assert!(!document(LevelShadingTarget::Cell, "提示").contains("<w:shd"));
+    }
+
+    #[test]
+    fn test_duplicate_images_embedded_once() {
+        let mut png = std::io::Cursor::new(Vec::new());
+        image::RgbImage::from_pixel(64, 32, image::Rgb([200, 30, 30]))
+            .write_to(&mut png, image::ImageFormat::Png)
+            .unwrap();
+        let screenshot = ScreenshotContent::Image(ScreenshotImage {
+            data: png.into_inner(),
+            ..Default::default()
+        });
+        let report = RiskReportData {
+            report_id: "1".to_string(),
+            filing_screenshots: vec![screenshot.clone()],
+            evidence_screenshots: vec![screenshot.clone()],
+            ..Default::default()
+        };
+
+        let (docx, stats) = DocxBuilder::new()
+            .add_risk_table(report.clone())
+            .add_risk_table(report)
+            .build_with_stats();
+        assert_eq!(stats.images, 1);
+        assert_eq!(stats.deduplicated, 3);
+        assert_eq!(docx.build().media.len(), 1);
+
+        // 以不同尺寸出现的同一图片按各自尺寸分别嵌入（宽于单元格的图片缩放至单元格宽度）
+        let mut png = std::io::Cursor::new(Vec::new());
+        image::RgbImage::from_pixel(1600, 800, image::Rgb([200, 30, 30]))
+            .write_to(&mut png, image::ImageFormat::Png)
+            .unwrap();
+        let screenshot = ScreenshotContent::Image(ScreenshotImage {
+            data: png.into_inner(),
+            ..Default::default()
+        });
+        let report = RiskReportData {
+            report_id: "1".to_string(),
+            evidence_columns: 2,
+            evidence_screenshots: vec![screenshot.clone(), screenshot.clone()],
+            filing_screenshots: vec![screenshot],
+            ..Default::default()
+        };
+        let (docx, stats) = DocxBuilder::new().add_risk_table(report).build_with_stats();
+        assert_eq!(stats.images, 2);
+        assert_eq!(stats.deduplicated, 1);
+        assert_eq!(docx.build().media.len(), 2);
+    }
+
+    #[test]
+    fn test_jpeg_media_extension() {
+        let mut jpeg = std::io::Cursor::new(Vec::new());
+        image::RgbImage::from_pixel(64, 32, image::Rgb([200, 30, 30]))
+            .write_to(&mut jpeg, image::ImageFormat::Jpeg)
+            .unwrap();
+        let report = RiskReportData {
+            report_id: "1".to_string(),
+            evidence_screenshots: vec![ScreenshotContent::Image(ScreenshotImage {
+                data: jpeg.into_inner(),
+                ..Default::default()
+            })],
+            ..Default::default()
+        };
+
+        let path = std::env::temp_dir().join(format!("rg_jpeg_media_{}.docx", std::process::id()));
+        DocxBuilder::new()
+            .add_risk_table(report)
+            .save_to_file(path.to_str().unwrap())
+            .unwrap();
+        let mut archive = zip::ZipArchive::new(std::fs::File::open(&path).unwrap()).unwrap();
+        let media: Vec<String> = archive
+            .file_names()
+            .filter(|name| name.starts_with("word/media/") && !name.ends_with('/'))
+            .map(str::to_string)
+            .collect();
+        let mut rels = String::new();
+        std::io::Read::read_to_string(
+            &mut archive.by_name("word/_rels/document.xml.rels").unwrap(),
+            &mut rels,
+        )
+        .unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        // JPEG 媒体文件使用 .jpeg 扩展名，关系文件引用同步更新
+        assert_eq!(media.len(), 1);
+        assert!(media[0].ends_with(".jpeg"));
+        assert!(rels.contains(media[0].trim_start_matches("word/")));
+        assert!(!rels.contains(".png"));
+    }
+
+    #[test]
+    fn test_custom_fields() {
+        let defs = vec![
+            CustomFieldDef {
+                name: "端口".to_string(),
+                field_type: CustomFieldType::Number,
+                required: true,
+            },
+            CustomFieldDef {
+                name: "系统名称".to_string(),
+                field_type: CustomFieldType::Text,
+                required: false,
+            },
+        ];
+        let mut report = RiskReportData {
+            report_id: "1".to_string(),
+            ..Default::default()
+        };
+        assert!(report.check_custom_fields(&defs).is_err());
+        report
+            .custom_fields
+            .insert("端口".to_string(), "abc".to_string());
+        assert!(report.check_custom_fields(&defs).is_err());
+        report
+            .custom_fields
+            .insert("端口".to_string(), "8080".to_string());
+        report
+            .custom_fields
+            .insert("CVE编号".to_string(), "CVE-2024-0001".to_string());
+        assert!(report.check_custom_fields(&defs).is_ok());
+
+        // 声明的字段按顺序在前，未声明但有值的字段在后
+        let names: Vec<&str> = report
+            .custom_field_values(&defs)
+            .into_iter()
+            .map(|(name, _, _)| name)
+            .collect();
+        assert_eq!(names, vec!["端口", "系统名称", "CVE编号"]);
+
+        let xml = DocxBuilder::new()
+            .with_custom_fields(defs)
+            .add_risk_table(report)
+            .build()
+            .build()
+            .document;
+        let xml = String::from_utf8(xml).unwrap();
+        for text in ["端口", "8080", "系统名称", "CVE-2024-0001"] {
+            assert!(xml.contains(text), "{}", text);
+        }
+    }
+
+    #[test]
+    fn test_custom_date_field() {
+        let defs = vec![CustomFieldDef {
+            name: "上线日期".to_string(),
+            field_type: CustomFieldType::Date,
+            required: false,
+        }];
+        let mut report = RiskReportData::default();
+        for (value, valid) in [
+            ("2024-02-29", true),
+            ("2023-02-29", false),
+            ("2024-13-01", false),
+            ("2024/01/01", false),
+            ("2024-1-01", false),
+            ("下周一", false),
+        ] {
+            report
+                .custom_fields
+                .insert("上线日期".to_string(), value.to_string());
+            assert_eq!(
+                report.check_custom_fields(&defs).is_ok(),
+                valid,
+                "{}",
+                value
+            );
+        }
+    }
+
+    #[test]
+    fn test_retest_variant() {
+        let report = RiskReportData {
+            report_id: "1".to_string(),
+            remediation_status: RemediationStatus::PartiallyFixed,
+            retest_date: "2024-05-01".to_string(),
+            evidence_screenshots: vec![ScreenshotContent::Text("修复前".to_string())],
+            retest_screenshots: vec![ScreenshotContent::Text("修复后".to_string())],
+            filing_screenshots: vec![ScreenshotContent::Text("备案".to_string())],
+            ..Default::default()
+        };
+
+        let document = |variant| {
+            let xml = DocxBuilder::new()
+                .with_variant(variant)
+                .add_risk_table(report.clone())
+                .build()
+                .build()
+                .document;
+            String::from_utf8(xml).unwrap()
+        };
+        let retest = document(ReportVariant::Retest);
+        for text in [
+            "风险隐患复测",
+            "部分修复",
+            "2024-05-01",
+            "原始证据",
+            "修复前",
+            "修复后",
+            "工信域名备案截图",
+            "备案",
+        ] {
+            assert!(retest.contains(text), "{}", text);
+        }
+        // 备案截图位于复测证据之后
+        assert!(retest.find("修复后").unwrap() < retest.find("工信域名备案截图").unwrap());
+
+        let original = document(ReportVariant::Original);
+        assert!(original.contains("工信域名备案截图"));
+        assert!(!original.contains("修复后"));
     }
 }
@@ -0,0 +1,157 @@
+use super::RiskReportData;
+use serde::de::Error as _;
+use serde_json::{Map, Value};
+use std::sync::LazyLock;
+
+/// 当前报告数据结构版本，RiskReportData 结构变化时递增并添加对应的升级步骤
+/// 1: 初始版本，全部为文本字段，截图图片为字节数组
+/// 2: 增加证据截图列数，截图图片支持说明和尺寸策略
+/// 3: 增加修复状态、复测日期和复测证据截图
+/// 4: 增加自定义字段
+pub const SCHEMA_VERSION: u32 = 4;
+
+/// 报告数据中记录结构版本的字段，缺失时视为版本 1
+pub const VERSION_FIELD: &str = "schema_version";
+
+/// 升级步骤，第 N 项将版本 N+1 的数据升级到版本 N+2
+const MIGRATIONS: [fn(&mut Map<String, Value>); (SCHEMA_VERSION - 1) as usize] =
+    [migrate_v1_to_v2, migrate_v2_to_v3, migrate_v3_to_v4];
+
+/// 版本 1 -> 2：补充证据截图列数，字节数组形式的图片转换为图片对象
+fn migrate_v1_to_v2(report: &mut Map<String, Value>) {
+    report.entry("evidence_columns").or_insert(Value::from(0));
+    for field in ["evidence_screenshots", "filing_screenshots"] {
+        let Some(Value::Array(contents)) = report.get_mut(field) else {
+            continue;
+        };
+        for content in contents {
+            if content["type"] == "image" && content["content"].is_array() {
+                let data = content["content"].take();
+                content["content"] = serde_json::json!({ "data": data });
+            }
+        }
+    }
+}
+
+/// 版本 2 -> 3：补充复测相关字段
+fn migrate_v2_to_v3(report: &mut Map<String, Value>) {
+    report
+        .entry("remediation_status")
+        .or_insert(Value::from("unfixed"));
+    report.entry("retest_date").or_insert(Value::from(""));
+    report
+        .entry("retest_screenshots")
+        .or_insert(Value::Array(Vec::new()));
+}
+
+/// 版本 3 -> 4：补充自定义字段
+fn migrate_v3_to_v4(report: &mut Map<String, Value>) {
+    report
+        .entry("custom_fields")
+        .or_insert(Value::Object(Map::new()));
+}
+
+/// 报告中的文本字段名，由默认报告的序列化结果得出，只计算一次
+static TEXT_FIELDS: LazyLock<Vec<String>> =
+    LazyLock::new(|| match serde_json::to_value(RiskReportData::default()) {
+        Ok(Value::Object(defaults)) => defaults
+            .into_iter()
+            .filter(|(_, default)| default.is_string())
+            .map(|(field, _)| field)
+            .collect(),
+        _ => Vec::new(),
+    });
+
+/// 宽松处理文本字段：null 视为空文本，数字和布尔值转换为文本
+fn normalize_text_fields(report: &mut Map<String, Value>) {
+    for field in TEXT_FIELDS.iter() {
+        let Some(value) = report.get_mut(field) else {
+            continue;
+        };
+        match value {
+            Value::Null => *value = Value::String(String::new()),
+            Value::Number(_) | Value::Bool(_) => *value = Value::String(value.to_string()),
+            _ => {}
+        }
+    }
+}
+
+/// 将任意版本的报告 JSON 升级为当前结构
+pub fn upgrade(value: Value) -> Result<Value, serde_json::Error> {
+    let Value::Object(mut report) = value else {
+        return Err(serde_json::Error::custom("报告数据不是对象"));
+    };
+    let version = match report.get(VERSION_FIELD) {
+        None | Some(Value::Null) => 1,
+        Some(version) => version
+            .as_u64()
+            .and_then(|version| u32::try_from(version).ok())
+            .ok_or_else(|| serde_json::Error::custom(format!("报告数据版本无效: {}", version)))?,
+    };
+    if version > SCHEMA_VERSION {
+        return Err(serde_json::Error::custom(format!(
+            "报告数据版本 {} 高于当前支持的版本 {}，请升级应用",
+            version, SCHEMA_VERSION
+        )));
+    }
+
+    for migration in MIGRATIONS.iter().skip(version.saturating_sub(1) as usize) {
+        migration(&mut report);
+    }
+    normalize_text_fields(&mut report);
+    report.insert(VERSION_FIELD.to_string(), Value::from(SCHEMA_VERSION));
+    Ok(Value::Object(report))
+}
+
+/// 解析任意版本的报告 JSON
+pub fn from_value(value: Value) -> Result<RiskReportData, serde_json::Error> {
+    serde_json::from_value(upgrade(value)?)
+}
+
+/// 解析任意版本的报告 JSON 字节
+pub fn from_slice(data: &[u8]) -> Result<RiskReportData, serde_json::Error> {
+    from_value(serde_json::from_slice(data)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::report::{RemediationStatus, ScreenshotContent};
+
+    #[test]
+    fn test_upgrade_v1_report() {
+        let report = from_value(serde_json::json!({
+            "id": "a",
+            "report_id": 1024,
+            "hazard_level": null,
+            "evidence_screenshots": [{ "type": "image", "content": [1, 2, 3] }],
+            "filing_screenshots": [{ "type": "text", "content": "备案" }],
+        }))
+        .unwrap();
+        assert_eq!(report.report_id, "1024");
+        assert_eq!(report.hazard_level, "");
+        assert_eq!(report.remediation_status, RemediationStatus::Unfixed);
+        assert_eq!(report.schema_version, SCHEMA_VERSION);
+        assert!(matches!(
+            &report.evidence_screenshots[0],
+            ScreenshotContent::Image(image) if image.data == [1, 2, 3]
+        ));
+    }
+
+    #[test]
+    fn test_upgrade_keeps_current_fields() {
+        let current = RiskReportData {
+            id: "a".to_string(),
+            remediation_status: RemediationStatus::Fixed,
+            evidence_columns: 2,
+            schema_version: SCHEMA_VERSION,
+            ..Default::default()
+        };
+        let upgraded = from_value(serde_json::to_value(&current).unwrap()).unwrap();
+        assert_eq!(upgraded.remediation_status, RemediationStatus::Fixed);
+        assert_eq!(upgraded.evidence_columns, 2);
+
+        let future = serde_json::json!({ "id": "a", "schema_version": SCHEMA_VERSION + 1 });
+        assert!(from_value(future).is_err());
+    }
+}